@@ -0,0 +1,52 @@
+//! Packing benchmarks, run with `cargo bench --bench packing`
+//!
+//! Compares the lookup table packing against the per pixel masking it replaced, on a full screen image.
+
+use std::{hint::black_box, time::{Duration, Instant}};
+
+use image::{GrayImage, Luma};
+use ws_1in5_i2c::{images, OLED_WIDTH, OLED_HEIGHT};
+
+const ITERATIONS: u32 = 200;
+
+/// The per pixel packing `get_buffer` used before the lookup tables
+fn pack_per_pixel(image: &GrayImage) -> Vec<u8> {
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let mut buf: Vec<u8> = vec![0xff; (width / 2) * height];
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let (x, y, pixel) = (x as usize, y as usize, pixel.0[0]);
+
+        let addr = x / 2 + y * (width / 2);
+        let color = pixel % 16;
+        let data: u8 = buf[addr] & ((!0xf0u8).rotate_right((x as u32 % 2) * 4));
+        buf[addr] &= data | ((color << 4) >> ((x % 2) * 4));
+    }
+    buf
+}
+
+fn bench<F: FnMut() -> Vec<u8>>(name: &str, mut f: F) -> Duration {
+    // Warm up
+    for _ in 0..ITERATIONS / 10 {
+        black_box(f());
+    }
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    let per_frame = start.elapsed() / ITERATIONS;
+    println!("{:<24} {:>10.1?} per frame", name, per_frame);
+    per_frame
+}
+
+fn main() {
+    let image = GrayImage::from_fn(OLED_WIDTH as u32, OLED_HEIGHT as u32, |x, y| Luma([((x ^ y) % 16) as u8]));
+
+    assert_eq!(pack_per_pixel(&image), images::pack(&image));
+
+    let baseline = bench("per pixel", || pack_per_pixel(black_box(&image)));
+    let pack = bench("images::pack", || images::pack(black_box(&image)));
+
+    println!();
+    println!("images::pack is {:.1}x faster", baseline.as_secs_f64() / pack.as_secs_f64());
+}
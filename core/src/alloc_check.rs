@@ -0,0 +1,51 @@
+//! Checking the render loop doesn't allocate
+
+use std::{alloc::{GlobalAlloc, Layout, System}, cell::Cell};
+
+thread_local! {
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// A global allocator which counts allocations on each thread, for finding allocations in a render loop
+///
+/// Install it in the application, then turn on [`crate::Driver::with_allocation_check`] so debug builds assert that
+/// [`crate::Driver::present_from`] doesn't allocate.
+///
+/// ```no_run
+/// use ws_1in5_i2c_core::alloc_check::CountingAllocator;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator;
+/// ```
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+fn count() {
+    // Fails while the thread is being torn down, those allocations aren't interesting
+    let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+}
+
+/// Number of allocations made on this thread, always 0 unless [`CountingAllocator`] is installed
+pub fn allocations() -> u64 {
+    ALLOCATIONS.try_with(|allocations| allocations.get()).unwrap_or(0)
+}
@@ -0,0 +1,44 @@
+//! Clipping areas which run past the edge of the screen instead of rejecting them
+
+use crate::{Controller, Driver, Error, Rect};
+
+impl Driver {
+    /// Clip windows and scroll regions which run past the edge of the screen rather than returning [`Error::InvalidWindow`], see [`Driver::set_clipping`]
+    pub fn with_clipping(mut self, clip: bool) -> Driver {
+        self.clip = clip;
+        self
+    }
+
+    /// Returns true if areas running past the edge of the screen are clipped
+    pub fn is_clipping(&self) -> bool {
+        self.clip
+    }
+
+    /// Clip areas which run past the edge of the screen rather than rejecting them.
+    ///
+    /// [`Driver::set_window`], [`Driver::start_scroll`] and `show_rgb565` then use the part of their area on the screen, widened to even columns on the SSD1327,
+    /// so size raw data with [`Driver::clip_window`]. Areas wholly off the screen are still rejected by windows and ignored by draws.
+    /// `ws-1in5-i2c`'s `draw_image_at` draws the visible part of an image at a coord which can be negative.
+    pub fn set_clipping(&mut self, clip: bool) {
+        self.clip = clip;
+    }
+
+    /// The part of an area in physical pixels that can be addressed as a window: on the screen, and widened to even columns on the SSD1327.
+    /// None if no part of it is on the screen
+    pub fn clip_window(&self, rect: Rect) -> Option<Rect> {
+        let bounds = self.geometry().bounds();
+        let rect = rect.intersection(&bounds)?;
+        match self.controller() {
+            Controller::Ssd1327 => rect.align_even().intersection(&bounds),
+            #[cfg(feature = "ssd1351")]
+            Controller::Ssd1351(_) => Some(rect),
+        }
+    }
+
+    /// A window clipped to the screen when clipping, checked it can be addressed
+    pub(crate) fn window_for(&self, rect: Rect) -> Result<Rect, Error> {
+        let rect = if self.clip { self.clip_window(rect).unwrap_or(rect) } else { rect };
+        self.check_window(rect)?;
+        Ok(rect)
+    }
+}
@@ -0,0 +1,80 @@
+//! Sending several controller commands in one go, so configuration doesn't take a bus transaction per byte
+
+use alloc::vec::Vec;
+
+use crate::{Driver, Error};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// A sequence of commands and their arguments, sent by [`Driver::send_batch`] in as few transactions as the interface allows
+///
+/// Over i2c a batch is one write per 8KiB rather than one per byte, which matters most on a bus shared with other devices.
+pub struct CommandBatch {
+    bytes: Vec<u8>,
+}
+
+impl CommandBatch {
+    /// Create new, empty
+    pub fn new() -> CommandBatch {
+        CommandBatch { bytes: Vec::new() }
+    }
+
+    /// Add a command without arguments
+    pub fn command(&mut self, cmd: u8) -> &mut CommandBatch {
+        self.bytes.push(cmd);
+        self
+    }
+
+    /// Add a command followed by its arguments
+    pub fn command_with_args(&mut self, cmd_and_args: &[u8]) -> &mut CommandBatch {
+        self.bytes.extend_from_slice(cmd_and_args);
+        self
+    }
+
+    /// The bytes to send
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Number of bytes in the batch
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns true if nothing has been added
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Remove every command, keeping the allocation for reuse
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+    }
+}
+
+impl Driver {
+    /// Send a batch of raw commands to the controller, combined into as few transactions as the interface allows.
+    ///
+    /// This is low level access, see [`Driver::send_command`]. A failed batch is retried from its first command.
+    pub fn send_batch(&mut self, batch: &CommandBatch) -> Result<(), Error> {
+        self.commands(batch.as_bytes())
+    }
+
+    /// Send command bytes in as few transactions as the interface allows, without allocating
+    pub(crate) fn commands(&mut self, cmds: &[u8]) -> Result<(), Error> {
+        if cmds.is_empty() {
+            return Ok(())
+        }
+
+        let result = self.retry(|this| this.write_commands(cmds));
+        self.supervise(result)
+    }
+
+    /// Send command bytes once, without retrying, for writes which retry as a whole
+    pub(crate) fn write_commands(&mut self, cmds: &[u8]) -> Result<(), Error> {
+        #[cfg(feature = "std")]
+        self.record_transaction(cmds.len());
+        #[cfg(feature = "tracing")]
+        tracing::trace!(?cmds, "commands");
+        self.interface.commands(cmds)
+    }
+}
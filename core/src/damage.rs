@@ -0,0 +1,191 @@
+//! Tracking which areas of the screen hold content, so widgets sharing the screen can tell whether their area is blank
+
+use alloc::vec::Vec;
+
+use crate::{Driver, Rect};
+
+/// Past this many areas they're collapsed into their bounds, which only ever overstates what holds content
+const MAX_RECTS: usize = 32;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Areas of the screen holding content, in logical pixels. Areas never overlap
+///
+/// Writing anything but black to an area adds it, writing black (e.g. [`Driver::clear`]) removes it.
+pub struct Damage {
+    rects: Vec<Rect>,
+}
+
+impl Damage {
+    /// Create new, with the whole screen blank
+    pub fn new() -> Damage {
+        Damage { rects: Vec::new() }
+    }
+
+    /// Mark an area as holding content
+    pub fn add(&mut self, rect: Rect) {
+        if rect.is_empty() {
+            return
+        }
+
+        self.remove(rect);
+        self.rects.push(rect);
+        if self.rects.len() > MAX_RECTS {
+            let bounds = self.rects.iter().fold(Rect::new(0, 0, 0, 0), |bounds, rect| bounds.union(rect));
+            self.rects.clear();
+            self.rects.push(bounds);
+        }
+    }
+
+    /// Mark an area as blank, splitting any areas it partly covers
+    pub fn remove(&mut self, rect: Rect) {
+        if rect.is_empty() || !self.rects.iter().any(|other| other.intersects(&rect)) {
+            return
+        }
+
+        let mut rects = Vec::with_capacity(self.rects.len() + 4);
+        for other in self.rects.drain(..) {
+            match other.intersection(&rect) {
+                Some(overlap) => {
+                    // The parts above and below the overlap span the whole width, the parts beside it only its height
+                    let pieces = [
+                        Rect::new(other.x, other.y, other.w, overlap.y - other.y),
+                        Rect::new(other.x, overlap.bottom(), other.w, other.bottom() - overlap.bottom()),
+                        Rect::new(other.x, overlap.y, overlap.x - other.x, overlap.h),
+                        Rect::new(overlap.right(), overlap.y, other.right() - overlap.right(), overlap.h),
+                    ];
+                    rects.extend(pieces.into_iter().filter(|piece| !piece.is_empty()));
+                },
+                None => rects.push(other),
+            }
+        }
+        self.rects = rects;
+    }
+
+    /// The areas holding content
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+
+    /// Returns true if no part of an area holds content
+    pub fn is_clean(&self, rect: Rect) -> bool {
+        !self.rects.iter().any(|other| other.intersects(&rect))
+    }
+
+    /// Returns true if the whole screen is blank
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// Mark the whole screen as blank
+    pub fn clear(&mut self) {
+        self.rects.clear();
+    }
+}
+
+impl Driver {
+    /// The areas of the screen holding content
+    pub fn damage(&self) -> &Damage {
+        &self.damage
+    }
+
+    /// Returns true if nothing but black has been written to an area since it was last cleared
+    pub fn is_clean(&self, rect: Rect) -> bool {
+        self.damage.is_clean(rect)
+    }
+
+    /// Record a write to an area in logical pixels, adding it to the damage or removing it if it was all black
+    pub(crate) fn record_write(&mut self, blank: bool, rect: Rect) {
+        let Some(rect) = rect.intersection(&self.framebuffer().bounds()) else {
+            return
+        };
+        if blank {
+            self.damage.remove(rect);
+        } else {
+            self.damage.add(rect);
+        }
+    }
+
+    /// Record a write which bypassed the framebuffer to an area in physical pixels, which is assumed to hold content
+    pub(crate) fn record_physical_write(&mut self, rect: Rect) {
+        let rect = if self.is_pixel_doubled() {
+            Rect::new(rect.x / 2, rect.y / 2, rect.w.div_ceil(2) + rect.x % 2, rect.h.div_ceil(2) + rect.y % 2)
+        } else {
+            rect
+        };
+        if let Some(rect) = rect.intersection(&self.framebuffer().bounds()) {
+            self.damage.add(rect);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Number of pixels covered, the areas never overlap
+    fn area(damage: &Damage) -> usize {
+        damage.rects().iter().map(|rect| rect.w * rect.h).sum()
+    }
+
+    #[test]
+    fn added_area_holds_content() {
+        let mut damage = Damage::new();
+        damage.add(Rect::new(10, 10, 20, 20));
+        assert!(!damage.is_clean(Rect::new(29, 29, 4, 4)));
+        assert!(damage.is_clean(Rect::new(30, 0, 10, 40)));
+    }
+
+    #[test]
+    fn removing_the_middle_splits_an_area() {
+        let mut damage = Damage::new();
+        damage.add(Rect::new(0, 0, 30, 30));
+        damage.remove(Rect::new(10, 10, 10, 10));
+
+        assert_eq!(damage.rects(), &[
+            Rect::new(0, 0, 30, 10),
+            Rect::new(0, 20, 30, 10),
+            Rect::new(0, 10, 10, 10),
+            Rect::new(20, 10, 10, 10),
+        ]);
+        assert_eq!(area(&damage), 30 * 30 - 10 * 10);
+        assert!(damage.is_clean(Rect::new(10, 10, 10, 10)));
+    }
+
+    #[test]
+    fn removing_an_edge_trims_an_area() {
+        let mut damage = Damage::new();
+        damage.add(Rect::new(0, 0, 20, 20));
+        damage.remove(Rect::new(10, 0, 20, 20));
+        assert_eq!(damage.rects(), &[Rect::new(0, 0, 10, 20)]);
+    }
+
+    #[test]
+    fn removing_a_covering_area_clears_it() {
+        let mut damage = Damage::new();
+        damage.add(Rect::new(4, 4, 8, 8));
+        damage.add(Rect::new(40, 40, 8, 8));
+        damage.remove(Rect::new(0, 0, 128, 128));
+        assert!(damage.is_empty());
+    }
+
+    #[test]
+    fn added_areas_never_overlap() {
+        let mut damage = Damage::new();
+        damage.add(Rect::new(0, 0, 20, 20));
+        damage.add(Rect::new(10, 10, 20, 20));
+        assert_eq!(area(&damage), 20 * 20 * 2 - 10 * 10);
+
+        // Adding an area covering everything leaves just that area
+        damage.add(Rect::new(0, 0, 40, 40));
+        assert_eq!(damage.rects(), &[Rect::new(0, 0, 40, 40)]);
+    }
+
+    #[test]
+    fn too_many_areas_collapse_into_their_bounds() {
+        let mut damage = Damage::new();
+        for i in 0..=MAX_RECTS {
+            damage.add(Rect::new(i * 3, 0, 2, 2));
+        }
+        assert_eq!(damage.rects(), &[Rect::new(0, 0, MAX_RECTS * 3 + 2, 2)]);
+    }
+}
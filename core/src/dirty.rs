@@ -0,0 +1,144 @@
+//! Explicit partial updates, for callers which know what they changed
+
+use alloc::vec::Vec;
+
+use crate::{Driver, Error, Rect};
+
+/// Past this many areas they're collapsed into their bounds, one larger write being cheaper than many small ones
+const MAX_RECTS: usize = 16;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Areas which need writing to the screen, overlapping or adjacent areas are merged as they are added
+pub struct DirtyRegion {
+    rects: Vec<Rect>,
+}
+
+impl DirtyRegion {
+    /// Create new, with nothing dirty
+    pub fn new() -> DirtyRegion {
+        DirtyRegion { rects: Vec::new() }
+    }
+
+    /// Add an area, merging it with any area it overlaps or shares an edge with. Too many areas are collapsed into one covering them all
+    pub fn add(&mut self, rect: Rect) {
+        if rect.is_empty() {
+            return
+        }
+
+        let mut merged = rect;
+        // Growing an area can make it touch areas it didn't before, so merge until nothing changes
+        while let Some(i) = self.rects.iter().position(|other| merged.touches(other)) {
+            merged = merged.union(&self.rects.swap_remove(i));
+        }
+        self.rects.push(merged);
+        if self.rects.len() > MAX_RECTS {
+            let bounds = self.rects.iter().fold(Rect::new(0, 0, 0, 0), |bounds, rect| bounds.union(rect));
+            self.rects.clear();
+            self.rects.push(bounds);
+        }
+    }
+
+    /// The areas, none of which touch
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+
+    /// Returns true if nothing is dirty
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// Forget every area
+    pub fn clear(&mut self) {
+        self.rects.clear();
+    }
+}
+
+impl Driver {
+    /// Mark an area of the framebuffer as changed, so it's written by [`Driver::flush_invalidated`]. The area is clipped to the screen
+    pub fn invalidate(&mut self, rect: Rect) {
+        if let Some(rect) = rect.intersection(&self.framebuffer().bounds()) {
+            self.dirty.add(rect);
+        }
+    }
+
+    /// The areas marked as changed and not yet written
+    pub fn invalidated(&self) -> &DirtyRegion {
+        &self.dirty
+    }
+
+    /// Write the areas marked as changed to the screen, one transfer per merged area.
+    /// If a write fails, the areas not yet written stay marked
+    pub fn flush_invalidated(&mut self) -> Result<(), Error> {
+        let mut rects = core::mem::take(&mut self.dirty.rects);
+        let mut result = Ok(());
+        for (i, rect) in rects.iter().enumerate() {
+            if let Err(e) = self.flush_rect(*rect) {
+                for rect in &rects[i..] {
+                    self.dirty.add(*rect);
+                }
+                result = Err(e);
+                break
+            }
+        }
+
+        // Hand the allocation back if nothing was re-marked
+        if self.dirty.rects.is_empty() {
+            rects.clear();
+            self.dirty.rects = rects;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(rects: &[Rect]) -> DirtyRegion {
+        let mut region = DirtyRegion::new();
+        rects.iter().for_each(|rect| region.add(*rect));
+        region
+    }
+
+    #[test]
+    fn overlapping_areas_merge() {
+        let region = region(&[Rect::new(0, 0, 10, 10), Rect::new(5, 5, 10, 10)]);
+        assert_eq!(region.rects(), &[Rect::new(0, 0, 15, 15)]);
+    }
+
+    #[test]
+    fn adjacent_areas_merge() {
+        let region = region(&[Rect::new(0, 0, 10, 10), Rect::new(10, 0, 4, 10)]);
+        assert_eq!(region.rects(), &[Rect::new(0, 0, 14, 10)]);
+    }
+
+    #[test]
+    fn disjoint_areas_stay_apart() {
+        // Touching only at a corner doesn't count as adjacent
+        let rects = [Rect::new(0, 0, 10, 10), Rect::new(20, 20, 4, 4), Rect::new(10, 10, 2, 2)];
+        assert_eq!(region(&rects).rects(), &rects);
+    }
+
+    #[test]
+    fn merging_cascades() {
+        // The third area bridges the first two, and the merged area then reaches the fourth
+        let region = region(&[Rect::new(0, 0, 4, 4), Rect::new(8, 0, 4, 4), Rect::new(12, 2, 4, 8), Rect::new(3, 0, 6, 2)]);
+        assert_eq!(region.rects(), &[Rect::new(0, 0, 16, 10)]);
+    }
+
+    #[test]
+    fn empty_areas_are_ignored() {
+        assert!(region(&[Rect::new(4, 4, 0, 10)]).is_empty());
+    }
+
+    #[test]
+    fn too_many_areas_collapse_into_their_bounds() {
+        let rects: Vec<Rect> = (0..MAX_RECTS).map(|i| Rect::new(i * 4, i * 4, 2, 2)).collect();
+        let mut region = region(&rects);
+        assert_eq!(region.rects(), rects.as_slice());
+
+        region.add(Rect::new(100, 0, 2, 2));
+        assert_eq!(region.rects(), &[Rect::new(0, 0, 102, (MAX_RECTS - 1) * 4 + 2)]);
+    }
+}
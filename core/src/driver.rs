@@ -0,0 +1,756 @@
+//! The controller: initialisation, power, windowing and flushing the framebuffer
+
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::time::Duration;
+
+use crate::{Error, Rect, Damage, DirtyRegion, Framebuffer, PackedBuffer, Geometry, Orientation, Interface, PowerConfig, TransferConfig, FrameSync, CommandBatch, Remap, STATUS_DISPLAY_OFF, recovery::Recovery, swap::SwapCallback};
+#[cfg(feature = "ssd1351")]
+use crate::ssd1351::{self, Palette};
+#[cfg(feature = "std")]
+use crate::{stats::StatsCollector, screensaver::ScreensaverState, overlay::Overlay};
+
+/// Contrast set when the controller is initialised
+pub(crate) const DEFAULT_CONTRAST: u8 = 0x80;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// The controller driving the panel
+pub enum Controller {
+    /// SSD1327, 4 bit greyscale (the 1.5 inch OLED)
+    #[default]
+    Ssd1327,
+    /// SSD1351, 16 bit colour (the 1.5 inch RGB OLED), showing each grey level as a colour from the palette
+    #[cfg(feature = "ssd1351")]
+    Ssd1351(Palette),
+}
+
+/// An SSD1327 and the in memory copy of what it is showing
+///
+/// This is everything needed to drive the screen, without text or image handling.
+pub struct Driver {
+    pub(crate) interface: Box<dyn Interface>,
+
+    controller: Controller,
+    geometry: Geometry,
+    pub(crate) damage: Damage,
+    pub(crate) suspended: bool,
+    framebuffer: Framebuffer,
+    pub(crate) dirty: DirtyRegion,
+    power: PowerConfig,
+    contrast: u8,
+    verify: bool,
+    doubled: bool,
+    orientation: Orientation,
+    pub(crate) scrolling: bool,
+    pub(crate) recovery: Recovery,
+    pub(crate) transfer: TransferConfig,
+    pub(crate) clip: bool,
+    pub(crate) remap: Remap,
+    pub(crate) sync: FrameSync,
+    pub(crate) back: Option<Framebuffer>,
+    pub(crate) swap_callback: Option<SwapCallback>,
+    pub(crate) swaps: u64,
+    #[cfg(feature = "std")]
+    pub(crate) last_frame: Option<std::time::Instant>,
+    #[cfg(feature = "std")]
+    pub(crate) stats: Option<StatsCollector>,
+    #[cfg(feature = "std")]
+    pub(crate) screensaver: Option<ScreensaverState>,
+    #[cfg(feature = "std")]
+    pub(crate) overlay: Option<Overlay>,
+    scratch: Scratch,
+    #[cfg(feature = "std")]
+    check_allocations: bool,
+}
+
+impl Driver {
+    /// Create new, connected through an interface, and initialise the controller for the 128x128 panel
+    pub fn new(interface: Box<dyn Interface>) -> Result<Driver, Error> {
+        Driver::with_geometry(interface, Geometry::default())
+    }
+
+    /// Create new for a panel of another size, connected through an interface, and initialise the controller
+    pub fn with_geometry(interface: Box<dyn Interface>, geometry: Geometry) -> Result<Driver, Error> {
+        Driver::with_controller(interface, Controller::Ssd1327, geometry)
+    }
+
+    /// Create new for a panel driven by another controller, connected through an interface, and initialise the controller
+    pub fn with_controller(interface: Box<dyn Interface>, controller: Controller, geometry: Geometry) -> Result<Driver, Error> {
+        if !geometry.is_valid() {
+            return Err(Error::InvalidGeometry(geometry))
+        }
+
+        let mut this = Driver { interface, controller, geometry, damage: Damage::new(), suspended: false, framebuffer: Framebuffer::new(geometry.width, geometry.height), dirty: DirtyRegion::new(), power: PowerConfig::default(), contrast: DEFAULT_CONTRAST, verify: false, doubled: false, orientation: Orientation::Normal, scrolling: false, recovery: Recovery::default(), transfer: TransferConfig::default(), clip: false, remap: Remap::IDENTITY, sync: FrameSync::None, back: None, swap_callback: None, swaps: 0, #[cfg(feature = "std")] last_frame: None, #[cfg(feature = "std")] stats: None, #[cfg(feature = "std")] screensaver: None, #[cfg(feature = "std")] overlay: None, scratch: Scratch::new(&geometry, &controller), #[cfg(feature = "std")] check_allocations: false };
+        this.init()?;
+
+        Ok(this)
+    }
+
+    pub(crate) fn command(&mut self, cmd: u8) -> Result<(), Error> {
+        let result = self.retry(|this| {
+            #[cfg(feature = "std")]
+            this.record_transaction(1);
+            #[cfg(feature = "tracing")]
+            tracing::trace!("command {:#04x}", cmd);
+            this.interface.command(cmd)
+        });
+        self.supervise(result)
+    }
+
+    /// Wait using the interface's delay
+    pub(crate) fn delay(&mut self, duration: Duration) {
+        self.interface.delay(duration)
+    }
+
+    /// Send a raw command byte to the controller.
+    ///
+    /// This is low level access for controller features the crate doesn't wrap, see the SSD1327 datasheet.
+    /// Commands which change addressing, remapping or power can leave the screen out of step with the crate's view of it.
+    pub fn send_command(&mut self, cmd: u8) -> Result<(), Error> {
+        self.command(cmd)
+    }
+
+    /// Send a raw command followed by its arguments.
+    ///
+    /// This is low level access, see [`Driver::send_command`].
+    pub fn send_command_with_args(&mut self, cmd_and_args: &[u8]) -> Result<(), Error> {
+        self.commands(cmd_and_args)
+    }
+
+    /// Send raw display data to the controller's current window.
+    ///
+    /// This is low level access, the data bypasses the framebuffer so it won't appear in screenshots or be considered by partial updates.
+    pub fn send_data(&mut self, data: &[u8]) -> Result<(), Error> {
+        if self.scrolling {
+            return Err(Error::Scrolling)
+        }
+        #[cfg(feature = "std")]
+        self.wake_screensaver()?;
+        let bounds = self.geometry.bounds();
+        self.record_physical_write(bounds);
+        let result = self.write_chunked(data);
+        self.supervise(result)
+    }
+
+    pub(crate) fn init(&mut self) -> Result<(), Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("init", controller = ?self.controller, geometry = ?self.geometry).entered();
+        #[cfg(feature = "tracing")]
+        let start = std::time::Instant::now();
+
+        self.reset();
+
+        match self.controller {
+            Controller::Ssd1327 => self.init_ssd1327()?,
+            #[cfg(feature = "ssd1351")]
+            Controller::Ssd1351(_) => {
+                let mut batch = CommandBatch::new();
+                for (cmd, args, len) in ssd1351::init_commands(&self.geometry) {
+                    batch.command(cmd).command_with_args(&args[..len]);
+                }
+                self.send_batch(&batch)?;
+            },
+        }
+
+        if self.contrast != DEFAULT_CONTRAST {
+            self.send_contrast(self.contrast)?;
+        }
+
+        self.delay(Duration::from_millis(100));
+        self.command(0xAF)?;
+        self.check_status(true)?;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(elapsed = ?start.elapsed(), "initialised");
+        Ok(())
+    }
+
+    fn init_ssd1327(&mut self) -> Result<(), Error> {
+        let (first_column, last_column) = self.geometry.columns();
+        let mut batch = CommandBatch::new();
+        batch.command(0xae)
+            .command(0x2e)
+            .command_with_args(&[0x15, first_column, last_column])
+            .command_with_args(&[0x75, 0x00, self.geometry.mux_ratio()])
+            .command_with_args(&[0x81, DEFAULT_CONTRAST])
+            .command_with_args(&[0xa0, 0x51])
+            .command_with_args(&[0xa1, 0x00])
+            .command_with_args(&[0xa2, self.geometry.row_offset as u8])
+            .command(0xa4)
+            .command_with_args(&[0xa8, self.geometry.mux_ratio()]);
+        self.power_commands(&mut batch);
+        batch.command_with_args(&[0xfd, 0x12]);
+
+        self.send_batch(&batch)?;
+        self.scrolling = false;
+        Ok(())
+    }
+
+    fn send_power_config(&mut self) -> Result<(), Error> {
+        let mut batch = CommandBatch::new();
+        self.power_commands(&mut batch);
+        self.send_batch(&batch)
+    }
+
+    /// Add the power settings to a batch
+    fn power_commands(&self, batch: &mut CommandBatch) {
+        // The settings are the SSD1327's, other controllers keep the ones from their init sequence
+        if self.controller != Controller::Ssd1327 {
+            return
+        }
+
+        for (cmd, arg) in self.power.commands() {
+            batch.command_with_args(&[cmd, arg]);
+        }
+    }
+
+    /// The power and driving settings
+    pub fn power_config(&self) -> &PowerConfig {
+        &self.power
+    }
+
+    /// Change the power and driving settings, they are sent now and whenever the controller is initialised (SSD1327 only)
+    pub fn set_power_config(&mut self, power: PowerConfig) -> Result<(), Error> {
+        self.power = power;
+        self.send_power_config()
+    }
+
+    /// Contrast (brightness) of lit pixels
+    pub fn contrast(&self) -> u8 {
+        self.contrast
+    }
+
+    /// Set the contrast (brightness) of lit pixels, it's kept when the controller is initialised again (e.g. by recovery). The SSD1351 only has 16 steps
+    pub fn set_contrast(&mut self, contrast: u8) -> Result<(), Error> {
+        self.contrast = contrast;
+        // The screensaver's dimmed contrast stays until it wakes
+        #[cfg(feature = "std")]
+        if self.is_screensaver_active() {
+            return Ok(())
+        }
+        self.send_contrast(contrast)
+    }
+
+    pub(crate) fn send_contrast(&mut self, contrast: u8) -> Result<(), Error> {
+        match self.controller {
+            Controller::Ssd1327 => {
+                self.command(0x81)?;
+                self.command(contrast)
+            },
+            // The master contrast only has 16 steps
+            #[cfg(feature = "ssd1351")]
+            Controller::Ssd1351(_) => {
+                self.command(0xc7)?;
+                self.command(contrast >> 4)
+            },
+        }
+    }
+
+    /// Check the panel is on or off after switching it, returning [`Error::Desync`] if it isn't. Ignored when the interface can't read status
+    pub fn with_verification(mut self, verify: bool) -> Driver {
+        self.verify = verify;
+        self
+    }
+
+    /// Read the controller's status byte, None if the interface can't read from the controller
+    pub fn status(&mut self) -> Result<Option<u8>, Error> {
+        self.interface.status()
+    }
+
+    /// Read whether the panel is on, None if the interface can't read from the controller
+    pub fn is_on(&mut self) -> Result<Option<bool>, Error> {
+        Ok(self.status()?.map(|status| status & STATUS_DISPLAY_OFF == 0))
+    }
+
+    fn check_status(&mut self, on: bool) -> Result<(), Error> {
+        if !self.verify {
+            return Ok(())
+        }
+
+        match self.is_on()? {
+            Some(is_on) if is_on != on => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(expected_on = on, "controller status doesn't match the panel's expected state");
+                Err(Error::Desync)
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Switch the panel off ahead of a system suspend, the framebuffer is kept so [`Driver::resume`] can restore it
+    pub fn suspend(&mut self) -> Result<(), Error> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("suspending");
+        self.command(0xae)?;
+        self.suspended = true;
+        self.check_status(false)
+    }
+
+    /// Re-initialise the controller after a system resume (it may have lost power) and restore the framebuffer
+    pub fn resume(&mut self) -> Result<(), Error> {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("resuming");
+        self.init()?;
+        self.suspended = false;
+        self.flush()
+    }
+
+    /// Returns true if the panel has been suspended
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    pub fn reset(&mut self) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("hardware reset");
+        self.interface.reset();
+        
+        self.damage.clear();
+    }
+
+    /// Set the area of the controller's memory the next data is written to, in physical pixels.
+    ///
+    /// Returns [`Error::InvalidWindow`] if the area is empty or off the screen, or for the SSD1327 if x or the width is odd (it's written two pixels at a time).
+    /// With [`Driver::set_clipping`] an area partly off the screen is clipped instead.
+    pub fn set_window(&mut self, rect: Rect) -> Result<(), Error> {
+        let result = self.retry(|this| this.write_window(rect));
+        self.supervise(result)
+    }
+
+    /// Set the window once, without retrying, for writes which retry from the window as a whole
+    pub(crate) fn write_window(&mut self, rect: Rect) -> Result<(), Error> {
+        let rect = self.window_for(rect)?;
+        #[cfg(feature = "tracing")]
+        tracing::trace!(?rect, "set window");
+
+        let (xstart, ystart, xend, yend) = (rect.x as u8, rect.y as u8, rect.right() as u8, rect.bottom() as u8);
+        match self.controller {
+            Controller::Ssd1327 => {
+                let first_column = self.geometry.columns().0;
+                self.write_commands(&[0x15, first_column + xstart/2, first_column + xend/2 - 1, 0x75, ystart, yend - 1])?;
+            },
+            #[cfg(feature = "ssd1351")]
+            Controller::Ssd1351(_) => {
+                // Columns are addressed per pixel
+                let first_column = self.geometry.column_offset as u8;
+                self.write_commands(&[0x15, first_column + xstart, first_column + xend - 1, 0x75, ystart, yend - 1, ssd1351::WRITE_RAM])?;
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Check a window can be addressed, see [`Driver::set_window`]
+    pub(crate) fn check_window(&self, rect: Rect) -> Result<(), Error> {
+        let reason = if rect.is_empty() {
+            Some("window is empty")
+        } else if rect.right() > self.geometry.width || rect.bottom() > self.geometry.height {
+            Some("window is off the screen")
+        } else if self.controller == Controller::Ssd1327 && !(rect.x.is_multiple_of(2) && rect.w.is_multiple_of(2)) {
+            Some("x and width must be even")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(?rect, reason, "invalid window");
+            return Err(Error::InvalidWindow { rect, reason })
+        }
+        Ok(())
+    }
+
+    #[deprecated(note = "use Driver::is_clean or Driver::damage, which track each area of the screen")]
+    /// Returns true if nothing but black has been written to the screen since it was last cleared
+    pub fn has_cleared(&self) -> bool {
+        self.damage.is_empty()
+    }
+
+    /// Clear a section of the screen
+    pub fn clear(&mut self, x: usize, y: usize, width: usize, height: usize) -> Result<(), Error> {
+        let buffer: Vec<u8> = vec![0x00; (width  /2) * height];
+        self.show_image(buffer, x, y, width, height)
+    }
+
+    /// Fill a section of the screen with a colour (0-15)
+    pub fn fill_rect(&mut self, rect: Rect, color: u8) -> Result<(), Error> {
+        self.framebuffer.fill_rect(rect, color);
+        self.flush_rect(rect)
+    }
+
+    /// Clear the whole screen
+    pub fn clear_all(&mut self) -> Result<(), Error> {
+        self.clear(0, 0, self.width(), self.height())
+    }
+
+    /// Width of the screen in (logical) pixels
+    pub fn width(&self) -> usize {
+        self.framebuffer.width()
+    }
+
+    /// Height of the screen in (logical) pixels
+    pub fn height(&self) -> usize {
+        self.framebuffer.height()
+    }
+
+    /// The panel's size and wiring
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    /// The controller driving the panel
+    pub fn controller(&self) -> Controller {
+        self.controller
+    }
+
+    #[cfg(feature = "ssd1351")]
+    /// Change the colours grey levels are shown as and rewrite the screen, returns [`Error::Unsupported`] on a greyscale controller
+    pub fn set_palette(&mut self, palette: Palette) -> Result<(), Error> {
+        match &mut self.controller {
+            Controller::Ssd1351(current) => *current = palette,
+            _ => return Err(Error::Unsupported("palette")),
+        }
+        self.flush()
+    }
+
+    #[cfg(feature = "ssd1351")]
+    /// Write RGB565 pixels straight to a section of a colour screen, in physical pixels.
+    ///
+    /// Like [`Driver::send_data`] this bypasses the framebuffer, so the pixels are replaced by the next write to the area.
+    /// Returns [`Error::Unsupported`] on a greyscale controller. With [`Driver::set_clipping`] the part of the area on the screen is written
+    pub fn show_rgb565(&mut self, rect: Rect, pixels: &[u16]) -> Result<(), Error> {
+        if !matches!(self.controller, Controller::Ssd1351(_)) {
+            return Err(Error::Unsupported("show_rgb565"))
+        }
+        let visible = rect.intersection(&self.geometry.bounds());
+        if pixels.len() < rect.w * rect.h || (!self.clip && visible != Some(rect)) {
+            return Err(Error::OutOfBounds { operation: "show_rgb565", rect, len: pixels.len() })
+        }
+        let Some(visible) = visible else {
+            return Ok(())
+        };
+
+        self.record_physical_write(visible);
+        let mut colors = core::mem::take(&mut self.scratch.colors);
+        colors.clear();
+        for y in visible.y..visible.bottom() {
+            let start = (y - rect.y) * rect.w + visible.x - rect.x;
+            colors.extend(pixels[start..start + visible.w].iter().flat_map(|pixel| pixel.to_be_bytes()));
+        }
+        let result = self.retry(|this| {
+            this.write_window(visible)?;
+            this.write_chunked(&colors)
+        });
+        self.scratch.colors = colors;
+        self.supervise(result)
+    }
+
+    /// Switch to a half resolution logical screen (64x64 on the 128x128 panel) where every pixel is drawn as a 2x2 block, or back to the full resolution.
+    /// Every drawing method then works in logical pixels, the current content is scaled to fit and rewritten
+    pub fn set_pixel_doubling(&mut self, doubled: bool) -> Result<(), Error> {
+        if doubled == self.doubled {
+            return Ok(())
+        }
+
+        let (width, height) = if doubled { (self.geometry.width / 2, self.geometry.height / 2) } else { (self.geometry.width, self.geometry.height) };
+        let mut framebuffer = Framebuffer::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let color = self.framebuffer.get_pixel(x * self.framebuffer.width() / width, y * self.framebuffer.height() / height);
+                framebuffer.set_pixel(x, y, color);
+            }
+        }
+
+        // The back buffer is the old size
+        self.framebuffer = framebuffer;
+        self.back = None;
+        self.doubled = doubled;
+        self.flush()
+    }
+
+    /// Mount the panel another way up, see [`Driver::set_orientation`]
+    pub fn with_orientation(mut self, orientation: Orientation) -> Driver {
+        self.orientation = orientation;
+        self.scratch.reserve_rotated(&self.geometry, orientation);
+        self
+    }
+
+    /// Which way up the panel is mounted
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// Change which way up the panel is mounted. Everything drawn (text, images, clears and raw blits alike) is in the viewer's coords
+    /// and rotated on its way to the screen, the current content is rewritten.
+    ///
+    /// Windows, scrolling and raw data sent with [`Driver::set_window`] and [`Driver::send_data`] stay in physical pixels.
+    pub fn set_orientation(&mut self, orientation: Orientation) -> Result<(), Error> {
+        if orientation == self.orientation {
+            return Ok(())
+        }
+
+        self.orientation = orientation;
+        self.scratch.reserve_rotated(&self.geometry, orientation);
+        self.flush()
+    }
+
+    #[cfg(feature = "std")]
+    /// Replace the framebuffer, orientation and pixel doubling without writing to the screen, for restoring saved state
+    pub(crate) fn replace_state(&mut self, framebuffer: Framebuffer, orientation: Orientation, doubled: bool) {
+        self.framebuffer = framebuffer;
+        self.back = None;
+        self.orientation = orientation;
+        self.doubled = doubled;
+        self.scratch.reserve_rotated(&self.geometry, orientation);
+    }
+
+    /// Returns true if every logical pixel is drawn as a 2x2 block
+    pub fn is_pixel_doubled(&self) -> bool {
+        self.doubled
+    }
+
+    /// Show packed data of a certain size on the screen at the specified coord.
+    ///
+    /// The data is borrowed (a `Vec<u8>` or `&[u8]`), so a buffer can be reused between frames without allocating
+    pub fn show_image<B: AsRef<[u8]>>(&mut self, buffer: B, x: usize, y: usize, width: usize, height: usize) -> Result<(), Error> {
+        let buffer = buffer.as_ref();
+        if buffer.len() < (width /2) * height {
+            return Err(Error::OutOfBounds { operation: "show_image", rect: Rect::new(x, y, width, height), len: buffer.len() })
+        }
+
+        self.framebuffer.blit(x, y, width, height, buffer)?;
+        // Written back from the framebuffer, so the screen gets the same rounding and clipping
+        self.flush_rect(Rect::new(x, y, width, height))
+    }
+
+    /// Show a packed buffer on the screen at the specified coord, clipped to the screen. Unlike [`Driver::show_image`] the buffer carries its own size
+    pub fn show_packed(&mut self, x: usize, y: usize, buffer: &PackedBuffer) -> Result<(), Error> {
+        self.framebuffer.blit_framebuffer(x, y, buffer);
+        self.flush_rect(Rect::new(x, y, buffer.width(), buffer.height()))
+    }
+
+    /// Show packed data of a certain size from an iterator on the screen at the specified coord, e.g. data generated on the fly.
+    ///
+    /// Nothing is shown if the iterator runs out early. Doesn't allocate for images up to the size of the screen
+    pub fn show_image_iter<I: IntoIterator<Item = u8>>(&mut self, data: I, x: usize, y: usize, width: usize, height: usize) -> Result<(), Error> {
+        let len = (width / 2) * height;
+        let mut staged = core::mem::take(&mut self.scratch.staged);
+        staged.clear();
+        staged.extend(data.into_iter().take(len));
+        let result = self.show_image(staged.as_slice(), x, y, width, height);
+        self.scratch.staged = staged;
+        result
+    }
+
+    fn write_data(&mut self, buffer: &[u8], x: usize, y: usize, width: usize, height: usize) -> Result<(), Error> {
+        #[cfg(feature = "std")]
+        let overlaid = self.overlay_intersects(Rect::new(x, y, width, height));
+        #[cfg(not(feature = "std"))]
+        let overlaid = false;
+        if self.remap.is_identity() && !overlaid {
+            return self.write_remapped(buffer, x, y, width, height)
+        }
+
+        // The overlay is drawn first so it's remapped along with everything else
+        let mut remapped = core::mem::take(&mut self.scratch.remapped);
+        remapped.clear();
+        remapped.extend_from_slice(&buffer[..(width / 2 * height).min(buffer.len())]);
+        #[cfg(feature = "std")]
+        self.composite_overlay(&mut remapped, Rect::new(x, y, width, height));
+        self.remap.apply(&mut remapped);
+        let result = self.write_remapped(&remapped, x, y, width, height);
+        self.scratch.remapped = remapped;
+        result
+    }
+
+    fn write_remapped(&mut self, buffer: &[u8], x: usize, y: usize, width: usize, height: usize) -> Result<(), Error> {
+        let blanked = self.begin_frame(Rect::new(x, y, width, height))?;
+        let mut result = if self.doubled {
+            let mut doubled = core::mem::take(&mut self.scratch.doubled);
+            let result = double_pixels(buffer, width, height, &mut doubled)
+                .and_then(|_| self.write_physical(&doubled, x * 2, y * 2, width * 2, height * 2));
+            self.scratch.doubled = doubled;
+            result
+        } else {
+            self.write_physical(buffer, x, y, width, height)
+        };
+        if blanked {
+            result = result.and(self.end_frame());
+        }
+
+        // A failed write may have left anything in the area, so it's only recorded as blank once written
+        let blank = result.is_ok() && buffer[..(width / 2 * height).min(buffer.len())].iter().all(|&byte| byte == 0);
+        self.record_write(blank, Rect::new(x, y, width, height));
+        result
+    }
+
+    fn write_physical(&mut self, buffer: &[u8], x: usize, y: usize, width: usize, height: usize) -> Result<(), Error> {
+        if buffer.len() < (width /2) * height {
+            return Err(Error::OutOfBounds { operation: "write_data", rect: Rect::new(x, y, width, height), len: buffer.len() })
+        }
+        if self.scrolling {
+            return Err(Error::Scrolling)
+        }
+
+        #[cfg(feature = "std")]
+        self.wake_screensaver()?;
+        #[cfg(feature = "std")]
+        let flush = self.begin_flush();
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("flush", x, y, width, height).entered();
+
+        let buffer = &buffer[..(width / 2) * height];
+        let mut rotated = core::mem::take(&mut self.scratch.rotated);
+        let (buffer, x, y) = match self.orientation {
+            Orientation::Normal => (buffer, x, y),
+            Orientation::Rotated180 => {
+                rotate_packed(buffer, &mut rotated);
+                (&rotated[..], self.geometry.width - x - width, self.geometry.height - y - height)
+            },
+        };
+
+        // A failed write may have moved the controller's cursor, so retries start again from the window
+        let result = match self.controller {
+            Controller::Ssd1327 => self.retry(|this| {
+                this.write_window(Rect::new(x, y, width, height))?;
+                this.write_chunked(buffer)
+            }),
+            #[cfg(feature = "ssd1351")]
+            Controller::Ssd1351(palette) => {
+                let mut colors = core::mem::take(&mut self.scratch.colors);
+                palette.convert(buffer, &mut colors);
+                let result = self.retry(|this| {
+                    this.write_window(Rect::new(x, y, width, height))?;
+                    this.write_chunked(&colors)
+                });
+                self.scratch.colors = colors;
+                result
+            },
+        };
+        self.scratch.rotated = rotated;
+        #[cfg(feature = "std")]
+        self.end_flush(flush, Rect::new(x, y, width, height));
+        self.supervise(result)
+    }
+
+    /// The in memory copy of what is on the screen
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+
+    /// The in memory copy of what is on the screen, changes are shown on the next flush
+    pub fn framebuffer_mut(&mut self) -> &mut Framebuffer {
+        &mut self.framebuffer
+    }
+
+    /// Write a section of the framebuffer to the screen
+    pub fn flush_rect(&mut self, rect: Rect) -> Result<(), Error> {
+        let mut region = core::mem::take(&mut self.scratch.region);
+        let result = match self.framebuffer.region_into(rect, &mut region) {
+            Some(rect) if !rect.is_empty() => self.write_data(&region, rect.x, rect.y, rect.w, rect.h),
+            _ => Ok(()),
+        };
+        self.scratch.region = region;
+        result
+    }
+
+    /// Write the whole framebuffer to the screen
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.flush_rect(self.framebuffer.bounds())?;
+        self.dirty.clear();
+        Ok(())
+    }
+
+    /// Replace the framebuffer with a full screen frame and write the area that changed to the screen. Returns the area written
+    pub fn present(&mut self, frame: Framebuffer) -> Result<Option<Rect>, Error> {
+        self.present_from(&frame)
+    }
+
+    /// Copy a full screen frame into the framebuffer and write the area that changed to the screen. Returns the area written.
+    ///
+    /// Once the screen has been flushed this doesn't allocate, so a render loop can reuse one frame without allocating at all.
+    pub fn present_from(&mut self, frame: &Framebuffer) -> Result<Option<Rect>, Error> {
+        #[cfg(feature = "std")]
+        let allocations = crate::alloc_check::allocations();
+
+        if frame.width() != self.framebuffer.width() || frame.height() != self.framebuffer.height() {
+            return Err(Error::OutOfBounds { operation: "present", rect: self.framebuffer.bounds(), len: frame.as_bytes().len() })
+        }
+
+        let changed = frame.diff(&self.framebuffer);
+        if let Some(rect) = changed {
+            self.framebuffer.copy_from(frame)?;
+            self.flush_rect(rect)?;
+        }
+
+        #[cfg(feature = "std")]
+        debug_assert!(!self.check_allocations || crate::alloc_check::allocations() == allocations, "present allocated");
+        Ok(changed)
+    }
+
+    #[cfg(feature = "std")]
+    /// Assert that [`Driver::present_from`] doesn't allocate in debug builds, needs [`alloc_check::CountingAllocator`] installed
+    pub fn with_allocation_check(mut self, check: bool) -> Driver {
+        self.check_allocations = check;
+        self
+    }
+}
+
+/// Scale packed data up so every pixel becomes a 2x2 block
+pub(crate) fn double_pixels(buffer: &[u8], width: usize, height: usize, doubled: &mut Vec<u8>) -> Result<(), Error> {
+    let stride = width / 2;
+    if buffer.len() < stride * height {
+        return Err(Error::OutOfBounds { operation: "double_pixels", rect: Rect::new(0, 0, width, height), len: buffer.len() })
+    }
+
+    doubled.clear();
+    for row in buffer[..stride * height].chunks(stride.max(1)).take(height) {
+        let start = doubled.len();
+        for byte in row {
+            let (left, right) = (byte >> 4, byte & 0x0f);
+            doubled.push(left << 4 | left);
+            doubled.push(right << 4 | right);
+        }
+        doubled.extend_from_within(start..);
+    }
+    Ok(())
+}
+
+/// Rotate packed data 180 degrees. Rows are a whole number of bytes, so this reverses the bytes and the pixels in each byte
+pub(crate) fn rotate_packed(buffer: &[u8], rotated: &mut Vec<u8>) {
+    rotated.clear();
+    rotated.extend(buffer.iter().rev().map(|byte| byte.rotate_left(4)));
+}
+
+/// Reusable buffers for writing to the screen, so steady state drawing doesn't allocate
+struct Scratch {
+    staged: Vec<u8>,
+    region: Vec<u8>,
+    /// Data with the levels remapped
+    remapped: Vec<u8>,
+    doubled: Vec<u8>,
+    /// Data rotated for an upside down panel
+    rotated: Vec<u8>,
+    /// Converted RGB565 data for colour controllers
+    #[cfg(feature = "ssd1351")]
+    colors: Vec<u8>,
+}
+
+impl Scratch {
+    fn new(geometry: &Geometry, controller: &Controller) -> Scratch {
+        let size = geometry.width / 2 * geometry.height;
+        #[cfg(feature = "ssd1351")]
+        let colors = match controller {
+            Controller::Ssd1351(_) => Vec::with_capacity(size * 4),
+            _ => Vec::new(),
+        };
+        #[cfg(not(feature = "ssd1351"))]
+        let _ = controller;
+        Scratch { staged: Vec::with_capacity(size), region: Vec::with_capacity(size), remapped: Vec::new(), doubled: Vec::with_capacity(size), rotated: Vec::new(), #[cfg(feature = "ssd1351")] colors }
+    }
+
+    /// Only rotated panels need room for rotated data
+    fn reserve_rotated(&mut self, geometry: &Geometry, orientation: Orientation) {
+        if orientation != Orientation::Normal {
+            self.rotated.reserve(geometry.width / 2 * geometry.height);
+        }
+    }
+}
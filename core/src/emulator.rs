@@ -0,0 +1,204 @@
+//! In memory emulation of the screen's controller
+
+use crate::{Error, Rect, Framebuffer, Geometry, Interface, STATUS_DISPLAY_OFF};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// What the controller is showing (commands 0xa4-0xa7)
+pub enum DisplayMode {
+    /// Show the contents of memory
+    Normal,
+    /// Every pixel at full brightness
+    AllOn,
+    /// Every pixel off
+    AllOff,
+    /// Show the contents of memory with the grey levels inverted
+    Inverse,
+}
+
+/// An emulated SSD1327 which keeps its display memory in a framebuffer
+///
+/// Only the commands this crate relies on change state (windowing, display on/off, contrast, display mode and whether it's scrolling), other commands have their arguments consumed and are otherwise ignored.
+pub struct Emulator {
+    geometry: Geometry,
+    ram: Framebuffer,
+    columns: (usize, usize),
+    rows: (usize, usize),
+    cursor: (usize, usize),
+    pending: Option<(u8, [u8; 15], usize)>,
+    on: bool,
+    contrast: u8,
+    mode: DisplayMode,
+    scrolling: bool,
+}
+
+impl Default for Emulator {
+    fn default() -> Self {
+        Emulator::new()
+    }
+}
+
+impl Emulator {
+    /// Create new, in the controller's power on state, wired to the 128x128 panel
+    pub fn new() -> Emulator {
+        Emulator::with_geometry(Geometry::default())
+    }
+
+    /// Create new, in the controller's power on state, wired to a panel of another size
+    pub fn with_geometry(geometry: Geometry) -> Emulator {
+        Emulator {
+            geometry,
+            ram: Framebuffer::new(128, 128),
+            columns: (0, 63),
+            rows: (0, 127),
+            cursor: (0, 0),
+            pending: None,
+            on: false,
+            contrast: 0x7f,
+            mode: DisplayMode::Normal,
+            scrolling: false,
+        }
+    }
+
+    /// The panel the controller is wired to
+    pub fn geometry(&self) -> Geometry {
+        self.geometry
+    }
+
+    /// The display memory, which is always 128x128 whatever the panel's size
+    pub fn ram(&self) -> &Framebuffer {
+        &self.ram
+    }
+
+    /// Returns true if the display is switched on
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+
+    /// The contrast setting
+    pub fn contrast(&self) -> u8 {
+        self.contrast
+    }
+
+    /// Returns true if scrolling is active
+    pub fn is_scrolling(&self) -> bool {
+        self.scrolling
+    }
+
+    /// The display mode
+    pub fn mode(&self) -> DisplayMode {
+        self.mode
+    }
+
+    /// Returns true if the cursor is at the start of the window, as it is once a window's data has all been written
+    pub fn at_window_start(&self) -> bool {
+        self.cursor == (self.columns.0, self.rows.0)
+    }
+
+    /// What the panel would be showing
+    pub fn frame(&self) -> Framebuffer {
+        let mut frame = self.ram.region(Rect::new(self.geometry.column_offset, 0, self.geometry.width, self.geometry.height));
+        if !self.on {
+            frame.fill(0);
+            return frame
+        }
+
+        match self.mode {
+            DisplayMode::Normal => (),
+            DisplayMode::AllOn => frame.fill(15),
+            DisplayMode::AllOff => frame.fill(0),
+            DisplayMode::Inverse => frame.as_bytes_mut().iter_mut().for_each(|b| *b = !*b),
+        }
+        frame
+    }
+
+    fn argument_count(cmd: u8) -> usize {
+        match cmd {
+            0x15 | 0x75 => 2,
+            0x81 | 0xa0 | 0xa1 | 0xa2 | 0xa8 | 0xab | 0xb1 | 0xb3 | 0xb5 | 0xb6 | 0xbc | 0xbe | 0xd5 | 0xfd => 1,
+            0x26 | 0x27 => 7,
+            0xb8 => 15,
+            _ => 0,
+        }
+    }
+
+    fn execute(&mut self, cmd: u8, args: &[u8]) {
+        match cmd {
+            0x15 => {
+                let start = (args[0] & 0x3f) as usize;
+                let end = (args[1] & 0x3f) as usize;
+                self.columns = (start, end.max(start));
+                self.cursor.0 = start;
+            },
+            0x75 => {
+                let start = (args[0] & 0x7f) as usize;
+                let end = (args[1] & 0x7f) as usize;
+                self.rows = (start, end.max(start));
+                self.cursor.1 = start;
+            },
+            0x81 => self.contrast = args[0],
+            0xa4 => self.mode = DisplayMode::Normal,
+            0xa5 => self.mode = DisplayMode::AllOn,
+            0xa6 => self.mode = DisplayMode::AllOff,
+            0xa7 => self.mode = DisplayMode::Inverse,
+            0x2e => self.scrolling = false,
+            0x2f => self.scrolling = true,
+            0xae => self.on = false,
+            0xaf => self.on = true,
+            _ => (),
+        }
+    }
+}
+
+impl Interface for Emulator {
+    fn command(&mut self, cmd: u8) -> Result<(), Error> {
+        match self.pending.take() {
+            Some((pending, mut args, len)) => {
+                args[len] = cmd;
+                if len + 1 == Emulator::argument_count(pending) {
+                    self.execute(pending, &args[..len + 1]);
+                } else {
+                    self.pending = Some((pending, args, len + 1));
+                }
+            },
+            None => {
+                if Emulator::argument_count(cmd) == 0 {
+                    self.execute(cmd, &[]);
+                } else {
+                    self.pending = Some((cmd, [0; 15], 0));
+                }
+            },
+        }
+        Ok(())
+    }
+
+    fn data(&mut self, data: &[u8]) -> Result<(), Error> {
+        let stride = self.ram.width() / 2;
+        for byte in data {
+            let (column, row) = self.cursor;
+            if column < stride && row < self.ram.height() {
+                self.ram.as_bytes_mut()[column + row * stride] = *byte;
+            }
+
+            self.cursor.0 += 1;
+            if self.cursor.0 > self.columns.1 {
+                self.cursor.0 = self.columns.0;
+                self.cursor.1 += 1;
+                if self.cursor.1 > self.rows.1 {
+                    self.cursor.1 = self.rows.0;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn status(&mut self) -> Result<Option<u8>, Error> {
+        Ok(Some(if self.on { 0 } else { STATUS_DISPLAY_OFF }))
+    }
+
+    fn reset(&mut self) {
+        let ram = self.ram.clone();
+        *self = Emulator::with_geometry(self.geometry);
+        // A hardware reset doesn't clear the display memory
+        self.ram = ram;
+    }
+}
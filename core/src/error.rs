@@ -0,0 +1,119 @@
+//! Errors
+
+use core::fmt::Display;
+
+#[cfg(feature = "rppal")]
+use rppal::{gpio, i2c, spi};
+
+use crate::{Rect, Geometry};
+
+#[derive(Debug)]
+#[non_exhaustive]
+/// Screen Error
+pub enum Error {
+    /// GPIO error
+    #[cfg(feature = "rppal")]
+    GPIO(gpio::Error),
+    /// i2c error
+    #[cfg(feature = "rppal")]
+    I2C(i2c::Error),
+    /// SPI error
+    #[cfg(feature = "rppal")]
+    SPI(spi::Error),
+    /// An i2c bus couldn't be opened, e.g. i2c isn't enabled, with the bus number
+    #[cfg(feature = "rppal")]
+    BusUnavailable(u8, i2c::Error),
+    /// Nothing answered at any of the screen's addresses on an i2c bus, with the bus number
+    NoDevice(u8),
+    /// Write to the bus failed, for interfaces without their own error type
+    Bus,
+    /// Image loading error
+    #[cfg(feature = "image")]
+    Image(image::ImageError),
+    /// Data didn't match the area it was written to, with the operation, the area (or expected size) and the length of the data given
+    OutOfBounds { operation: &'static str, rect: Rect, len: usize },
+    /// Grey level over 15
+    InvalidLevel(u8),
+    /// A window can't be addressed on the controller, with the reason
+    InvalidWindow { rect: Rect, reason: &'static str },
+    /// Panel geometry doesn't fit in the controller's memory
+    InvalidGeometry(Geometry),
+    /// The controller doesn't support an operation
+    Unsupported(&'static str),
+    /// The screen can't be written while the controller is scrolling
+    Scrolling,
+    /// IO error
+    #[cfg(feature = "std")]
+    IO(std::io::Error),
+    /// The screen is in use by another process
+    Busy,
+    /// The controller's status doesn't match what was sent to it
+    Desync,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            #[cfg(feature = "rppal")]
+            Error::GPIO(e) => f.write_fmt(format_args!("{}", e)),
+            #[cfg(feature = "rppal")]
+            Error::I2C(e) => f.write_fmt(format_args!("{}", e)),
+            #[cfg(feature = "rppal")]
+            Error::SPI(e) => f.write_fmt(format_args!("{}", e)),
+            #[cfg(feature = "rppal")]
+            Error::BusUnavailable(bus, e) => f.write_fmt(format_args!("i2c bus {} is unavailable (is i2c enabled?): {}", bus, e)),
+            Error::NoDevice(bus) => f.write_fmt(format_args!("No screen found on i2c bus {} at 0x3c or 0x3d, check the wiring and address jumper", bus)),
+            Error::Bus => f.write_str("Bus write failed"),
+            #[cfg(feature = "image")]
+            Error::Image(e) => f.write_fmt(format_args!("{}", e)),
+            Error::OutOfBounds { operation, rect, len } => f.write_fmt(format_args!(
+                "Out of bounds in {}: {} bytes for {}x{} at ({}, {})", operation, len, rect.w, rect.h, rect.x, rect.y
+            )),
+            Error::InvalidLevel(level) => f.write_fmt(format_args!("Grey level {} is over 15", level)),
+            Error::InvalidWindow { rect, reason } => f.write_fmt(format_args!(
+                "Invalid window {}x{} at ({}, {}): {}", rect.w, rect.h, rect.x, rect.y, reason
+            )),
+            Error::InvalidGeometry(geometry) => f.write_fmt(format_args!(
+                "Invalid panel geometry: {}x{} at column {}", geometry.width, geometry.height, geometry.column_offset
+            )),
+            Error::Unsupported(operation) => f.write_fmt(format_args!("{} isn't supported by this controller", operation)),
+            Error::Scrolling => f.write_str("Screen can't be written while scrolling"),
+            #[cfg(feature = "std")]
+            Error::IO(e) => f.write_fmt(format_args!("{}", e)),
+            Error::Busy => f.write_str("Screen is in use by another process"),
+            Error::Desync => f.write_str("Controller status doesn't match what was sent"),
+        }
+    }
+}
+
+impl Error {
+    /// Returns true for bus errors, which are worth retrying
+    pub fn is_bus_error(&self) -> bool {
+        match self {
+            #[cfg(feature = "rppal")]
+            Error::I2C(_) | Error::SPI(_) => true,
+            Error::Bus => true,
+            _ => false,
+        }
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "rppal")]
+            Error::GPIO(e) => Some(e),
+            #[cfg(feature = "rppal")]
+            Error::I2C(e) => Some(e),
+            #[cfg(feature = "rppal")]
+            Error::SPI(e) => Some(e),
+            #[cfg(feature = "rppal")]
+            Error::BusUnavailable(_, e) => Some(e),
+            #[cfg(feature = "image")]
+            Error::Image(e) => Some(e),
+            #[cfg(feature = "std")]
+            Error::IO(e) => Some(e),
+            _ => None,
+        }
+    }
+}
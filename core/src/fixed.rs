@@ -0,0 +1,144 @@
+//! A framebuffer sized at compile time, for drawing without the heap
+
+use crate::{Driver, Error, Rect, Gray4};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A 4 bit greyscale image sized at compile time, which can live on the stack or in a static
+///
+/// Pixels are stored one per byte, so drawing never packs or shifts. [`Driver::present_fixed`] packs them into the driver's framebuffer,
+/// neither drawing nor presenting allocates.
+pub struct FixedFramebuffer<const W: usize, const H: usize> {
+    pixels: [[u8; W]; H],
+}
+
+impl<const W: usize, const H: usize> Default for FixedFramebuffer<W, H> {
+    fn default() -> Self {
+        FixedFramebuffer::new()
+    }
+}
+
+impl<const W: usize, const H: usize> FixedFramebuffer<W, H> {
+    /// Create a new black framebuffer, usable in a `static`
+    pub const fn new() -> FixedFramebuffer<W, H> {
+        FixedFramebuffer { pixels: [[0; W]; H] }
+    }
+
+    /// Width in pixels
+    pub const fn width(&self) -> usize {
+        W
+    }
+
+    /// Height in pixels
+    pub const fn height(&self) -> usize {
+        H
+    }
+
+    /// The whole framebuffer as a rectangle
+    pub const fn bounds(&self) -> Rect {
+        Rect::new(0, 0, W, H)
+    }
+
+    /// The pixels (0-15), row by row
+    pub fn rows(&self) -> &[[u8; W]; H] {
+        &self.pixels
+    }
+
+    /// Get a pixel (0-15), returns 0 outside of the framebuffer
+    pub fn get_pixel(&self, x: usize, y: usize) -> u8 {
+        if x >= W || y >= H {
+            return 0
+        }
+        self.pixels[y][x]
+    }
+
+    /// Get a pixel, None outside of the framebuffer
+    pub fn get(&self, x: usize, y: usize) -> Option<Gray4> {
+        if x >= W || y >= H {
+            return None
+        }
+        Gray4::new(self.pixels[y][x])
+    }
+
+    /// Set a pixel (0-15), ignored outside of the framebuffer
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: u8) {
+        if x < W && y < H {
+            self.pixels[y][x] = color % 16;
+        }
+    }
+
+    /// Set a pixel, returns false outside of the framebuffer
+    pub fn set(&mut self, x: usize, y: usize, gray: Gray4) -> bool {
+        if x >= W || y >= H {
+            return false
+        }
+        self.pixels[y][x] = gray.level();
+        true
+    }
+
+    /// Fill the whole framebuffer with a colour (0-15)
+    pub fn fill(&mut self, color: u8) {
+        for row in self.pixels.iter_mut() {
+            row.fill(color % 16);
+        }
+    }
+
+    /// Fill a rectangle with a colour (0-15), clipped to the framebuffer
+    pub fn fill_rect(&mut self, rect: Rect, color: u8) {
+        if let Some(rect) = rect.intersection(&self.bounds()) {
+            for row in self.pixels[rect.y..rect.bottom()].iter_mut() {
+                row[rect.x..rect.right()].fill(color % 16);
+            }
+        }
+    }
+
+    /// Draw a horizontal line with a colour (0-15), clipped to the framebuffer
+    pub fn hline(&mut self, x: usize, y: usize, len: usize, color: u8) {
+        self.fill_rect(Rect::new(x, y, len, 1), color);
+    }
+
+    /// Copy a rectangle of packed data (two pixels per byte, even pixel in the high nibble) into the framebuffer at a coord, clipped to the framebuffer.
+    /// An odd last column is dropped, as [`pack_pixels`](crate::pack::pack_pixels) drops it
+    pub fn blit(&mut self, x: usize, y: usize, width: usize, height: usize, data: &[u8]) -> Result<(), Error> {
+        let stride = width / 2;
+        if data.len() < stride * height {
+            return Err(Error::OutOfBounds { operation: "blit", rect: Rect::new(x, y, width, height), len: data.len() })
+        }
+
+        for row in 0..height {
+            for column in 0..stride * 2 {
+                let byte = data[row * stride + column / 2];
+                let color = if column.is_multiple_of(2) { byte >> 4 } else { byte & 0x0f };
+                self.set_pixel(x + column, y + row, color);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Driver {
+    /// Copy a fixed size frame into the framebuffer and write the area that changed to the screen. Returns the area written.
+    ///
+    /// The frame must be the size of the (logical) screen. Nothing is allocated once the screen has been flushed
+    pub fn present_fixed<const W: usize, const H: usize>(&mut self, frame: &FixedFramebuffer<W, H>) -> Result<Option<Rect>, Error> {
+        if W != self.width() || H != self.height() {
+            return Err(Error::OutOfBounds { operation: "present_fixed", rect: self.framebuffer().bounds(), len: W * H })
+        }
+
+        let mut changed: Option<Rect> = None;
+        let framebuffer = self.framebuffer_mut();
+        for (y, row) in frame.rows().iter().enumerate() {
+            let Some(first) = (0..W).find(|&x| framebuffer.get_pixel(x, y) != row[x]) else { continue };
+            let last = (first..W).rev().find(|&x| framebuffer.get_pixel(x, y) != row[x]).unwrap_or(first);
+            for (x, color) in row.iter().enumerate().take(last + 1).skip(first) {
+                framebuffer.set_pixel(x, y, *color);
+            }
+            let rect = Rect::new(first, y, last + 1 - first, 1);
+            changed = Some(changed.map_or(rect, |changed| changed.union(&rect)));
+        }
+
+        if let Some(rect) = changed {
+            self.flush_rect(rect)?;
+        }
+        Ok(changed)
+    }
+}
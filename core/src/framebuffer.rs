@@ -0,0 +1,310 @@
+//! In memory copy of the screen
+
+use alloc::{vec, vec::Vec};
+
+#[cfg(feature = "image")]
+use image::{GrayImage, Luma};
+
+use crate::{Error, Rect, Gray4, BlendMode};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A 4 bit greyscale image packed two pixels per byte, in the same layout as the screen's memory
+///
+/// The even pixel of each pair is stored in the high nibble.
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    data: Vec<u8>,
+}
+
+/// Packed image data which knows its own size, so it can't be written with the wrong stride
+///
+/// This is the same type as the screen's framebuffer, named for use as image data.
+pub type PackedBuffer = Framebuffer;
+
+impl Framebuffer {
+    /// Create a new black framebuffer (the width is rounded up to be even)
+    pub fn new(width: usize, height: usize) -> Framebuffer {
+        let width = width + width % 2;
+        Framebuffer { width, height, data: vec![0x00; width / 2 * height] }
+    }
+
+    /// Create a framebuffer from packed data
+    pub fn from_packed(width: usize, height: usize, data: Vec<u8>) -> Result<Framebuffer, Error> {
+        if !width.is_multiple_of(2) || data.len() != width / 2 * height {
+            return Err(Error::OutOfBounds { operation: "from_packed", rect: Rect::new(0, 0, width, height), len: data.len() })
+        }
+        Ok(Framebuffer { width, height, data })
+    }
+
+    #[cfg(feature = "image")]
+    /// Create a framebuffer from a greyscale image (pixel values 0-15)
+    pub fn from_image(image: &GrayImage) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(image.width() as usize, image.height() as usize);
+        framebuffer.draw_image(0, 0, image);
+        framebuffer
+    }
+
+    /// Create a framebuffer with the level of each pixel from a function (the width is rounded up to be even)
+    pub fn from_fn<F: FnMut(usize, usize) -> Gray4>(width: usize, height: usize, mut f: F) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                framebuffer.set(x, y, f(x, y));
+            }
+        }
+        framebuffer
+    }
+
+    /// Width in pixels
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height in pixels
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Area covered by the framebuffer
+    pub fn bounds(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+
+    /// The packed data
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// The packed data
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.data
+    }
+
+    /// Get a pixel (0-15), returns 0 outside of the framebuffer
+    pub fn get_pixel(&self, x: usize, y: usize) -> u8 {
+        if x >= self.width || y >= self.height {
+            return 0
+        }
+
+        let byte = self.data[x / 2 + y * (self.width / 2)];
+        if x.is_multiple_of(2) {
+            byte >> 4
+        } else {
+            byte & 0x0f
+        }
+    }
+
+    /// Get a pixel, None outside of the framebuffer
+    pub fn get(&self, x: usize, y: usize) -> Option<Gray4> {
+        if x >= self.width || y >= self.height {
+            return None
+        }
+        Gray4::new(self.get_pixel(x, y))
+    }
+
+    /// Set a pixel, returns false outside of the framebuffer
+    pub fn set(&mut self, x: usize, y: usize, gray: Gray4) -> bool {
+        if x >= self.width || y >= self.height {
+            return false
+        }
+        self.set_pixel(x, y, gray.level());
+        true
+    }
+
+    /// Every pixel with its coord, row by row
+    pub fn pixels(&self) -> impl Iterator<Item = (usize, usize, Gray4)> + '_ {
+        (0..self.height).flat_map(move |y| (0..self.width).map(move |x| (x, y, Gray4::saturating(self.get_pixel(x, y)))))
+    }
+
+    /// Set a pixel (0-15), ignored outside of the framebuffer
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: u8) {
+        if x >= self.width || y >= self.height {
+            return
+        }
+
+        let addr = x / 2 + y * (self.width / 2);
+        let color = color % 16;
+        if x.is_multiple_of(2) {
+            self.data[addr] = (self.data[addr] & 0x0f) | (color << 4);
+        } else {
+            self.data[addr] = (self.data[addr] & 0xf0) | color;
+        }
+    }
+
+    /// Fill the whole framebuffer with a colour (0-15)
+    pub fn fill(&mut self, color: u8) {
+        let color = color % 16;
+        self.data.fill(color << 4 | color);
+    }
+
+    /// Fill a rectangle with a colour (0-15), clipped to the framebuffer
+    pub fn fill_rect(&mut self, rect: Rect, color: u8) {
+        if let Some(rect) = rect.intersection(&self.bounds()) {
+            for y in rect.y..rect.bottom() {
+                self.hline(rect.x, y, rect.w, color);
+            }
+        }
+    }
+
+    /// Draw a horizontal line with a colour (0-15), clipped to the framebuffer. Whole bytes (pixel pairs) are written at once
+    pub fn hline(&mut self, x: usize, y: usize, len: usize, color: u8) {
+        if y >= self.height || x >= self.width {
+            return
+        }
+
+        let color = color % 16;
+        let (mut start, end) = (x, (x + len).min(self.width));
+        if start < end && !start.is_multiple_of(2) {
+            self.set_pixel(start, y, color);
+            start += 1;
+        }
+
+        let pairs = (end - start.min(end)) / 2;
+        let row = y * (self.width / 2);
+        self.data[row + start / 2..row + start / 2 + pairs].fill(color << 4 | color);
+
+        if start + pairs * 2 < end {
+            self.set_pixel(end - 1, y, color);
+        }
+    }
+
+    #[cfg(feature = "image")]
+    /// Draw a greyscale image (pixel values 0-15) at the specified coord, clipped to the framebuffer
+    pub fn draw_image(&mut self, x: usize, y: usize, image: &GrayImage) {
+        for (px, py, pixel) in image.enumerate_pixels() {
+            self.set_pixel(x + px as usize, y + py as usize, pixel.0[0]);
+        }
+    }
+
+    /// Copy packed data of a certain size into the framebuffer at the specified coord (x is rounded down to be even), clipped to the framebuffer
+    pub fn blit(&mut self, x: usize, y: usize, width: usize, height: usize, data: &[u8]) -> Result<(), Error> {
+        if data.len() < width / 2 * height {
+            return Err(Error::OutOfBounds { operation: "blit", rect: Rect::new(x, y, width, height), len: data.len() })
+        }
+
+        let x = x - x % 2;
+        let stride = self.width / 2;
+        for row in 0..height {
+            if y + row >= self.height {
+                break
+            }
+            for col in 0..(width / 2) {
+                if x / 2 + col >= stride {
+                    break
+                }
+                self.data[x / 2 + col + (y + row) * stride] = data[col + row * (width / 2)];
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy another framebuffer into this one at the specified coord, clipped to the framebuffer
+    pub fn blit_framebuffer(&mut self, x: usize, y: usize, other: &Framebuffer) {
+        if x.is_multiple_of(2) {
+            let _ = self.blit(x, y, other.width, other.height, &other.data);
+        } else {
+            for py in 0..other.height {
+                for px in 0..other.width {
+                    self.set_pixel(x + px, y + py, other.get_pixel(px, py));
+                }
+            }
+        }
+    }
+
+    /// Blend another framebuffer into this one at the specified coord, clipped to the framebuffer
+    pub fn blend_framebuffer(&mut self, x: usize, y: usize, other: &Framebuffer, mode: BlendMode) {
+        if mode == BlendMode::Replace {
+            return self.blit_framebuffer(x, y, other)
+        }
+
+        let width = other.width.min(self.width.saturating_sub(x));
+        let height = other.height.min(self.height.saturating_sub(y));
+        for py in 0..height {
+            for px in 0..width {
+                let level = mode.blend(other.get_pixel(px, py), self.get_pixel(x + px, y + py));
+                self.set_pixel(x + px, y + py, level);
+            }
+        }
+    }
+
+    /// Copy a section out as packed data (the rectangle is grown to an even x and width), clipped to the framebuffer
+    pub fn region(&self, rect: Rect) -> Framebuffer {
+        let mut data = Vec::new();
+        match self.region_into(rect, &mut data) {
+            Some(rect) => Framebuffer { width: rect.w, height: rect.h, data },
+            None => Framebuffer::new(0, 0),
+        }
+    }
+
+    /// Copy a section out as packed data into a reusable buffer, which doesn't allocate if it has the capacity. Returns the area copied
+    pub fn region_into(&self, rect: Rect, data: &mut Vec<u8>) -> Option<Rect> {
+        data.clear();
+        let rect = rect.align_even().intersection(&self.bounds())?;
+
+        let stride = self.width / 2;
+        for y in rect.y..rect.bottom() {
+            let start = rect.x / 2 + y * stride;
+            data.extend_from_slice(&self.data[start..start + rect.w / 2]);
+        }
+        Some(rect)
+    }
+
+    /// Copy another framebuffer of the same size into this one without allocating
+    pub fn copy_from(&mut self, other: &Framebuffer) -> Result<(), Error> {
+        if other.width != self.width || other.height != self.height {
+            return Err(Error::OutOfBounds { operation: "copy_from", rect: self.bounds(), len: other.data.len() })
+        }
+        self.data.copy_from_slice(&other.data);
+        Ok(())
+    }
+
+    /// A copy rotated by 180 degrees
+    pub fn rotate180(&self) -> Framebuffer {
+        let mut rotated = Framebuffer::new(self.width, self.height);
+        self.rotate180_into(&mut rotated);
+        rotated
+    }
+
+    /// Write a copy rotated by 180 degrees into a framebuffer, resized to match if needed
+    pub fn rotate180_into(&self, rotated: &mut Framebuffer) {
+        if rotated.width != self.width || rotated.height != self.height {
+            *rotated = Framebuffer::new(self.width, self.height);
+        }
+
+        // Reversing the bytes reverses the pixel pairs, then each pair is swapped
+        rotated.data.copy_from_slice(&self.data);
+        rotated.data.reverse();
+        rotated.data.iter_mut().for_each(|b| *b = b.rotate_left(4));
+    }
+
+    #[cfg(feature = "image")]
+    /// Convert to a greyscale image (pixel values 0-15)
+    pub fn to_image(&self) -> GrayImage {
+        GrayImage::from_fn(self.width as u32, self.height as u32, |x, y| Luma([self.get_pixel(x as usize, y as usize)]))
+    }
+
+    /// Bounding box of the pixels that differ from another framebuffer of the same size
+    pub fn diff(&self, other: &Framebuffer) -> Option<Rect> {
+        if self.width != other.width || self.height != other.height {
+            return Some(self.bounds())
+        }
+
+        let stride = self.width / 2;
+        let mut bounds: Option<Rect> = None;
+        for y in 0..self.height {
+            let row = y * stride;
+            let a = &self.data[row..row + stride];
+            let b = &other.data[row..row + stride];
+            if a == b {
+                continue
+            }
+
+            let first = a.iter().zip(b).position(|(a, b)| a != b).unwrap_or(0);
+            let last = a.iter().zip(b).rposition(|(a, b)| a != b).unwrap_or(0);
+            let changed = Rect::new(first * 2, y, (last - first + 1) * 2, 1);
+            bounds = Some(bounds.map(|b| b.union(&changed)).unwrap_or(changed));
+        }
+        bounds
+    }
+}
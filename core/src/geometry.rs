@@ -0,0 +1,198 @@
+//! Geometry
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// A rectangle on the screen
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub w: usize,
+    pub h: usize,
+}
+
+impl Rect {
+    /// Create new
+    pub const fn new(x: usize, y: usize, w: usize, h: usize) -> Rect {
+        Rect { x, y, w, h }
+    }
+
+    /// The x coord one past the right edge
+    pub const fn right(&self) -> usize {
+        self.x + self.w
+    }
+
+    /// The y coord one past the bottom edge
+    pub const fn bottom(&self) -> usize {
+        self.y + self.h
+    }
+
+    /// Returns true if the rectangle has no area
+    pub const fn is_empty(&self) -> bool {
+        self.w == 0 || self.h == 0
+    }
+
+    /// Returns true if the coord is inside the rectangle
+    pub const fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    /// Smallest rectangle containing both rectangles, empty rectangles are ignored
+    pub fn union(&self, other: &Rect) -> Rect {
+        if self.is_empty() {
+            return *other
+        }
+        if other.is_empty() {
+            return *self
+        }
+
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Rect::new(x, y, self.right().max(other.right()) - x, self.bottom().max(other.bottom()) - y)
+    }
+
+    /// Overlapping area of both rectangles
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+
+        if right > x && bottom > y {
+            Some(Rect::new(x, y, right - x, bottom - y))
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the rectangles overlap
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Returns true if the rectangles overlap or share part of an edge
+    pub fn touches(&self, other: &Rect) -> bool {
+        if self.is_empty() || other.is_empty() {
+            return false
+        }
+
+        let overlaps_x = self.x < other.right() && other.x < self.right();
+        let overlaps_y = self.y < other.bottom() && other.y < self.bottom();
+        let touches_x = self.x <= other.right() && other.x <= self.right();
+        let touches_y = self.y <= other.bottom() && other.y <= self.bottom();
+        (overlaps_x && touches_y) || (touches_x && overlaps_y)
+    }
+
+    /// Grow the rectangle horizontally so x and the width are even, as the screen is written two pixels at a time
+    pub const fn align_even(&self) -> Rect {
+        let x = self.x - self.x % 2;
+        let right = self.right() + self.right() % 2;
+        Rect::new(x, self.y, right - x, self.h)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// Which way up the panel is mounted
+pub enum Orientation {
+    #[default]
+    Normal,
+    /// Upside down, everything drawn is rotated 180 degrees on its way to the screen
+    Rotated180,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// Which point of an area something is placed against
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    TopCenter,
+    TopRight,
+    CenterLeft,
+    Center,
+    CenterRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Place something of a size against the anchor point of an area, moved in from the anchored edges by an offset
+    /// (on a centred axis the offset moves it right or down). Anything too big to fit is placed at the area's top left
+    pub fn place(self, area: Rect, width: usize, height: usize, offset: (usize, usize)) -> Rect {
+        let (dx, dy) = offset;
+        let (free_x, free_y) = (area.w.saturating_sub(width), area.h.saturating_sub(height));
+        let x = match self {
+            Anchor::TopLeft | Anchor::CenterLeft | Anchor::BottomLeft => dx,
+            Anchor::TopCenter | Anchor::Center | Anchor::BottomCenter => free_x / 2 + dx,
+            Anchor::TopRight | Anchor::CenterRight | Anchor::BottomRight => free_x.saturating_sub(dx),
+        };
+        let y = match self {
+            Anchor::TopLeft | Anchor::TopCenter | Anchor::TopRight => dy,
+            Anchor::CenterLeft | Anchor::Center | Anchor::CenterRight => free_y / 2 + dy,
+            Anchor::BottomLeft | Anchor::BottomCenter | Anchor::BottomRight => free_y.saturating_sub(dy),
+        };
+        Rect::new(area.x + x, area.y + y, width, height)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The size of an SSD1327 panel and where it sits in the controller's 128x128 memory
+///
+/// The init sequence's column range and multiplex ratio are derived from it.
+pub struct Geometry {
+    /// Width in pixels, must be even and at most 128
+    pub width: usize,
+    /// Height in pixels, at most 128
+    pub height: usize,
+    /// First memory column (in pixels, must be even) wired to the panel
+    pub column_offset: usize,
+    /// Display offset (command 0xa2), the memory row shown on the panel's first row
+    pub row_offset: usize,
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Geometry::SIZE_128X128
+    }
+}
+
+impl Geometry {
+    /// The Waveshare 1.5 inch 128x128 panel
+    pub const SIZE_128X128: Geometry = Geometry::new(128, 128);
+    /// 128x96 panels, such as the Adafruit 1.12 inch
+    pub const SIZE_128X96: Geometry = Geometry::new(128, 96);
+    /// 96x96 panels, such as the Seeed 1.12 inch, wired to the middle columns
+    pub const SIZE_96X96: Geometry = Geometry::new(96, 96);
+
+    /// Create new, with the panel centred on the controller's columns
+    pub const fn new(width: usize, height: usize) -> Geometry {
+        let column_offset = 128usize.saturating_sub(width) / 2;
+        Geometry { width, height, column_offset: column_offset - column_offset % 2, row_offset: 0 }
+    }
+
+    /// Set where the panel sits in the controller's memory
+    pub const fn with_offset(mut self, column_offset: usize, row_offset: usize) -> Geometry {
+        self.column_offset = column_offset;
+        self.row_offset = row_offset;
+        self
+    }
+
+    /// Returns true if the panel fits in the controller's memory
+    pub const fn is_valid(&self) -> bool {
+        self.width > 0 && self.height > 0 && self.width.is_multiple_of(2) && self.column_offset.is_multiple_of(2)
+            && self.column_offset + self.width <= 128 && self.height <= 128 && self.row_offset < 128
+    }
+
+    /// Area of the panel
+    pub const fn bounds(&self) -> Rect {
+        Rect::new(0, 0, self.width, self.height)
+    }
+
+    /// First and last column addresses (command 0x15), each column holds two pixels
+    pub const fn columns(&self) -> (u8, u8) {
+        ((self.column_offset / 2) as u8, ((self.column_offset + self.width) / 2 - 1) as u8)
+    }
+
+    /// Multiplex ratio (command 0xa8), the number of rows driven less one
+    pub const fn mux_ratio(&self) -> u8 {
+        (self.height - 1) as u8
+    }
+}
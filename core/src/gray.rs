@@ -0,0 +1,103 @@
+//! Typed 4 bit grey levels
+
+use crate::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+/// A 4 bit grey level (0-15), as stored in the screen's memory
+pub struct Gray4(u8);
+
+impl Gray4 {
+    /// Off
+    pub const BLACK: Gray4 = Gray4(0);
+    /// Full brightness
+    pub const WHITE: Gray4 = Gray4(15);
+
+    /// Create new, None if the level is over 15
+    pub const fn new(level: u8) -> Option<Gray4> {
+        if level < 16 {
+            Some(Gray4(level))
+        } else {
+            None
+        }
+    }
+
+    /// Create new, levels over 15 are clamped to white
+    pub const fn saturating(level: u8) -> Gray4 {
+        if level < 16 {
+            Gray4(level)
+        } else {
+            Gray4::WHITE
+        }
+    }
+
+    /// Convert an 8 bit grey value, the same way images are quantized
+    pub const fn from_8bit(value: u8) -> Gray4 {
+        Gray4(value >> 4)
+    }
+
+    /// The level (0-15)
+    pub const fn level(self) -> u8 {
+        self.0
+    }
+
+    /// The level spread over the full 8 bit range
+    pub const fn to_8bit(self) -> u8 {
+        self.0 * 17
+    }
+
+    /// Pack a pair of pixels into a byte, in the screen's layout
+    pub const fn pack(left: Gray4, right: Gray4) -> u8 {
+        left.0 << 4 | right.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+/// How a source level is combined with the level already there when blitting or compositing
+pub enum BlendMode {
+    /// Overwrite with the source
+    #[default]
+    Replace,
+    /// The source's level is its opacity as well as its colour, so black is transparent and white opaque
+    Over,
+    /// Add the levels, saturating at white
+    Add,
+    /// Multiply the levels, white leaves the destination unchanged and black clears it
+    Multiply,
+    /// Keep the brighter level
+    Max,
+    /// Mix the source in with a fixed opacity (0-15), e.g. for translucent overlays
+    Mix(u8),
+}
+
+impl BlendMode {
+    /// Combine a source level (0-15) with a destination level (0-15)
+    pub fn blend(self, source: u8, destination: u8) -> u8 {
+        let (s, d) = (source.min(15) as u16, destination.min(15) as u16);
+        let level = match self {
+            BlendMode::Replace => s,
+            BlendMode::Over => s + (d * (15 - s) + 7) / 15,
+            BlendMode::Add => s + d,
+            BlendMode::Multiply => (s * d + 7) / 15,
+            BlendMode::Max => s.max(d),
+            BlendMode::Mix(opacity) => {
+                let a = opacity.min(15) as u16;
+                (s * a + d * (15 - a) + 7) / 15
+            },
+        };
+        level.min(15) as u8
+    }
+}
+
+impl From<Gray4> for u8 {
+    fn from(gray: Gray4) -> u8 {
+        gray.0
+    }
+}
+
+impl TryFrom<u8> for Gray4 {
+    type Error = Error;
+
+    fn try_from(level: u8) -> Result<Gray4, Error> {
+        Gray4::new(level).ok_or(Error::InvalidLevel(level))
+    }
+}
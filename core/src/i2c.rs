@@ -0,0 +1,233 @@
+//! The screen over i2c on a Raspberry Pi, using rppal
+
+use std::{sync::{Arc, Mutex, MutexGuard}, thread, time::Duration};
+
+use rppal::{gpio::{Gpio, OutputPin}, i2c::I2c};
+
+use crate::{Error, Interface, lock::DisplayLock};
+
+/// Most bytes i2c-dev writes in one transaction
+const MAX_WRITE: usize = 8191;
+
+/// The addresses the screen can be set to with its address jumper, in the order they're probed
+pub const ADDRESSES: [u16; 2] = [0x3c, 0x3d];
+
+/// Write display data as i2c writes prefixed with the data control byte, one per chunk of up to [`MAX_WRITE`] bytes
+fn write_data(i2c_bus: &mut I2c, buffer: &mut Vec<u8>, data: &[u8]) -> Result<(), Error> {
+    for chunk in data.chunks(MAX_WRITE) {
+        buffer.clear();
+        buffer.push(0x40);
+        buffer.extend_from_slice(chunk);
+        i2c_bus.write(buffer).map_err(Error::I2C)?;
+    }
+    Ok(())
+}
+
+/// Write command bytes as i2c writes prefixed with the command control byte, one per chunk of up to [`MAX_WRITE`] bytes
+fn write_commands(i2c_bus: &mut I2c, buffer: &mut Vec<u8>, cmds: &[u8]) -> Result<(), Error> {
+    for chunk in cmds.chunks(MAX_WRITE - 1) {
+        buffer.clear();
+        buffer.push(0x00);
+        buffer.extend_from_slice(chunk);
+        i2c_bus.write(buffer).map_err(Error::I2C)?;
+    }
+    Ok(())
+}
+
+/// The screen connected over i2c, with an optional GPIO reset pin
+///
+/// The screen is locked while the interface exists, so a second process opening it gets [`Error::Busy`] rather than interleaving writes.
+pub struct I2cInterface {
+    reset_pin: Option<OutputPin>,
+    i2c_bus: I2c,
+    buffer: Vec<u8>,
+    _lock: Option<DisplayLock>,
+}
+
+impl I2cInterface {
+    /// Create new
+    pub fn new(address: u16, bus: u8, reset: u8) -> Result<I2cInterface, Error> {
+        let lock = DisplayLock::acquire(bus, address)?;
+
+        let gpio = Gpio::new().map_err(Error::GPIO)?;
+        let mut reset_pin = gpio.get(reset).map_err(Error::GPIO)?.into_output();
+        reset_pin.set_low();
+
+        let i2c_bus = I2cInterface::open_bus(address, bus)?;
+        Ok(I2cInterface { reset_pin: Some(reset_pin), i2c_bus, buffer: Vec::new(), _lock: Some(lock) })
+    }
+
+    /// Create new without a reset pin, for boards where reset is tied high or handled elsewhere. GPIO isn't touched
+    pub fn without_reset(address: u16, bus: u8) -> Result<I2cInterface, Error> {
+        let lock = DisplayLock::acquire(bus, address)?;
+        let i2c_bus = I2cInterface::open_bus(address, bus)?;
+        Ok(I2cInterface { reset_pin: None, i2c_bus, buffer: Vec::new(), _lock: Some(lock) })
+    }
+
+    /// Create new from a bus and reset pin that are already set up, the bus's slave address must already be set to the screen's
+    ///
+    /// The screen isn't locked, whoever configured the bus is responsible for not sharing the screen.
+    pub fn from_parts(i2c_bus: I2c, reset_pin: Option<OutputPin>) -> I2cInterface {
+        I2cInterface { reset_pin, i2c_bus, buffer: Vec::new(), _lock: None }
+    }
+
+    /// Find the screen on a bus by reading the status byte at each of [`ADDRESSES`], returns the first address that answers.
+    ///
+    /// Returns [`Error::BusUnavailable`] if the bus can't be opened and [`Error::NoDevice`] if nothing answers.
+    /// The screen must be out of reset, a reset pin left floating low hides it
+    pub fn probe(bus: u8) -> Result<u16, Error> {
+        let mut i2c_bus = I2c::with_bus(bus).map_err(|e| Error::BusUnavailable(bus, e))?;
+        for address in ADDRESSES {
+            i2c_bus.set_slave_address(address).map_err(Error::I2C)?;
+            if i2c_bus.smbus_receive_byte().is_ok() {
+                return Ok(address)
+            }
+        }
+        Err(Error::NoDevice(bus))
+    }
+
+    fn open_bus(address: u16, bus: u8) -> Result<I2c, Error> {
+        let mut i2c_bus = I2c::with_bus(bus).map_err(|e| Error::BusUnavailable(bus, e))?;
+        i2c_bus.set_slave_address(address).map_err(Error::I2C)?;
+        Ok(i2c_bus)
+    }
+}
+
+impl Interface for I2cInterface {
+    fn command(&mut self, cmd: u8) -> Result<(), Error> {
+        self.i2c_bus.smbus_write_byte(0x00, cmd).map_err(Error::I2C)
+    }
+
+    fn commands(&mut self, cmds: &[u8]) -> Result<(), Error> {
+        write_commands(&mut self.i2c_bus, &mut self.buffer, cmds)
+    }
+
+    fn data(&mut self, data: &[u8]) -> Result<(), Error> {
+        write_data(&mut self.i2c_bus, &mut self.buffer, data)
+    }
+
+    fn status(&mut self) -> Result<Option<u8>, Error> {
+        self.i2c_bus.smbus_receive_byte().map(Some).map_err(Error::I2C)
+    }
+
+    fn reset(&mut self) {
+        if let Some(reset_pin) = &mut self.reset_pin {
+            reset_pin.set_high();
+            thread::sleep(Duration::from_millis(100));
+            reset_pin.set_low();
+            thread::sleep(Duration::from_millis(100));
+            reset_pin.set_high();
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
+
+/// An i2c bus shared between several screens and anything else on it (e.g. sensors)
+///
+/// Clones refer to the same bus. Each user locks the bus for a transaction and sets the slave address it needs,
+/// so other devices should also go through [`SharedBus::lock`] and set their address first.
+#[derive(Clone)]
+pub struct SharedBus {
+    i2c_bus: Arc<Mutex<I2c>>,
+}
+
+impl SharedBus {
+    /// Open an i2c bus for sharing
+    pub fn new(bus: u8) -> Result<SharedBus, Error> {
+        Ok(SharedBus::from_i2c(I2c::with_bus(bus).map_err(|e| Error::BusUnavailable(bus, e))?))
+    }
+
+    /// Share a bus the application has already opened
+    pub fn from_i2c(i2c_bus: I2c) -> SharedBus {
+        SharedBus { i2c_bus: Arc::new(Mutex::new(i2c_bus)) }
+    }
+
+    /// Lock the bus for a transaction
+    pub fn lock(&self) -> MutexGuard<'_, I2c> {
+        self.i2c_bus.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A screen on a [`SharedBus`], optionally behind an i2c multiplexer (e.g. a TCA9548A) for screens with the same address
+///
+/// The bus is only held while a command or a block of data is being written.
+pub struct SharedI2cInterface {
+    bus: SharedBus,
+    address: u16,
+    mux: Option<(u16, u8)>,
+    reset_pin: Option<OutputPin>,
+    buffer: Vec<u8>,
+    _lock: DisplayLock,
+}
+
+impl SharedI2cInterface {
+    /// Create new for the screen at an address, with an optional GPIO reset pin
+    pub fn new(bus: &SharedBus, address: u16, reset: Option<u8>) -> Result<SharedI2cInterface, Error> {
+        let lock = DisplayLock::acquire(bus.lock().bus(), address)?;
+        SharedI2cInterface::create(bus, address, None, reset, lock)
+    }
+
+    /// Create new for the screen at an address behind a multiplexer channel, the channel is selected before every transaction
+    pub fn muxed(bus: &SharedBus, mux_address: u16, channel: u8, address: u16, reset: Option<u8>) -> Result<SharedI2cInterface, Error> {
+        let lock = DisplayLock::acquire_muxed(bus.lock().bus(), mux_address, channel, address)?;
+        SharedI2cInterface::create(bus, address, Some((mux_address, channel)), reset, lock)
+    }
+
+    fn create(bus: &SharedBus, address: u16, mux: Option<(u16, u8)>, reset: Option<u8>, lock: DisplayLock) -> Result<SharedI2cInterface, Error> {
+        let reset_pin = match reset {
+            Some(reset) => {
+                let mut reset_pin = Gpio::new().map_err(Error::GPIO)?.get(reset).map_err(Error::GPIO)?.into_output();
+                reset_pin.set_low();
+                Some(reset_pin)
+            },
+            None => None,
+        };
+
+        Ok(SharedI2cInterface { bus: bus.clone(), address, mux, reset_pin, buffer: Vec::new(), _lock: lock })
+    }
+
+    fn select(&self) -> Result<MutexGuard<'_, I2c>, Error> {
+        let mut i2c_bus = self.bus.lock();
+        if let Some((mux_address, channel)) = self.mux {
+            i2c_bus.set_slave_address(mux_address).map_err(Error::I2C)?;
+            i2c_bus.smbus_send_byte(1 << channel).map_err(Error::I2C)?;
+        }
+        i2c_bus.set_slave_address(self.address).map_err(Error::I2C)?;
+        Ok(i2c_bus)
+    }
+}
+
+impl Interface for SharedI2cInterface {
+    fn command(&mut self, cmd: u8) -> Result<(), Error> {
+        self.select()?.smbus_write_byte(0x00, cmd).map_err(Error::I2C)
+    }
+
+    fn commands(&mut self, cmds: &[u8]) -> Result<(), Error> {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        let result = self.select().and_then(|mut i2c_bus| write_commands(&mut i2c_bus, &mut buffer, cmds));
+        self.buffer = buffer;
+        result
+    }
+
+    fn data(&mut self, data: &[u8]) -> Result<(), Error> {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        let result = self.select().and_then(|mut i2c_bus| write_data(&mut i2c_bus, &mut buffer, data));
+        self.buffer = buffer;
+        result
+    }
+
+    fn status(&mut self) -> Result<Option<u8>, Error> {
+        self.select()?.smbus_receive_byte().map(Some).map_err(Error::I2C)
+    }
+
+    fn reset(&mut self) {
+        if let Some(reset_pin) = &mut self.reset_pin {
+            reset_pin.set_high();
+            thread::sleep(Duration::from_millis(100));
+            reset_pin.set_low();
+            thread::sleep(Duration::from_millis(100));
+            reset_pin.set_high();
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
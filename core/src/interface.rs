@@ -0,0 +1,49 @@
+//! Connections to the screen's controller
+
+use core::time::Duration;
+
+use crate::Error;
+
+#[cfg(feature = "rppal")]
+pub use crate::i2c::{I2cInterface, SharedBus, SharedI2cInterface};
+#[cfg(feature = "rppal")]
+pub use crate::spi::SpiInterface;
+
+/// A connection to an SSD1327 controller, or something pretending to be one
+pub trait Interface: Send {
+    /// Send a command byte (or a command's argument)
+    fn command(&mut self, cmd: u8) -> Result<(), Error>;
+
+    /// Send a sequence of command bytes, see [`CommandBatch`](crate::CommandBatch). Sends them one at a time unless the interface
+    /// can combine them, which should be done where the bus allows
+    fn commands(&mut self, cmds: &[u8]) -> Result<(), Error> {
+        for cmd in cmds {
+            self.command(*cmd)?;
+        }
+        Ok(())
+    }
+
+    /// Send display data to the current window.
+    ///
+    /// The driver splits data into chunks as set by its [`TransferConfig`](crate::TransferConfig), each chunk should be sent in one transaction where the bus allows
+    fn data(&mut self, data: &[u8]) -> Result<(), Error>;
+
+    /// Hardware reset the controller, if there is a reset line
+    fn reset(&mut self) {}
+
+    /// Read the controller's status byte, None if the interface can't read from the controller
+    fn status(&mut self) -> Result<Option<u8>, Error> {
+        Ok(None)
+    }
+
+    /// Wait, e.g. for the controller to power up. Sleeps the thread with `std`, bare metal interfaces should implement this with their timer
+    fn delay(&mut self, duration: Duration) {
+        #[cfg(feature = "std")]
+        std::thread::sleep(duration);
+        #[cfg(not(feature = "std"))]
+        let _ = duration;
+    }
+}
+
+/// Status bit set while the panel is switched off
+pub const STATUS_DISPLAY_OFF: u8 = 0x40;
@@ -0,0 +1,89 @@
+//! Driver core for the Waveshare 1.5 inch OLED (SSD1327)
+//!
+//! The transport, initialisation, framebuffer and flushing, without text, widgets or anything else that changes often.
+//! `ws-1in5-i2c` re-exports everything here and builds its drawing toolkit on top.
+//!
+//! Without default features this builds with `no_std` and `alloc`, for driving the controller from bare metal boards through your own [`Interface`].
+//!
+//! - `std`: thread sleeps for delays, allocation checks, statistics, the screensaver, overlays, process locks and saving screen state
+//! - `rppal`: the i2c and SPI interfaces for Raspberry Pis (implies `std`)
+//! - `image`: conversion between framebuffers and `image` crate images (implies `std`)
+//! - `ssd1351`: the 1.5 inch RGB OLED's controller, see [`Controller`]
+//! - `tracing`: `tracing` spans and events for initialisation, resets, windows, transfers and bus errors (implies `std`)
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod alloc_check;
+pub mod clip;
+pub mod commands;
+pub mod damage;
+pub mod dirty;
+pub mod driver;
+pub mod emulator;
+mod error;
+pub mod fixed;
+pub mod framebuffer;
+pub mod geometry;
+pub mod gray;
+#[cfg(feature = "rppal")]
+pub mod i2c;
+pub mod interface;
+#[cfg(feature = "std")]
+pub mod lock;
+#[cfg(feature = "std")]
+pub mod overlay;
+pub mod pack;
+pub mod power;
+mod recovery;
+pub mod remap;
+#[cfg(feature = "std")]
+pub mod screensaver;
+pub mod scroll;
+#[cfg(feature = "rppal")]
+pub mod spi;
+#[cfg(feature = "ssd1351")]
+pub mod ssd1351;
+#[cfg(feature = "std")]
+pub mod state;
+#[cfg(feature = "std")]
+pub mod stats;
+pub mod stream;
+pub mod swap;
+pub mod sync;
+pub mod transfer;
+
+pub use commands::CommandBatch;
+pub use damage::Damage;
+pub use dirty::DirtyRegion;
+pub use driver::{Driver, Controller};
+pub use error::Error;
+pub use fixed::FixedFramebuffer;
+pub use framebuffer::{Framebuffer, PackedBuffer};
+pub use geometry::{Rect, Geometry, Anchor, Orientation};
+pub use gray::{Gray4, BlendMode};
+pub use interface::{Interface, STATUS_DISPLAY_OFF};
+#[cfg(feature = "rppal")]
+pub use i2c::{I2cInterface, SharedBus, SharedI2cInterface};
+pub use power::{PowerConfig, Regulator};
+pub use recovery::RetryPolicy;
+pub use remap::Remap;
+#[cfg(feature = "std")]
+pub use screensaver::{Screensaver, BurnInProtection};
+pub use scroll::{ScrollDirection, ScrollSpeed};
+#[cfg(feature = "std")]
+pub use stats::{Stats, FlushStats};
+pub use swap::{SwapAction, SwapInfo};
+pub use sync::FrameSync;
+pub use transfer::TransferConfig;
+#[cfg(feature = "rppal")]
+pub use spi::SpiInterface;
+#[cfg(feature = "ssd1351")]
+pub use ssd1351::{Palette, rgb565};
+
+/// Screen width of the 128x128 panel
+pub const OLED_WIDTH: usize = 128;
+/// Screen height of the 128x128 panel
+pub const OLED_HEIGHT: usize = 128; 
@@ -0,0 +1,62 @@
+//! Stopping two processes driving the same screen
+
+use std::{fs::{File, OpenOptions, TryLockError}, path::{Path, PathBuf}};
+
+use crate::Error;
+
+/// An exclusive lock on a screen, held until dropped
+///
+/// The lock is an advisory file lock named after the bus and address, so it is released automatically if the process dies.
+/// It lives in `/run/lock` (or `/var/lock`) so every process and service on the system uses the same file whatever its temp directory,
+/// the temp directory is only used on systems without either.
+#[derive(Debug)]
+pub struct DisplayLock {
+    _file: File,
+    path: PathBuf,
+}
+
+impl DisplayLock {
+    /// Lock the screen at an address on an i2c bus, returns [`Error::Busy`] if another process holds the lock
+    pub fn acquire(bus: u8, address: u16) -> Result<DisplayLock, Error> {
+        DisplayLock::acquire_path(DisplayLock::path(bus, address))
+    }
+
+    /// Lock the screen at an address behind a channel of an i2c multiplexer
+    pub fn acquire_muxed(bus: u8, mux_address: u16, channel: u8, address: u16) -> Result<DisplayLock, Error> {
+        DisplayLock::acquire_path(lock_dir().join(format!("ws-1in5-i2c-{}-{:#04x}-{}-{:#04x}.lock", bus, mux_address, channel, address)))
+    }
+
+    fn acquire_path(path: PathBuf) -> Result<DisplayLock, Error> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(Error::IO)?;
+
+        match file.try_lock() {
+            Ok(()) => Ok(DisplayLock { _file: file, path }),
+            Err(TryLockError::WouldBlock) => Err(Error::Busy),
+            Err(TryLockError::Error(e)) => Err(Error::IO(e)),
+        }
+    }
+
+    /// Path of the lock file for a screen
+    pub fn path(bus: u8, address: u16) -> PathBuf {
+        lock_dir().join(format!("ws-1in5-i2c-{}-{:#04x}.lock", bus, address))
+    }
+
+    /// Path of the lock file
+    pub fn lock_path(&self) -> &PathBuf {
+        &self.path
+    }
+}
+
+/// The system's lock directory. Chosen by what exists rather than what's writable, so processes with different permissions never pick different files
+fn lock_dir() -> PathBuf {
+    ["/run/lock", "/var/lock"].into_iter()
+        .map(Path::new)
+        .find(|dir| dir.is_dir())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(std::env::temp_dir)
+}
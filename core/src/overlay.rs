@@ -0,0 +1,90 @@
+//! A temporary overlay shown over the screen's content, e.g. a notification, which goes away by itself
+//!
+//! The overlay is composited as data is written to the screen, so the framebuffer keeps the content underneath and drawing carries on as usual while it's shown.
+
+use std::time::{Duration, Instant};
+
+use crate::{Driver, Error, Framebuffer, Rect};
+
+pub(crate) struct Overlay {
+    x: usize,
+    y: usize,
+    image: Framebuffer,
+    expires: Instant,
+}
+
+impl Overlay {
+    fn rect(&self) -> Rect {
+        Rect::new(self.x, self.y, self.image.width(), self.image.height())
+    }
+}
+
+impl Driver {
+    /// Show a framebuffer over the screen at a coord for a while, clipped to the screen. A second overlay replaces the first. Returns the area covered
+    ///
+    /// The driver doesn't run a timer, call [`Driver::update_overlay`] regularly, e.g. from the main loop (`ws-1in5-i2c`'s render thread does this itself).
+    /// Draws underneath carry on as usual, screenshots and the framebuffer don't include the overlay. Streamed frames aren't overlaid.
+    pub fn show_overlay(&mut self, x: usize, y: usize, image: Framebuffer, duration: Duration) -> Result<Rect, Error> {
+        let rect = Rect::new(x, y, image.width(), image.height());
+        let rect = rect.intersection(&self.framebuffer().bounds()).unwrap_or(Rect::new(x, y, 0, 0));
+        let previous = self.overlay.replace(Overlay { x, y, image, expires: Instant::now() + duration }).map(|overlay| overlay.rect());
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(?rect, ?duration, "overlay shown");
+        if let Some(previous) = previous {
+            self.flush_rect(previous)?;
+        }
+        self.flush_rect(rect)?;
+        Ok(rect)
+    }
+
+    /// The area covered by the overlay, None if there isn't one
+    pub fn overlay(&self) -> Option<Rect> {
+        self.overlay.as_ref().map(|overlay| overlay.rect())
+    }
+
+    /// Time until the overlay is removed, None if there isn't one
+    pub fn overlay_due(&self) -> Option<Duration> {
+        self.overlay.as_ref().map(|overlay| overlay.expires.saturating_duration_since(Instant::now()))
+    }
+
+    /// Remove the overlay if its time is up, returns true if it is still shown
+    pub fn update_overlay(&mut self) -> Result<bool, Error> {
+        match self.overlay_due() {
+            Some(due) if due.is_zero() => self.dismiss_overlay().map(|_| false),
+            due => Ok(due.is_some()),
+        }
+    }
+
+    /// Remove the overlay now, rewriting the content underneath
+    pub fn dismiss_overlay(&mut self) -> Result<(), Error> {
+        let Some(overlay) = self.overlay.take() else {
+            return Ok(())
+        };
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(rect = ?overlay.rect(), "overlay removed");
+        self.flush_rect(overlay.rect())
+    }
+
+    /// Returns true if the overlay covers part of an area, in logical pixels
+    pub(crate) fn overlay_intersects(&self, rect: Rect) -> bool {
+        self.overlay.as_ref().is_some_and(|overlay| overlay.rect().intersects(&rect))
+    }
+
+    /// Draw the overlay over packed data for an area, in logical pixels
+    pub(crate) fn composite_overlay(&self, buffer: &mut [u8], rect: Rect) {
+        let Some(overlay) = &self.overlay else { return };
+        let Some(area) = overlay.rect().intersection(&rect) else { return };
+
+        // An odd last column isn't in the packed data
+        let stride = rect.w / 2;
+        for y in area.y..area.bottom() {
+            for x in area.x..area.right().min(rect.x + stride * 2) {
+                let color = overlay.image.get_pixel(x - overlay.x, y - overlay.y);
+                let byte = &mut buffer[(y - rect.y) * stride + (x - rect.x) / 2];
+                *byte = if (x - rect.x).is_multiple_of(2) { (*byte & 0x0f) | color << 4 } else { (*byte & 0xf0) | color };
+            }
+        }
+    }
+}
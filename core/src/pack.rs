@@ -0,0 +1,48 @@
+//! Packing pixels into the screen's memory layout
+
+use alloc::vec::Vec;
+
+/// Pixel values (taken mod 16) shifted into the high nibble
+static HIGH_NIBBLE: [u8; 256] = nibble_table(4);
+/// Pixel values (taken mod 16) in the low nibble
+static LOW_NIBBLE: [u8; 256] = nibble_table(0);
+
+const fn nibble_table(shift: u32) -> [u8; 256] {
+    let mut table = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = ((i % 16) as u8) << shift;
+        i += 1;
+    }
+    table
+}
+
+/// Pack row major pixels (values 0-15) of a certain size two pixels per byte, in the screen's layout. An odd last column is dropped
+pub fn pack_pixels(pixels: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut packed = Vec::with_capacity(width / 2 * height);
+    for row in pixels.chunks_exact(width.max(1)).take(height) {
+        packed.extend(row.chunks_exact(2).map(|pair| HIGH_NIBBLE[pair[0] as usize] | LOW_NIBBLE[pair[1] as usize]));
+    }
+    packed
+}
+
+/// Unpack data in the screen's layout to row major pixels (values 0-15) of a certain size, the inverse of [`pack_pixels`].
+/// An odd last column is 0, as packing drops it. None if the data is too short
+pub fn unpack_pixels(packed: &[u8], width: usize, height: usize) -> Option<Vec<u8>> {
+    let stride = width / 2;
+    if packed.len() < stride * height {
+        return None
+    }
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for row in 0..height {
+        for byte in &packed[row * stride..(row + 1) * stride] {
+            pixels.push(byte >> 4);
+            pixels.push(byte & 0x0f);
+        }
+        if !width.is_multiple_of(2) {
+            pixels.push(0);
+        }
+    }
+    Some(pixels)
+}
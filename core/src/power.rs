@@ -0,0 +1,83 @@
+//! Power and driving settings
+
+use core::time::Duration;
+
+/// Typical oscillator frequency at the lowest frequency setting, and how much each step of the setting adds (SSD1327 datasheet, approximate)
+const OSCILLATOR_HZ: (u64, u64) = (560_000, 35_000);
+/// Clocks each row is driven for after the two phases, the longest pulse of the default grey scale table
+const CURRENT_DRIVE_CLOCKS: u64 = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Where the controller's VDD comes from (function selection A, command 0xab)
+pub enum Regulator {
+    /// The controller's internal VDD regulator
+    #[default]
+    Internal,
+    /// VDD supplied externally by the carrier board
+    External,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Power and driving settings sent during initialisation, the defaults suit the Waveshare module
+///
+/// Values are written to the controller as is, see the SSD1327 datasheet for their meaning.
+pub struct PowerConfig {
+    /// VDD regulator (0xab)
+    pub regulator: Regulator,
+    /// Phase 1 and 2 lengths (0xb1)
+    pub phase_length: u8,
+    /// Front clock divider and oscillator frequency (0xb3)
+    pub clock: u8,
+    /// Second precharge period (0xb6)
+    pub second_precharge: u8,
+    /// VCOMH voltage (0xbe)
+    pub vcomh: u8,
+    /// Precharge voltage (0xbc)
+    pub precharge_voltage: u8,
+    /// Function selection B, second precharge and internal VSL (0xd5)
+    pub function_selection_b: u8,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        PowerConfig {
+            regulator: Regulator::Internal,
+            phase_length: 0xf1,
+            clock: 0x00,
+            second_precharge: 0x0f,
+            vcomh: 0x0f,
+            precharge_voltage: 0x08,
+            function_selection_b: 0x62,
+        }
+    }
+}
+
+impl PowerConfig {
+    /// The settings as (command, argument) pairs, in the order they are sent
+    pub fn commands(&self) -> [(u8, u8); 7] {
+        let regulator = match self.regulator {
+            Regulator::Internal => 0x01,
+            Regulator::External => 0x00,
+        };
+
+        [
+            (0xb1, self.phase_length),
+            (0xb3, self.clock),
+            (0xab, regulator),
+            (0xb6, self.second_precharge),
+            (0xbe, self.vcomh),
+            (0xbc, self.precharge_voltage),
+            (0xd5, self.function_selection_b),
+        ]
+    }
+
+    /// Estimate the time the panel takes to refresh every row from the clock (0xb3) and phase lengths (0xb1), for a panel with a number of rows.
+    ///
+    /// The oscillator varies between parts and with temperature, so this is only close to the real period
+    pub fn refresh_period(&self, rows: usize) -> Duration {
+        let divider = (self.clock & 0x0f) as u64 + 1;
+        let oscillator = OSCILLATOR_HZ.0 + (self.clock >> 4) as u64 * OSCILLATOR_HZ.1;
+        let clocks_per_row = (self.phase_length & 0x0f) as u64 + (self.phase_length >> 4) as u64 + CURRENT_DRIVE_CLOCKS;
+        Duration::from_nanos(divider * clocks_per_row * rows as u64 * 1_000_000_000 / oscillator)
+    }
+}
@@ -0,0 +1,137 @@
+//! Recovering from i2c failures
+
+use core::time::Duration;
+
+use crate::{Driver, Error};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// How failed i2c writes are retried before the error is returned
+pub struct RetryPolicy {
+    /// Retries after the first attempt
+    pub retries: u32,
+    /// Wait before the first retry, doubled for each retry after
+    pub backoff: Duration,
+    /// Longest wait between retries
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Don't retry
+    pub const NONE: RetryPolicy = RetryPolicy { retries: 0, backoff: Duration::ZERO, max_backoff: Duration::ZERO };
+
+    /// Retry a number of times, doubling the wait each time up to a second
+    pub fn new(retries: u32, backoff: Duration) -> RetryPolicy {
+        RetryPolicy { retries, backoff, max_backoff: Duration::from_secs(1) }
+    }
+
+    /// Wait before a retry (starting from 0)
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.backoff.saturating_mul(1 << retry.min(16)).min(self.max_backoff)
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Recovery {
+    retry: RetryPolicy,
+    max_failures: Option<u32>,
+    failures: u32,
+    recovering: bool,
+    recoveries: u64,
+}
+
+impl Driver {
+    /// Re-initialise the screen and restore the framebuffer after a number of i2c errors in a row (e.g. from a loose cable).
+    ///
+    /// The error that triggered recovery is still returned, the operation may have only partly reached the screen,
+    /// but anything drawn through the framebuffer is shown again once recovery succeeds.
+    pub fn with_recovery(mut self, max_failures: u32) -> Driver {
+        self.recovery.max_failures = Some(max_failures.max(1));
+        self
+    }
+
+    /// Retry failed command and data writes. A failed data write is retried from setting the window, so a frame is never left half drawn by a transient error
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Driver {
+        self.recovery.retry = retry;
+        self
+    }
+
+    /// How failed writes are retried
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.recovery.retry
+    }
+
+    /// Number of times the screen has been recovered
+    pub fn recoveries(&self) -> u64 {
+        self.recovery.recoveries
+    }
+
+    /// Reset and re-initialise the controller, then restore the framebuffer (leaving the panel off if suspended)
+    pub fn recover(&mut self) -> Result<(), Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("recover", recoveries = self.recovery.recoveries).entered();
+
+        self.recovery.recovering = true;
+        let result = self.reinit();
+        self.recovery.recovering = false;
+
+        #[cfg(feature = "tracing")]
+        match &result {
+            Ok(()) => tracing::info!("recovered"),
+            Err(e) => tracing::error!(error = %e, "recovery failed"),
+        }
+
+        if result.is_ok() {
+            self.recovery.failures = 0;
+            self.recovery.recoveries += 1;
+        }
+        result
+    }
+
+    fn reinit(&mut self) -> Result<(), Error> {
+        self.init()?;
+        if self.suspended {
+            self.command(0xae)
+        } else {
+            self.flush()
+        }
+    }
+
+    /// Run a write, retrying i2c errors according to the retry policy
+    pub(crate) fn retry<F: FnMut(&mut Driver) -> Result<(), Error>>(&mut self, mut write: F) -> Result<(), Error> {
+        let mut retry = 0;
+        loop {
+            match write(self) {
+                Err(e) if e.is_bus_error() && retry < self.recovery.retry.retries => {
+                    let delay = self.recovery.retry.delay(retry);
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(error = %e, retry, ?delay, "bus error, retrying");
+                    self.delay(delay);
+                    retry += 1;
+                },
+                result => return result,
+            }
+        }
+    }
+
+    /// Count i2c errors from the interface, recovering once too many happen in a row
+    pub(crate) fn supervise(&mut self, result: Result<(), Error>) -> Result<(), Error> {
+        if self.recovery.recovering {
+            return result
+        }
+
+        match (&result, self.recovery.max_failures) {
+            (Ok(()), _) => self.recovery.failures = 0,
+            (Err(e), Some(max_failures)) if e.is_bus_error() => {
+                self.recovery.failures += 1;
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %e, failures = self.recovery.failures, "bus error");
+                if self.recovery.failures >= max_failures {
+                    // If recovery fails too, the next failures will try again
+                    let _ = self.recover();
+                }
+            },
+            _ => {},
+        }
+        result
+    }
+}
@@ -0,0 +1,182 @@
+//! Remapping grey levels as pixels are packed for the screen, so a theme like dark mode or high contrast doesn't mean re-rendering
+
+use crate::{Driver, Error, Framebuffer, Gray4, Rect};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A table mapping each of the 16 grey levels to another, e.g. to invert, posterize or brighten the midtones
+///
+/// Set on a driver with [`Driver::with_remap`] it's applied to everything written to the screen, while the framebuffer keeps the levels
+/// as drawn. [`Framebuffer::remap_rect`] applies one to part of a framebuffer instead, e.g. for a single draw.
+pub struct Remap {
+    levels: [u8; 16],
+    /// The levels applied to both pixels of every packed byte
+    bytes: [u8; 256],
+}
+
+impl Default for Remap {
+    fn default() -> Self {
+        Remap::IDENTITY
+    }
+}
+
+impl Remap {
+    /// Leaves every level as it is
+    pub const IDENTITY: Remap = Remap::new([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+
+    /// Create new from the level each level (0-15) becomes, levels over 15 are clamped to white
+    pub const fn new(levels: [u8; 16]) -> Remap {
+        let mut clamped = [0u8; 16];
+        let mut i = 0;
+        while i < 16 {
+            clamped[i] = if levels[i] > 15 { 15 } else { levels[i] };
+            i += 1;
+        }
+
+        let mut bytes = [0u8; 256];
+        let mut i = 0;
+        while i < 256 {
+            bytes[i] = clamped[i >> 4] << 4 | clamped[i & 0x0f];
+            i += 1;
+        }
+        Remap { levels: clamped, bytes }
+    }
+
+    /// Swap black and white, e.g. for a light theme drawn with dark mode assets
+    pub const fn invert() -> Remap {
+        let mut levels = [0u8; 16];
+        let mut i = 0;
+        while i < 16 {
+            levels[i] = 15 - i as u8;
+            i += 1;
+        }
+        Remap::new(levels)
+    }
+
+    /// Reduce the levels to a number of evenly spaced steps (2-16), black and white are kept
+    pub const fn posterize(steps: u8) -> Remap {
+        let steps = if steps < 2 { 2 } else if steps > 16 { 16 } else { steps } as usize;
+        let mut levels = [0u8; 16];
+        let mut i = 0;
+        while i < 16 {
+            // Round to the nearest step, then spread the steps back over 0-15
+            let step = (i * (steps - 1) + 7) / 15;
+            levels[i] = (step * 15 / (steps - 1)) as u8;
+            i += 1;
+        }
+        Remap::new(levels)
+    }
+
+    /// Black below a level and white from it, for high contrast
+    pub const fn threshold(level: u8) -> Remap {
+        let mut levels = [0u8; 16];
+        let mut i = 0;
+        while i < 16 {
+            levels[i] = if i as u8 >= level { 15 } else { 0 };
+            i += 1;
+        }
+        Remap::new(levels)
+    }
+
+    /// Stretch the levels from black to white over 0-15, levels outside are clamped. Raising black hides a dim background,
+    /// lowering white brightens everything
+    pub const fn stretch(black: u8, white: u8) -> Remap {
+        let white = if white > 15 { 15 } else { white };
+        let black = if black >= white { white.saturating_sub(1) } else { black };
+        let mut levels = [0u8; 16];
+        let mut i = 0;
+        while i < 16 {
+            let level = if (i as u8) <= black { 0 } else if i as u8 >= white { 15 } else { ((i as u8 - black) as usize * 15 / (white - black) as usize) as u8 };
+            levels[i] = level;
+            i += 1;
+        }
+        Remap::new(levels)
+    }
+
+    /// Brighten the midtones while keeping black and white, roughly a gamma of 0.6. Makes dim detail readable
+    pub const fn boost_midtones() -> Remap {
+        Remap::new([0, 3, 4, 6, 7, 8, 9, 10, 10, 11, 12, 13, 13, 14, 15, 15])
+    }
+
+    /// Apply this remap then another
+    pub const fn then(&self, other: &Remap) -> Remap {
+        let mut levels = [0u8; 16];
+        let mut i = 0;
+        while i < 16 {
+            levels[i] = other.levels[self.levels[i] as usize];
+            i += 1;
+        }
+        Remap::new(levels)
+    }
+
+    /// The level each level becomes
+    pub const fn levels(&self) -> [u8; 16] {
+        self.levels
+    }
+
+    /// Returns true if every level is left as it is
+    pub fn is_identity(&self) -> bool {
+        *self == Remap::IDENTITY
+    }
+
+    /// The level a level (taken mod 16) becomes
+    pub const fn level(&self, level: u8) -> u8 {
+        self.levels[(level % 16) as usize]
+    }
+
+    /// The grey a grey becomes
+    pub const fn gray(&self, gray: Gray4) -> Gray4 {
+        Gray4::saturating(self.levels[gray.level() as usize])
+    }
+
+    /// Remap both pixels of a packed byte
+    pub const fn byte(&self, byte: u8) -> u8 {
+        self.bytes[byte as usize]
+    }
+
+    /// Remap packed data (two pixels per byte) in place
+    pub fn apply(&self, packed: &mut [u8]) {
+        packed.iter_mut().for_each(|byte| *byte = self.bytes[*byte as usize]);
+    }
+
+    /// Remap pixels (values 0-15, one per byte) in place
+    pub fn apply_pixels(&self, pixels: &mut [u8]) {
+        pixels.iter_mut().for_each(|pixel| *pixel = self.level(*pixel));
+    }
+}
+
+impl Framebuffer {
+    /// Remap the levels in a rectangle, clipped to the framebuffer
+    pub fn remap_rect(&mut self, rect: Rect, remap: &Remap) {
+        let Some(rect) = rect.intersection(&self.bounds()) else { return };
+        for y in rect.y..rect.bottom() {
+            for x in rect.x..rect.right() {
+                self.set_pixel(x, y, remap.level(self.get_pixel(x, y)));
+            }
+        }
+    }
+}
+
+impl Driver {
+    /// Remap the levels of everything written to the screen, see [`Remap`]
+    pub fn with_remap(mut self, remap: Remap) -> Driver {
+        self.remap = remap;
+        self
+    }
+
+    /// The remap applied to everything written to the screen
+    pub fn remap(&self) -> &Remap {
+        &self.remap
+    }
+
+    /// Change the remap applied to everything written to the screen, the current content is rewritten.
+    ///
+    /// The framebuffer (and screenshots) keep the levels as drawn, so switching back to [`Remap::IDENTITY`] restores them
+    pub fn set_remap(&mut self, remap: Remap) -> Result<(), Error> {
+        if remap == self.remap {
+            return Ok(())
+        }
+
+        self.remap = remap;
+        self.flush()
+    }
+}
@@ -0,0 +1,135 @@
+//! Dimming the screen and protecting against burn in while nothing is drawn
+
+use std::time::{Duration, Instant};
+
+use crate::{Driver, Error};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// What is done on top of dimming while the screensaver is on
+pub enum BurnInProtection {
+    /// Only dim
+    #[default]
+    None,
+    /// Move the image up a row (wrapping the top row to the bottom), so static pixels aren't lit in the same place all day
+    Shift,
+    /// Switch the panel off
+    Blank,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// When the screensaver starts and what it does
+pub struct Screensaver {
+    /// Time without drawing before the screensaver starts
+    pub idle: Duration,
+    /// Contrast while the screensaver is on (0-255, the screen is initialised with 128)
+    pub contrast: u8,
+    /// What is done on top of dimming
+    pub protection: BurnInProtection,
+}
+
+impl Default for Screensaver {
+    fn default() -> Self {
+        Screensaver { idle: Duration::from_secs(60), contrast: 0x10, protection: BurnInProtection::None }
+    }
+}
+
+impl Screensaver {
+    /// Create new
+    pub const fn new(idle: Duration, contrast: u8, protection: BurnInProtection) -> Screensaver {
+        Screensaver { idle, contrast, protection }
+    }
+}
+
+pub(crate) struct ScreensaverState {
+    config: Screensaver,
+    last_draw: Instant,
+    active: bool,
+}
+
+impl Driver {
+    /// Dim the screen (and optionally shift or blank it) once nothing has been drawn for a while, restoring it on the next draw.
+    ///
+    /// The driver doesn't run a timer, call [`Driver::update_screensaver`] regularly, e.g. from the main loop (`ws-1in5-i2c`'s render thread does this itself).
+    pub fn with_screensaver(mut self, screensaver: Screensaver) -> Driver {
+        self.screensaver = Some(ScreensaverState { config: screensaver, last_draw: Instant::now(), active: false });
+        self
+    }
+
+    /// The screensaver settings, None if there is no screensaver
+    pub fn screensaver(&self) -> Option<Screensaver> {
+        self.screensaver.as_ref().map(|state| state.config)
+    }
+
+    /// Returns true if the screensaver is on
+    pub fn is_screensaver_active(&self) -> bool {
+        self.screensaver.as_ref().is_some_and(|state| state.active)
+    }
+
+    /// Time until the screensaver starts, None if there is no screensaver or it's already on
+    pub fn screensaver_due(&self) -> Option<Duration> {
+        self.screensaver.as_ref()
+            .filter(|state| !state.active)
+            .map(|state| state.config.idle.saturating_sub(state.last_draw.elapsed()))
+    }
+
+    /// Start the screensaver if nothing has been drawn for long enough, returns true if it is on
+    pub fn update_screensaver(&mut self) -> Result<bool, Error> {
+        match self.screensaver_due() {
+            Some(due) if due.is_zero() && !self.suspended => self.start_screensaver().map(|_| true),
+            _ => Ok(self.is_screensaver_active()),
+        }
+    }
+
+    /// Start the screensaver now, whether or not the screen has been idle
+    pub fn start_screensaver(&mut self) -> Result<(), Error> {
+        let Some(state) = &mut self.screensaver else {
+            return Ok(())
+        };
+        if state.active {
+            return Ok(())
+        }
+        state.active = true;
+        let config = state.config;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(?config, "screensaver on");
+
+        self.send_contrast(config.contrast)?;
+        match config.protection {
+            BurnInProtection::None => Ok(()),
+            BurnInProtection::Shift => self.send_display_offset(1),
+            BurnInProtection::Blank => self.command(0xae),
+        }
+    }
+
+    /// Note a draw, restoring the screen if the screensaver is on
+    pub(crate) fn wake_screensaver(&mut self) -> Result<(), Error> {
+        let Some(state) = &mut self.screensaver else {
+            return Ok(())
+        };
+        state.last_draw = Instant::now();
+        if !state.active {
+            return Ok(())
+        }
+        state.active = false;
+        let config = state.config;
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("screensaver off");
+
+        self.send_contrast(self.contrast())?;
+        match config.protection {
+            BurnInProtection::None => Ok(()),
+            BurnInProtection::Shift => self.send_display_offset(0),
+            BurnInProtection::Blank if self.suspended => Ok(()),
+            BurnInProtection::Blank => self.command(0xaf),
+        }
+    }
+
+    /// Move the image up by rows on top of the panel's row offset
+    fn send_display_offset(&mut self, rows: usize) -> Result<(), Error> {
+        let offset = (self.geometry().row_offset + rows) % 128;
+        self.command(0xa2)?;
+        self.command(offset as u8)
+    }
+}
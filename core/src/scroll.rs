@@ -0,0 +1,105 @@
+//! Hardware scrolling (SSD1327 only)
+
+use crate::{CommandBatch, Controller, Driver, Error, Rect};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Which way a region scrolls
+pub enum ScrollDirection {
+    /// Towards x = 0
+    #[default]
+    Left,
+    /// Away from x = 0
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Frames between each step of a scroll, fastest first
+pub enum ScrollSpeed {
+    /// 2 frames per step
+    Frames2,
+    /// 6 frames per step
+    #[default]
+    Frames6,
+    /// 10 frames per step
+    Frames10,
+    /// 100 frames per step
+    Frames100,
+    /// 200 frames per step
+    Frames200,
+    /// 300 frames per step
+    Frames300,
+    /// 400 frames per step
+    Frames400,
+    /// 500 frames per step
+    Frames500,
+}
+
+impl ScrollSpeed {
+    /// The time interval as sent to the controller
+    fn interval(self) -> u8 {
+        match self {
+            ScrollSpeed::Frames2 => 0b111,
+            ScrollSpeed::Frames6 => 0b000,
+            ScrollSpeed::Frames10 => 0b001,
+            ScrollSpeed::Frames100 => 0b010,
+            ScrollSpeed::Frames200 => 0b011,
+            ScrollSpeed::Frames300 => 0b100,
+            ScrollSpeed::Frames400 => 0b101,
+            ScrollSpeed::Frames500 => 0b110,
+        }
+    }
+}
+
+impl Driver {
+    /// Have the controller scroll a region of the screen horizontally, wrapping around, without any more data being sent.
+    ///
+    /// The region is in physical pixels with the same limits as [`Driver::set_window`]. Writes to the screen return [`Error::Scrolling`] until [`Driver::stop_scroll`],
+    /// draw into the framebuffer in the meantime. Starting a new scroll stops the current one first.
+    pub fn start_scroll(&mut self, region: Rect, direction: ScrollDirection, speed: ScrollSpeed) -> Result<(), Error> {
+        if self.controller() != Controller::Ssd1327 {
+            return Err(Error::Unsupported("hardware scrolling"))
+        }
+        let region = self.window_for(region)?;
+        if self.scrolling {
+            self.stop_scroll()?;
+        }
+
+        let first_column = self.geometry().columns().0;
+        let cmd = match direction {
+            ScrollDirection::Right => 0x26,
+            ScrollDirection::Left => 0x27,
+        };
+        let mut batch = CommandBatch::new();
+        batch.command_with_args(&[
+            cmd,
+            0x00,
+            region.y as u8,
+            speed.interval(),
+            (region.bottom() - 1) as u8,
+            first_column + (region.x / 2) as u8,
+            first_column + (region.right() / 2) as u8 - 1,
+            0x00,
+        ]);
+        batch.command(0x2f);
+
+        self.send_batch(&batch)?;
+        self.scrolling = true;
+        Ok(())
+    }
+
+    /// Stop scrolling and write the framebuffer to the screen again, as the controller leaves its memory where the scroll stopped
+    pub fn stop_scroll(&mut self) -> Result<(), Error> {
+        if !self.scrolling {
+            return Ok(())
+        }
+
+        self.command(0x2e)?;
+        self.scrolling = false;
+        self.flush()
+    }
+
+    /// Returns true if the controller is scrolling part of the screen
+    pub fn is_scrolling(&self) -> bool {
+        self.scrolling
+    }
+}
@@ -0,0 +1,73 @@
+//! The screen over SPI on a Raspberry Pi, using rppal
+
+use std::{thread, time::Duration};
+
+use rppal::{gpio::{Gpio, OutputPin}, spi::{Spi, Bus, SlaveSelect, Mode}};
+
+use crate::{Error, Interface};
+
+/// Most bytes spidev accepts in one transfer by default
+const MAX_TRANSFER: usize = 4096;
+
+/// The screen connected over 4 wire SPI, with a GPIO data/command pin and an optional GPIO reset pin
+///
+/// The RGB OLED (SSD1351) only has an SPI connection, the greyscale OLED can be switched to SPI with its BS jumpers.
+pub struct SpiInterface {
+    spi: Spi,
+    dc_pin: OutputPin,
+    reset_pin: Option<OutputPin>,
+}
+
+impl SpiInterface {
+    /// Create new, on a bus and chip select with a clock speed in Hz
+    pub fn new(bus: Bus, slave_select: SlaveSelect, clock_speed: u32, dc: u8, reset: Option<u8>) -> Result<SpiInterface, Error> {
+        let gpio = Gpio::new().map_err(Error::GPIO)?;
+        let dc_pin = gpio.get(dc).map_err(Error::GPIO)?.into_output();
+        let reset_pin = match reset {
+            Some(reset) => Some(gpio.get(reset).map_err(Error::GPIO)?.into_output()),
+            None => None,
+        };
+
+        let spi = Spi::new(bus, slave_select, clock_speed, Mode::Mode0).map_err(Error::SPI)?;
+        Ok(SpiInterface { spi, dc_pin, reset_pin })
+    }
+
+    /// Create new from a bus and pins that are already set up
+    pub fn from_parts(spi: Spi, dc_pin: OutputPin, reset_pin: Option<OutputPin>) -> SpiInterface {
+        SpiInterface { spi, dc_pin, reset_pin }
+    }
+}
+
+impl Interface for SpiInterface {
+    fn command(&mut self, cmd: u8) -> Result<(), Error> {
+        self.dc_pin.set_low();
+        self.spi.write(&[cmd]).map(|_| ()).map_err(Error::SPI)
+    }
+
+    fn commands(&mut self, cmds: &[u8]) -> Result<(), Error> {
+        self.dc_pin.set_low();
+        for chunk in cmds.chunks(MAX_TRANSFER) {
+            self.spi.write(chunk).map_err(Error::SPI)?;
+        }
+        Ok(())
+    }
+
+    fn data(&mut self, data: &[u8]) -> Result<(), Error> {
+        self.dc_pin.set_high();
+        for chunk in data.chunks(MAX_TRANSFER) {
+            self.spi.write(chunk).map_err(Error::SPI)?;
+        }
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        if let Some(reset_pin) = &mut self.reset_pin {
+            reset_pin.set_high();
+            thread::sleep(Duration::from_millis(100));
+            reset_pin.set_low();
+            thread::sleep(Duration::from_millis(100));
+            reset_pin.set_high();
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+}
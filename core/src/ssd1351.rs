@@ -0,0 +1,103 @@
+//! The 1.5 inch RGB OLED's controller (SSD1351), driven through the same 4 bit framebuffer as the SSD1327
+//!
+//! Each grey level is shown as a colour from a [`Palette`], so text, images and widgets drawn for the greyscale screen work unchanged.
+//! Full colour images can still be written directly with [`Driver::show_rgb565`](crate::Driver::show_rgb565).
+
+use alloc::vec::Vec;
+
+use crate::Geometry;
+
+/// Pack 8 bit red, green and blue into a 16 bit RGB565 colour
+pub const fn rgb565(r: u8, g: u8, b: u8) -> u16 {
+    (r as u16 >> 3) << 11 | (g as u16 >> 2) << 5 | b as u16 >> 3
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The RGB565 colour shown for each grey level (0-15)
+pub struct Palette {
+    colors: [u16; 16],
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::grayscale()
+    }
+}
+
+impl Palette {
+    /// Create new from a colour for every level
+    pub const fn new(colors: [u16; 16]) -> Palette {
+        Palette { colors }
+    }
+
+    /// Levels from black to white
+    pub const fn grayscale() -> Palette {
+        Palette::tinted(0xff, 0xff, 0xff)
+    }
+
+    /// Levels from black to a colour, e.g. amber for a night mode
+    pub const fn tinted(r: u8, g: u8, b: u8) -> Palette {
+        let mut colors = [0u16; 16];
+        let mut level = 0;
+        while level < 16 {
+            colors[level as usize] = rgb565(scale(r, level), scale(g, level), scale(b, level));
+            level += 1;
+        }
+        Palette { colors }
+    }
+
+    /// The colour shown for a level (taken mod 16)
+    pub const fn get(&self, level: u8) -> u16 {
+        self.colors[(level % 16) as usize]
+    }
+
+    /// Change the colour shown for a level (taken mod 16)
+    pub fn set(&mut self, level: u8, color: u16) {
+        self.colors[(level % 16) as usize] = color;
+    }
+
+    /// Convert packed 4 bit data to big endian RGB565, two bytes per pixel
+    pub(crate) fn convert(&self, packed: &[u8], colors: &mut Vec<u8>) {
+        colors.clear();
+        for byte in packed {
+            colors.extend_from_slice(&self.get(byte >> 4).to_be_bytes());
+            colors.extend_from_slice(&self.get(byte & 0x0f).to_be_bytes());
+        }
+    }
+}
+
+const fn scale(channel: u8, level: u8) -> u8 {
+    (channel as u16 * level as u16 / 15) as u8
+}
+
+/// Write RAM, sent after setting the window and before the data
+pub(crate) const WRITE_RAM: u8 = 0x5c;
+
+/// The init sequence as (command, arguments), everything but switching the display on
+pub(crate) fn init_commands(geometry: &Geometry) -> [(u8, [u8; 3], usize); 19] {
+    let (first_column, last_column) = (geometry.column_offset as u8, (geometry.column_offset + geometry.width - 1) as u8);
+    let mux_ratio = geometry.height as u8 - 1;
+    [
+        // Unlock the command set
+        (0xfd, [0x12, 0, 0], 1),
+        (0xfd, [0xb1, 0, 0], 1),
+        (0xae, [0, 0, 0], 0),
+        (0xb3, [0xf1, 0, 0], 1),
+        (0xca, [mux_ratio, 0, 0], 1),
+        // 65k colours, COM split, scanning from COM[N-1]
+        (0xa0, [0x74, 0, 0], 1),
+        (0x15, [first_column, last_column, 0], 2),
+        (0x75, [0x00, mux_ratio, 0], 2),
+        (0xa1, [0x00, 0, 0], 1),
+        (0xa2, [geometry.row_offset as u8, 0, 0], 1),
+        (0xb5, [0x00, 0, 0], 1),
+        (0xb4, [0xa0, 0xb5, 0x55], 3),
+        (0xb6, [0x01, 0, 0], 1),
+        (0xab, [0x01, 0, 0], 1),
+        (0xb1, [0x32, 0, 0], 1),
+        (0xbe, [0x05, 0, 0], 1),
+        (0xa6, [0, 0, 0], 0),
+        (0xc1, [0xc8, 0x80, 0xc8], 3),
+        (0xc7, [0x0f, 0, 0], 1),
+    ]
+}
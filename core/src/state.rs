@@ -0,0 +1,81 @@
+//! Saving what the screen shows, so a restarted process can repaint it straight away instead of starting blank
+
+use std::{fs, io::{self, ErrorKind}, path::Path};
+
+use crate::{Driver, Error, Framebuffer, Orientation};
+
+const MAGIC: &[u8; 4] = b"WS15";
+const VERSION: u8 = 1;
+/// Magic, version, physical width and height, orientation, pixel doubling and contrast
+const HEADER_LEN: usize = 4 + 1 + 2 + 2 + 1 + 1 + 1;
+
+fn invalid_data(message: &str) -> Error {
+    Error::IO(io::Error::new(ErrorKind::InvalidData, message))
+}
+
+impl Driver {
+    /// Save the framebuffer, orientation, pixel doubling and contrast to a file, replacing it atomically
+    pub fn save_state<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let geometry = self.geometry();
+        let framebuffer = self.framebuffer();
+
+        let mut data = Vec::with_capacity(HEADER_LEN + framebuffer.as_bytes().len());
+        data.extend_from_slice(MAGIC);
+        data.push(VERSION);
+        data.extend_from_slice(&(geometry.width as u16).to_le_bytes());
+        data.extend_from_slice(&(geometry.height as u16).to_le_bytes());
+        data.push(match self.orientation() {
+            Orientation::Normal => 0,
+            Orientation::Rotated180 => 1,
+        });
+        data.push(self.is_pixel_doubled() as u8);
+        data.push(self.contrast());
+        data.extend_from_slice(framebuffer.as_bytes());
+
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        fs::write(&temp, &data).map_err(Error::IO)?;
+        fs::rename(&temp, path).map_err(Error::IO)
+    }
+
+    /// Restore state saved with [`Driver::save_state`] and write it to the screen in one flush.
+    /// Returns false if there's no saved state, e.g. on the first run
+    ///
+    /// State saved for a panel of another size is an error.
+    pub fn restore_state<P: AsRef<Path>>(&mut self, path: P) -> Result<bool, Error> {
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(Error::IO(e)),
+        };
+        if data.len() < HEADER_LEN || &data[..4] != MAGIC {
+            return Err(invalid_data("not a saved screen state"))
+        }
+        if data[4] != VERSION {
+            return Err(invalid_data("unsupported screen state version"))
+        }
+
+        let geometry = self.geometry();
+        let width = u16::from_le_bytes([data[5], data[6]]) as usize;
+        let height = u16::from_le_bytes([data[7], data[8]]) as usize;
+        if (width, height) != (geometry.width, geometry.height) {
+            return Err(invalid_data("screen state was saved for a panel of another size"))
+        }
+        let orientation = match data[9] {
+            0 => Orientation::Normal,
+            1 => Orientation::Rotated180,
+            _ => return Err(invalid_data("invalid orientation in screen state")),
+        };
+        let doubled = data[10] != 0;
+        let contrast = data[11];
+
+        let (width, height) = if doubled { (width / 2, height / 2) } else { (width, height) };
+        let framebuffer = Framebuffer::from_packed(width, height, data[HEADER_LEN..].to_vec())?;
+
+        self.set_contrast(contrast)?;
+        self.replace_state(framebuffer, orientation, doubled);
+        self.flush()?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,143 @@
+//! Opt in performance statistics
+
+use std::time::{Duration, Instant};
+
+use alloc::boxed::Box;
+
+use crate::{Driver, Rect};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// What one write of display data to the screen cost
+pub struct FlushStats {
+    /// Area written, in physical pixels
+    pub rect: Rect,
+    /// Bytes sent, commands included
+    pub bytes: u64,
+    /// Bus transactions issued, commands included
+    pub transactions: u64,
+    /// Time taken, including retries
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Totals since statistics were enabled or last reset
+pub struct Stats {
+    /// Bytes sent, commands included
+    pub bytes_written: u64,
+    /// Bus transactions issued, commands included
+    pub transactions: u64,
+    /// Writes of display data to the screen
+    pub flushes: u64,
+    /// Time spent writing display data
+    pub flush_time: Duration,
+    /// The longest write of display data
+    pub longest_flush: Duration,
+    /// When counting started
+    pub since: Instant,
+}
+
+impl Stats {
+    fn new() -> Stats {
+        Stats { bytes_written: 0, transactions: 0, flushes: 0, flush_time: Duration::ZERO, longest_flush: Duration::ZERO, since: Instant::now() }
+    }
+
+    /// Average time per write of display data
+    pub fn average_flush(&self) -> Duration {
+        self.flush_time.checked_div(self.flushes as u32).unwrap_or_default()
+    }
+
+    /// Screen updates per second since counting started
+    pub fn fps(&self) -> f64 {
+        let elapsed = self.since.elapsed().as_secs_f64();
+        if elapsed > 0.0 { self.flushes as f64 / elapsed } else { 0.0 }
+    }
+
+    /// Share of the time since counting started spent writing to the screen.
+    /// Close to 1 means the bus is the bottleneck, close to 0 means the time goes on drawing
+    pub fn bus_utilisation(&self) -> f64 {
+        let elapsed = self.since.elapsed().as_secs_f64();
+        if elapsed > 0.0 { (self.flush_time.as_secs_f64() / elapsed).min(1.0) } else { 0.0 }
+    }
+
+    /// Bytes sent per second since counting started
+    pub fn throughput(&self) -> f64 {
+        let elapsed = self.since.elapsed().as_secs_f64();
+        if elapsed > 0.0 { self.bytes_written as f64 / elapsed } else { 0.0 }
+    }
+}
+
+type FlushCallback = Box<dyn FnMut(&FlushStats) + Send>;
+
+pub(crate) struct StatsCollector {
+    stats: Stats,
+    callback: Option<FlushCallback>,
+}
+
+/// Counters at the start of a flush
+pub(crate) struct FlushStart {
+    start: Instant,
+    bytes: u64,
+    transactions: u64,
+}
+
+impl Driver {
+    /// Count bytes, transactions and time spent writing to the screen, read with [`Driver::stats`]
+    pub fn with_stats(mut self, enabled: bool) -> Driver {
+        self.stats = enabled.then(|| StatsCollector { stats: Stats::new(), callback: None });
+        self
+    }
+
+    /// Count statistics and call a function after every write of display data with what it cost
+    pub fn with_stats_callback<F: FnMut(&FlushStats) + Send + 'static>(mut self, callback: F) -> Driver {
+        self.stats = Some(StatsCollector { stats: Stats::new(), callback: Some(Box::new(callback)) });
+        self
+    }
+
+    /// Totals since statistics were enabled or last reset, None if they aren't enabled
+    pub fn stats(&self) -> Option<Stats> {
+        self.stats.as_ref().map(|collector| collector.stats)
+    }
+
+    /// Start counting again from zero
+    pub fn reset_stats(&mut self) {
+        if let Some(collector) = &mut self.stats {
+            collector.stats = Stats::new();
+        }
+    }
+
+    pub(crate) fn record_transaction(&mut self, bytes: usize) {
+        if let Some(collector) = &mut self.stats {
+            collector.stats.transactions += 1;
+            collector.stats.bytes_written += bytes as u64;
+        }
+    }
+
+    pub(crate) fn begin_flush(&self) -> Option<FlushStart> {
+        self.stats.as_ref().map(|collector| FlushStart {
+            start: Instant::now(),
+            bytes: collector.stats.bytes_written,
+            transactions: collector.stats.transactions,
+        })
+    }
+
+    pub(crate) fn end_flush(&mut self, flush: Option<FlushStart>, rect: Rect) {
+        let (Some(collector), Some(flush)) = (&mut self.stats, flush) else {
+            return
+        };
+
+        let duration = flush.start.elapsed();
+        let stats = &mut collector.stats;
+        stats.flushes += 1;
+        stats.flush_time += duration;
+        stats.longest_flush = stats.longest_flush.max(duration);
+
+        if let Some(callback) = &mut collector.callback {
+            callback(&FlushStats {
+                rect,
+                bytes: stats.bytes_written - flush.bytes,
+                transactions: stats.transactions - flush.transactions,
+                duration,
+            });
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Streaming pre-packed frames straight to the screen, e.g. a camera preview
+
+use alloc::vec::Vec;
+
+use crate::{driver::{double_pixels, rotate_packed}, Driver, Error, Orientation, Rect};
+#[cfg(feature = "ssd1351")]
+use crate::Controller;
+
+impl Driver {
+    /// Stream full screen packed frames (e.g. from a channel or ring buffer) to the screen until they run out. Returns the number of frames shown.
+    ///
+    /// The window is set once and each frame is written in one transaction where the bus allows, skipping the framebuffer, [`TransferConfig`](crate::TransferConfig) chunking and dirty tracking.
+    /// Frames are the size of the framebuffer, the last one shown is copied into it when the stream ends so later draws carry on from it.
+    pub fn stream_frames<I, B>(&mut self, frames: I) -> Result<usize, Error>
+    where
+        I: IntoIterator<Item = B>,
+        B: AsRef<[u8]>,
+    {
+        if self.scrolling {
+            return Err(Error::Scrolling)
+        }
+        #[cfg(feature = "std")]
+        self.wake_screensaver()?;
+        let bounds = self.geometry().bounds();
+        self.record_physical_write(bounds);
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("stream").entered();
+
+        let bounds = self.framebuffer().bounds();
+        let mut stream = Stream { window: false, remapped: Vec::new(), doubled: Vec::new(), rotated: Vec::new(), #[cfg(feature = "ssd1351")] colors: Vec::new() };
+        let mut last = None;
+        let mut shown = 0;
+        let mut result = Ok(());
+        for frame in frames {
+            if let Err(e) = self.stream_frame(frame.as_ref(), bounds, &mut stream) {
+                result = Err(e);
+                break
+            }
+            last = Some(frame);
+            shown += 1;
+        }
+
+        if let Some(frame) = last {
+            let len = self.framebuffer().as_bytes().len();
+            self.framebuffer_mut().as_bytes_mut().copy_from_slice(&frame.as_ref()[..len]);
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(frames = shown, "stream ended");
+        result.map(|_| shown)
+    }
+
+    fn stream_frame(&mut self, frame: &[u8], bounds: Rect, stream: &mut Stream) -> Result<(), Error> {
+        let len = bounds.w / 2 * bounds.h;
+        if frame.len() < len {
+            return Err(Error::OutOfBounds { operation: "stream_frames", rect: bounds, len: frame.len() })
+        }
+
+        let mut data = &frame[..len];
+        if !self.remap().is_identity() {
+            stream.remapped.clear();
+            stream.remapped.extend(data.iter().map(|byte| self.remap().byte(*byte)));
+            data = &stream.remapped;
+        }
+        if self.is_pixel_doubled() {
+            double_pixels(data, bounds.w, bounds.h, &mut stream.doubled)?;
+            data = &stream.doubled;
+        }
+        if self.orientation() == Orientation::Rotated180 {
+            rotate_packed(data, &mut stream.rotated);
+            data = &stream.rotated;
+        }
+        #[cfg(feature = "ssd1351")]
+        if let Controller::Ssd1351(palette) = self.controller() {
+            palette.convert(data, &mut stream.colors);
+            data = &stream.colors;
+        }
+
+        let blanked = self.begin_frame(bounds)?;
+        // Commands end the SSD1351's RAM write mode, so the window is set again after the blanking commands
+        stream.window &= !blanked;
+        #[cfg(feature = "std")]
+        let flush = self.begin_flush();
+        let geometry = self.geometry();
+        let screen = Rect::new(0, 0, geometry.width, geometry.height);
+        // The cursor wraps back to the start of a full screen window, so otherwise the window only needs setting again after a failed write
+        let window = &mut stream.window;
+        let result = self.retry(|this| {
+            if !*window {
+                this.write_window(screen)?;
+                *window = true;
+            }
+            #[cfg(feature = "std")]
+            this.record_transaction(data.len());
+            let result = this.interface.data(data);
+            *window = result.is_ok();
+            result
+        });
+        #[cfg(feature = "std")]
+        self.end_flush(flush, screen);
+        let result = self.supervise(result);
+        if blanked {
+            return result.and(self.end_frame())
+        }
+        result
+    }
+}
+
+/// Reusable buffers and the window state for one stream
+struct Stream {
+    window: bool,
+    remapped: Vec<u8>,
+    doubled: Vec<u8>,
+    rotated: Vec<u8>,
+    #[cfg(feature = "ssd1351")]
+    colors: Vec<u8>,
+}
@@ -0,0 +1,99 @@
+//! Double buffering, drawing a frame off screen and showing it in one go
+
+use alloc::boxed::Box;
+
+use crate::{Driver, Error, Framebuffer, Rect};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// What a swap callback does with a frame, see [`Driver::with_swap_callback`]
+pub enum SwapAction {
+    /// Show the back buffer
+    Present,
+    /// Keep the back buffer as it is without showing it, e.g. when frames arrive faster than a rate limit. The next swap shows it
+    Defer,
+    /// Throw away what was drawn to the back buffer since the last frame shown
+    Cancel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The frame a swap is about to show
+pub struct SwapInfo {
+    /// The area that differs from the screen, in logical pixels
+    pub changed: Rect,
+    /// Number of frames shown by swaps so far
+    pub frames: u64,
+}
+
+pub(crate) type SwapCallback = Box<dyn FnMut(&SwapInfo) -> SwapAction + Send>;
+
+impl Driver {
+    /// Call a function before each swap shows a frame, which can present, defer or cancel it
+    pub fn with_swap_callback<F: FnMut(&SwapInfo) -> SwapAction + Send + 'static>(mut self, callback: F) -> Driver {
+        self.swap_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Replace the function called before each swap, None to present every frame
+    pub fn set_swap_callback(&mut self, callback: Option<SwapCallback>) {
+        self.swap_callback = callback;
+    }
+
+    /// The back buffer, None until it's first drawn to
+    pub fn back_buffer(&self) -> Option<&Framebuffer> {
+        self.back.as_ref()
+    }
+
+    /// The back buffer, for drawing the next frame off screen. It starts as a copy of the framebuffer, and after each swap holds the frame shown,
+    /// so frames can be drawn incrementally.
+    ///
+    /// Draws straight to the framebuffer aren't copied into the back buffer, they're overwritten by the next swap.
+    pub fn back_buffer_mut(&mut self) -> &mut Framebuffer {
+        let back = self.back.take().unwrap_or_else(|| self.framebuffer().clone());
+        self.back.insert(back)
+    }
+
+    /// Show the back buffer, writing the area that changed in one window. Returns the area written, None if nothing changed
+    /// or the swap callback deferred or cancelled the frame.
+    ///
+    /// Doesn't allocate once the screen has been flushed, see [`Driver::present_from`].
+    pub fn swap(&mut self) -> Result<Option<Rect>, Error> {
+        let Some(mut back) = self.back.take() else {
+            return Ok(None)
+        };
+        let Some(changed) = back.diff(self.framebuffer()) else {
+            self.back = Some(back);
+            return Ok(None)
+        };
+
+        let action = match &mut self.swap_callback {
+            Some(callback) => callback(&SwapInfo { changed, frames: self.swaps }),
+            None => SwapAction::Present,
+        };
+        #[cfg(feature = "tracing")]
+        tracing::trace!(?changed, ?action, "swap");
+
+        let result = match action {
+            SwapAction::Present => {
+                let result = self.present_from(&back);
+                if result.is_ok() {
+                    self.swaps += 1;
+                }
+                result
+            },
+            SwapAction::Defer => Ok(None),
+            SwapAction::Cancel => back.copy_from(self.framebuffer()).map(|_| None),
+        };
+        self.back = Some(back);
+        result
+    }
+
+    /// Number of frames shown by [`Driver::swap`]
+    pub fn swaps(&self) -> u64 {
+        self.swaps
+    }
+
+    /// Drop the back buffer, freeing its memory. The next draw to it starts from a copy of the framebuffer again
+    pub fn discard_back_buffer(&mut self) {
+        self.back = None;
+    }
+}
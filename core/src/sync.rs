@@ -0,0 +1,98 @@
+//! Timing full frame writes against the panel's refresh, so fast animations don't show a frame half written
+//!
+//! The controller has no tearing signal, so a write either hides the panel while it happens or keeps a steady phase with the refresh.
+
+use core::time::Duration;
+
+use crate::{Controller, Driver, Error, Rect};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// How full frame writes are timed against the panel's refresh
+pub enum FrameSync {
+    /// Write straight away, a fast animation can show the top of one frame over the bottom of the last
+    #[default]
+    None,
+    /// Switch every pixel off while the frame is written and back on after, frames never tear but the panel blinks briefly
+    Blank,
+    /// Start each full frame write a whole number of refresh periods after the last one, so writes keep the same phase with the scan
+    /// instead of drifting through it. See [`PowerConfig::refresh_period`](crate::PowerConfig::refresh_period) for the period
+    #[cfg(feature = "std")]
+    Paced(Duration),
+}
+
+impl Driver {
+    /// Time full frame writes against the panel's refresh
+    pub fn with_frame_sync(mut self, sync: FrameSync) -> Driver {
+        self.sync = sync;
+        self
+    }
+
+    /// How full frame writes are timed
+    pub fn frame_sync(&self) -> FrameSync {
+        self.sync
+    }
+
+    /// Change how full frame writes are timed
+    pub fn set_frame_sync(&mut self, sync: FrameSync) {
+        self.sync = sync;
+        #[cfg(feature = "std")]
+        {
+            self.last_frame = None;
+        }
+    }
+
+    /// The panel's refresh period estimated from the power settings and the panel height
+    pub fn refresh_period(&self) -> Duration {
+        self.power_config().refresh_period(self.geometry().height)
+    }
+
+    /// Wait for or hide the refresh before a write to an area, in logical pixels. Returns true if the panel was blanked
+    pub(crate) fn begin_frame(&mut self, rect: Rect) -> Result<bool, Error> {
+        if rect != self.framebuffer().bounds() {
+            return Ok(false)
+        }
+
+        match self.sync {
+            FrameSync::None => Ok(false),
+            FrameSync::Blank => {
+                self.command(self.all_off_command())?;
+                Ok(true)
+            },
+            #[cfg(feature = "std")]
+            FrameSync::Paced(period) => {
+                let now = std::time::Instant::now();
+                let start = match self.last_frame {
+                    Some(last) if !period.is_zero() => {
+                        let periods = (now.saturating_duration_since(last).as_nanos() / period.as_nanos()) as u32 + 1;
+                        last + period * periods
+                    },
+                    _ => now,
+                };
+                std::thread::sleep(start.saturating_duration_since(now));
+                self.last_frame = Some(start);
+                Ok(false)
+            },
+        }
+    }
+
+    /// Show the panel again after a blanked write
+    pub(crate) fn end_frame(&mut self) -> Result<(), Error> {
+        self.command(self.normal_display_command())
+    }
+
+    fn all_off_command(&self) -> u8 {
+        match self.controller() {
+            Controller::Ssd1327 => 0xa6,
+            #[cfg(feature = "ssd1351")]
+            Controller::Ssd1351(_) => 0xa4,
+        }
+    }
+
+    fn normal_display_command(&self) -> u8 {
+        match self.controller() {
+            Controller::Ssd1327 => 0xa4,
+            #[cfg(feature = "ssd1351")]
+            Controller::Ssd1351(_) => 0xa6,
+        }
+    }
+}
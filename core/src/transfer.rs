@@ -0,0 +1,68 @@
+//! Splitting display data into bus transactions
+
+use core::time::Duration;
+
+use crate::{Driver, Error};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How display data is split up when it's written to the screen
+pub struct TransferConfig {
+    /// Most bytes of display data handed to the interface at once, `usize::MAX` (the default) to not split it
+    pub max_chunk: usize,
+    /// Wait between chunks, giving other devices on a shared bus a turn
+    pub chunk_delay: Duration,
+}
+
+impl Default for TransferConfig {
+    fn default() -> Self {
+        TransferConfig::UNCHUNKED
+    }
+}
+
+impl TransferConfig {
+    /// Write data in as few transactions as the interface allows
+    pub const UNCHUNKED: TransferConfig = TransferConfig { max_chunk: usize::MAX, chunk_delay: Duration::ZERO };
+
+    /// Create new
+    pub const fn new(max_chunk: usize, chunk_delay: Duration) -> TransferConfig {
+        TransferConfig { max_chunk, chunk_delay }
+    }
+}
+
+impl Driver {
+    /// Split display data into chunks, e.g. smaller chunks with a delay between them so other devices on the bus aren't starved by a full screen update
+    pub fn with_transfer_config(mut self, transfer: TransferConfig) -> Driver {
+        self.transfer = transfer;
+        self
+    }
+
+    /// How display data is split up
+    pub fn transfer_config(&self) -> TransferConfig {
+        self.transfer
+    }
+
+    /// Change how display data is split up, e.g. while another device needs more of the bus
+    pub fn set_transfer_config(&mut self, transfer: TransferConfig) {
+        self.transfer = transfer;
+    }
+
+    /// Write display data to the current window in chunks
+    pub(crate) fn write_chunked(&mut self, data: &[u8]) -> Result<(), Error> {
+        let TransferConfig { max_chunk, chunk_delay } = self.transfer;
+        #[cfg(feature = "tracing")]
+        let start = std::time::Instant::now();
+
+        for (i, chunk) in data.chunks(max_chunk.max(1)).enumerate() {
+            if i > 0 && !chunk_delay.is_zero() {
+                self.delay(chunk_delay);
+            }
+            #[cfg(feature = "std")]
+            self.record_transaction(chunk.len());
+            self.interface.data(chunk)?;
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(bytes = data.len(), chunks = data.len().div_ceil(max_chunk.max(1)), elapsed = ?start.elapsed(), "data written");
+        Ok(())
+    }
+}
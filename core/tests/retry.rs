@@ -0,0 +1,92 @@
+//! Retrying failed writes against an interface that fails on demand
+
+use std::{sync::{Arc, Mutex}, time::Duration};
+
+use ws_1in5_i2c_core::{Driver, Error, Interface, Rect, RetryPolicy};
+
+#[derive(Default)]
+struct Bus {
+    fail_commands: bool,
+    fail_data: bool,
+    commands: usize,
+    data: usize,
+    resets: usize,
+}
+
+struct FailingInterface(Arc<Mutex<Bus>>);
+
+impl Interface for FailingInterface {
+    fn command(&mut self, cmd: u8) -> Result<(), Error> {
+        self.commands(&[cmd])
+    }
+
+    fn commands(&mut self, _cmds: &[u8]) -> Result<(), Error> {
+        let mut bus = self.0.lock().unwrap();
+        bus.commands += 1;
+        if bus.fail_commands { Err(Error::Bus) } else { Ok(()) }
+    }
+
+    fn data(&mut self, _data: &[u8]) -> Result<(), Error> {
+        let mut bus = self.0.lock().unwrap();
+        bus.data += 1;
+        if bus.fail_data { Err(Error::Bus) } else { Ok(()) }
+    }
+
+    fn reset(&mut self) {
+        self.0.lock().unwrap().resets += 1;
+    }
+
+    fn delay(&mut self, _duration: Duration) {}
+}
+
+fn driver(retries: u32) -> (Driver, Arc<Mutex<Bus>>) {
+    let bus = Arc::new(Mutex::new(Bus::default()));
+    let driver = Driver::new(Box::new(FailingInterface(bus.clone()))).unwrap()
+        .with_retry_policy(RetryPolicy::new(retries, Duration::from_millis(1)));
+    *bus.lock().unwrap() = Bus::default();
+    (driver, bus)
+}
+
+#[test]
+fn failed_window_is_attempted_once_per_retry() {
+    let (mut driver, bus) = driver(2);
+    bus.lock().unwrap().fail_commands = true;
+
+    assert!(matches!(driver.flush(), Err(Error::Bus)));
+    let bus = bus.lock().unwrap();
+    assert_eq!(bus.commands, 3);
+    assert_eq!(bus.data, 0);
+}
+
+#[test]
+fn failed_data_retries_from_the_window() {
+    let (mut driver, bus) = driver(2);
+    bus.lock().unwrap().fail_data = true;
+
+    assert!(matches!(driver.flush(), Err(Error::Bus)));
+    let bus = bus.lock().unwrap();
+    assert_eq!(bus.commands, 3);
+    assert_eq!(bus.data, 3);
+}
+
+#[test]
+fn failed_window_is_one_failure_for_recovery() {
+    let (driver, bus) = driver(2);
+    let mut driver = driver.with_recovery(2);
+    bus.lock().unwrap().fail_commands = true;
+
+    // Recovery resets the controller
+    assert!(driver.flush().is_err());
+    assert_eq!(bus.lock().unwrap().resets, 0);
+    assert!(driver.flush().is_err());
+    assert_eq!(bus.lock().unwrap().resets, 1);
+}
+
+#[test]
+fn set_window_retries() {
+    let (mut driver, bus) = driver(3);
+    bus.lock().unwrap().fail_commands = true;
+
+    assert!(matches!(driver.set_window(Rect::new(0, 0, 16, 16)), Err(Error::Bus)));
+    assert_eq!(bus.lock().unwrap().commands, 4);
+}
@@ -0,0 +1,48 @@
+//! Streaming frames to an emulated controller
+
+use std::{sync::{Arc, Mutex}, time::Duration};
+
+use ws_1in5_i2c_core::{Driver, Error, Framebuffer, FrameSync, Interface, emulator::{DisplayMode, Emulator}};
+
+/// An emulator which, like the SSD1351, only takes data straight after a window is set and stops at the next command
+struct WriteRamEmulator {
+    emulator: Arc<Mutex<Emulator>>,
+    writing: bool,
+}
+
+impl Interface for WriteRamEmulator {
+    fn command(&mut self, cmd: u8) -> Result<(), Error> {
+        self.commands(&[cmd])
+    }
+
+    fn commands(&mut self, cmds: &[u8]) -> Result<(), Error> {
+        self.writing = cmds.first() == Some(&0x15);
+        let mut emulator = self.emulator.lock().unwrap();
+        cmds.iter().try_for_each(|cmd| emulator.command(*cmd))
+    }
+
+    fn data(&mut self, data: &[u8]) -> Result<(), Error> {
+        if !self.writing {
+            return Ok(())
+        }
+        self.emulator.lock().unwrap().data(data)
+    }
+
+    fn delay(&mut self, _duration: Duration) {}
+}
+
+fn frame(level: u8) -> Vec<u8> {
+    vec![level << 4 | level; 64 * 128]
+}
+
+#[test]
+fn blanked_frames_are_all_written() {
+    let emulator = Arc::new(Mutex::new(Emulator::new()));
+    let mut driver = Driver::new(Box::new(WriteRamEmulator { emulator: emulator.clone(), writing: false })).unwrap()
+        .with_frame_sync(FrameSync::Blank);
+
+    assert_eq!(driver.stream_frames([frame(3), frame(9)]).unwrap(), 2);
+    let emulator = emulator.lock().unwrap();
+    assert_eq!(emulator.mode(), DisplayMode::Normal);
+    assert_eq!(emulator.frame(), Framebuffer::from_packed(128, 128, frame(9)).unwrap());
+}
@@ -0,0 +1,19 @@
+//! systemd sleep hook for a screen served by a `RemoteServer`
+//!
+//! Install the built binary as `/usr/lib/systemd/system-sleep/ws1in5` so the screen is switched off before the system suspends and restored when it wakes.
+//! The server address defaults to `127.0.0.1:7878` and can be set with `WS1IN5_ADDR`, a token can be set with `WS1IN5_TOKEN`.
+
+use std::env;
+
+use ws_1in5_i2c::remote;
+
+fn main() {
+    let address = env::var("WS1IN5_ADDR").unwrap_or_else(|_| "127.0.0.1:7878".to_string());
+    let token = env::var("WS1IN5_TOKEN").ok();
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    if let Err(e) = remote::sleep_hook(address.as_str(), token.as_deref(), &args) {
+        eprintln!("ws1in5 sleep hook: {}", e);
+        std::process::exit(1);
+    }
+}
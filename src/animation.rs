@@ -0,0 +1,82 @@
+//! Frame based animation
+
+use std::{thread, time::{Duration, Instant}};
+
+use crate::{WS1in5, Error, Framebuffer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Statistics from an animation run
+pub struct AnimationStats {
+    /// Frames drawn and flushed
+    pub frames: u64,
+    /// Frames skipped because drawing or flushing took longer than the frame time
+    pub dropped: u64,
+    /// Total time the animation ran for
+    pub elapsed: Duration,
+}
+
+/// Runs an animation loop at a target frame rate
+///
+/// Each frame is drawn into the display's framebuffer, then only the area that changed is written to the screen.
+/// If a frame takes too long the animation skips ahead to stay in time, counting the skipped frames as dropped.
+pub struct Animator {
+    frame_time: Duration,
+    max_frames: Option<u64>,
+}
+
+impl Animator {
+    /// Create new with a target frame rate
+    pub fn new(fps: f32) -> Animator {
+        let fps = if fps.is_finite() && fps > 0.0 { fps } else { 1.0 };
+        Animator { frame_time: Duration::from_secs_f32(1.0 / fps), max_frames: None }
+    }
+
+    /// Stop after a number of frames (including dropped frames)
+    pub fn with_max_frames(mut self, max_frames: u64) -> Animator {
+        self.max_frames = Some(max_frames);
+        self
+    }
+
+    /// Time between frames
+    pub fn frame_time(&self) -> Duration {
+        self.frame_time
+    }
+
+    /// Run the animation. The closure draws frame N into a framebuffer holding the previous frame, and returns false to stop
+    pub fn run<F>(&self, display: &mut WS1in5, mut draw: F) -> Result<AnimationStats, Error>
+    where
+        F: FnMut(u64, &mut Framebuffer) -> bool
+    {
+        let start = Instant::now();
+        let mut stats = AnimationStats::default();
+        let mut frame = display.framebuffer().clone();
+        let mut n = 0;
+
+        loop {
+            if self.max_frames.map(|max| n >= max).unwrap_or(false) {
+                break
+            }
+
+            if !draw(n, &mut frame) {
+                break
+            }
+
+            display.present_from(&frame)?;
+            stats.frames += 1;
+            n += 1;
+
+            let elapsed = start.elapsed();
+            let due = Duration::from_secs_f64(self.frame_time.as_secs_f64() * n as f64);
+            if elapsed < due {
+                thread::sleep(due - elapsed);
+            } else {
+                let behind = ((elapsed - due).as_secs_f64() / self.frame_time.as_secs_f64()) as u64;
+                n += behind;
+                stats.dropped += behind;
+            }
+        }
+
+        stats.elapsed = start.elapsed();
+        Ok(stats)
+    }
+}
@@ -0,0 +1,107 @@
+//! Named assets shared between draw commands
+
+use std::collections::HashMap;
+
+#[cfg(feature = "text")]
+use rusttype::{Font, Scale};
+
+use crate::{Error, Rect, Framebuffer, DrawOp};
+#[cfg(feature = "image")]
+use crate::images;
+
+/// A decoded asset
+pub enum Asset {
+    /// A packed image or sprite
+    Image(Framebuffer),
+    /// A font
+    #[cfg(feature = "text")]
+    Font(Font<'static>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// A draw command which refers to assets by name
+pub enum AssetCommand {
+    /// Clear a section
+    Clear(Rect),
+    /// Fill a section with a colour (0-15)
+    Fill(Rect, u8),
+    /// Draw a named image at a coord
+    Image { name: String, x: usize, y: usize },
+    /// Draw text (ignores new lines) with a named font at a coord, size in pixels and colour (0-15)
+    Text { font: String, size: f32, x: usize, y: usize, text: String, color: u8 },
+}
+
+#[derive(Default)]
+/// Named images and fonts which are decoded once and then referenced by draw commands
+pub struct AssetCache {
+    assets: HashMap<String, Asset>,
+}
+
+impl AssetCache {
+    /// Create new
+    pub fn new() -> AssetCache {
+        AssetCache { assets: HashMap::new() }
+    }
+
+    /// Add an asset, replacing any asset with the same name
+    pub fn insert(&mut self, name: &str, asset: Asset) {
+        self.assets.insert(name.to_string(), asset);
+    }
+
+    #[cfg(feature = "image")]
+    /// Decode an encoded image (PNG, JPEG, BMP, ...) to 4 bit greyscale and add it
+    pub fn upload_image(&mut self, name: &str, data: &[u8]) -> Result<(), Error> {
+        let image = image::load_from_memory(data).map_err(Error::from)?.to_luma8();
+        self.insert(name, Asset::Image(Framebuffer::from_image(&images::quantize(&image))));
+        Ok(())
+    }
+
+    #[cfg(feature = "text")]
+    /// Parse a TrueType or OpenType font and add it
+    pub fn upload_font(&mut self, name: &str, data: Vec<u8>) -> Result<(), Error> {
+        let font = Font::try_from_vec(data).ok_or(Error::InvalidFont)?;
+        self.insert(name, Asset::Font(font));
+        Ok(())
+    }
+
+    /// Remove an asset
+    pub fn remove(&mut self, name: &str) -> Option<Asset> {
+        self.assets.remove(name)
+    }
+
+    /// Get an asset
+    pub fn get(&self, name: &str) -> Option<&Asset> {
+        self.assets.get(name)
+    }
+
+    /// Returns true if an asset with the name exists
+    pub fn contains(&self, name: &str) -> bool {
+        self.assets.contains_key(name)
+    }
+
+    /// Names of every asset
+    pub fn names(&self) -> Vec<&str> {
+        self.assets.keys().map(|k| k.as_str()).collect()
+    }
+
+    /// Draw a command into a framebuffer. Returns the area drawn to
+    pub fn draw(&self, framebuffer: &mut Framebuffer, command: &AssetCommand) -> Result<Option<Rect>, Error> {
+        let op = match command {
+            AssetCommand::Clear(rect) => DrawOp::Clear(*rect),
+            AssetCommand::Fill(rect, color) => DrawOp::Fill(*rect, *color),
+            AssetCommand::Image { name, x, y } => match self.assets.get(name) {
+                Some(Asset::Image(image)) => DrawOp::Framebuffer { x: *x, y: *y, framebuffer: image },
+                _ => return Err(Error::UnknownAsset(name.clone())),
+            },
+            #[cfg(feature = "text")]
+            AssetCommand::Text { font, size, x, y, text, color } => match self.assets.get(font) {
+                Some(Asset::Font(f)) => DrawOp::Text { x: *x, y: *y, text, scale: Scale::uniform(*size), font: f, color: *color },
+                _ => return Err(Error::UnknownAsset(font.clone())),
+            },
+            // Fonts can't be uploaded without the text feature
+            #[cfg(not(feature = "text"))]
+            AssetCommand::Text { font, .. } => return Err(Error::UnknownAsset(font.clone())),
+        };
+        Ok(op.apply(framebuffer))
+    }
+}
@@ -0,0 +1,88 @@
+//! Batched drawing
+
+#[cfg(feature = "image")]
+use image::GrayImage;
+#[cfg(feature = "text")]
+use rusttype::{Scale, Font};
+
+use crate::{WS1in5, Error, Rect, Framebuffer, BlendMode};
+#[cfg(feature = "text")]
+use crate::text;
+
+#[derive(Clone, Copy)]
+/// A drawing operation for [`WS1in5::draw_batch`]
+pub enum DrawOp<'a> {
+    /// Clear a section of the screen
+    Clear(Rect),
+    /// Fill a section of the screen with a colour (0-15)
+    Fill(Rect, u8),
+    /// Draw a greyscale image (pixel values 0-15) at a coord
+    #[cfg(feature = "image")]
+    Image { x: usize, y: usize, image: &'a GrayImage },
+    /// Draw a packed framebuffer at a coord
+    Framebuffer { x: usize, y: usize, framebuffer: &'a Framebuffer },
+    /// Blend a packed framebuffer with what is already drawn at a coord
+    Blend { x: usize, y: usize, framebuffer: &'a Framebuffer, mode: BlendMode },
+    /// Draw text (ignores new lines) at a coord with a colour (0-15)
+    #[cfg(feature = "text")]
+    Text { x: usize, y: usize, text: &'a str, scale: Scale, font: &'a Font<'a>, color: u8 },
+}
+
+impl<'a> DrawOp<'a> {
+    /// Area of the screen the operation draws to
+    pub fn bounds(&self) -> Rect {
+        match self {
+            DrawOp::Clear(rect) | DrawOp::Fill(rect, _) => *rect,
+            #[cfg(feature = "image")]
+            DrawOp::Image { x, y, image } => Rect::new(*x, *y, image.width() as usize, image.height() as usize),
+            DrawOp::Framebuffer { x, y, framebuffer } | DrawOp::Blend { x, y, framebuffer, .. } => Rect::new(*x, *y, framebuffer.width(), framebuffer.height()),
+            #[cfg(feature = "text")]
+            DrawOp::Text { x, y, text, scale, font, .. } => {
+                let (w, h) = text::text_size(text, scale, font);
+                Rect::new(*x, *y, w, h)
+            },
+        }
+    }
+
+    /// Apply the operation to a framebuffer. Returns the area drawn to
+    pub fn apply(&self, framebuffer: &mut Framebuffer) -> Option<Rect> {
+        let screen = framebuffer.bounds();
+        match self {
+            DrawOp::Clear(rect) => framebuffer.fill_rect(*rect, 0),
+            DrawOp::Fill(rect, color) => framebuffer.fill_rect(*rect, *color),
+            #[cfg(feature = "image")]
+            DrawOp::Image { x, y, image } => framebuffer.draw_image(*x, *y, image),
+            DrawOp::Framebuffer { x, y, framebuffer: source } => framebuffer.blit_framebuffer(*x, *y, source),
+            DrawOp::Blend { x, y, framebuffer: source, mode } => framebuffer.blend_framebuffer(*x, *y, source, *mode),
+            #[cfg(feature = "text")]
+            DrawOp::Text { x, y, text, scale, font, color } => {
+                let (w, h) = text::text_size(text, scale, font);
+                let mut image = GrayImage::new(w as u32, h as u32);
+                text::render_text(&mut image, 0, 0, text, scale, font, *color);
+                framebuffer.draw_image(*x, *y, &image);
+            },
+        }
+        self.bounds().intersection(&screen)
+    }
+}
+
+impl WS1in5 {
+    /// Draw every operation into the framebuffer, then write the changed area to the screen in a single update, so a half drawn batch is never shown.
+    /// Returns the area of the screen written to
+    pub fn draw_batch(&mut self, ops: &[DrawOp]) -> Result<Option<Rect>, Error> {
+        let mut framebuffer = self.framebuffer().clone();
+
+        let mut dirty: Option<Rect> = None;
+        for op in ops {
+            if let Some(rect) = op.apply(&mut framebuffer) {
+                dirty = Some(dirty.map(|d| d.union(&rect)).unwrap_or(rect));
+            }
+        }
+
+        *self.framebuffer_mut() = framebuffer;
+        if let Some(rect) = dirty {
+            self.flush_rect(rect)?;
+        }
+        Ok(dirty.map(|rect| rect.align_even()))
+    }
+}
@@ -0,0 +1,144 @@
+//! One shot screen operations from the command line, e.g. for cron jobs or checking new hardware
+//!
+//! ```text
+//! ws1in5 [--bus N] [--address N] [--reset PIN | --no-reset] [--brightness N] <command>
+//!
+//! show-text <text> [--x N] [--y N] [--size N] [--font PATH]
+//! show-image <path> [--fit stretch|letterbox|crop] [--auto-contrast]
+//! clear
+//! brightness <0-255>
+//! sleep
+//! probe
+//! ```
+//!
+//! The screen is initialised on every run, which resets the brightness, so `--brightness` can be given with any command.
+//! Addresses can be given in hex (`0x3d`). `probe` prints the address the screen answers at on the bus, without initialising it.
+
+use std::{env, fs, process};
+
+use rusttype::{Font, Scale};
+use ws_1in5_i2c::{WS1in5, Error, Fit, I2cInterface, ImageOptions};
+
+const USAGE: &str = "usage: ws1in5 [--bus N] [--address N] [--reset PIN | --no-reset] [--brightness N] <show-text TEXT [--x N] [--y N] [--size N] [--font PATH] | show-image PATH [--fit stretch|letterbox|crop] [--auto-contrast] | clear | brightness N | sleep | probe>";
+const DEFAULT_FONT: &str = "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf";
+
+enum Command {
+    ShowText { text: String, x: usize, y: usize, size: f32, font: String },
+    ShowImage { path: String, options: ImageOptions },
+    Clear,
+    Brightness(u8),
+    Sleep,
+    Probe,
+}
+
+struct Args {
+    bus: u8,
+    address: u16,
+    reset: Option<u8>,
+    brightness: Option<u8>,
+    command: Command,
+}
+
+fn number<T: TryFrom<u64>>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{} needs a value", flag))?;
+    let parsed = match value.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.ok().and_then(|n| T::try_from(n).ok()).ok_or_else(|| format!("invalid value for {}: {}", flag, value))
+}
+
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let (mut bus, mut address, mut reset, mut brightness) = (1, 0x3d, Some(27), None);
+    let mut positional = vec![];
+    let (mut x, mut y, mut size, mut font) = (0, 0, 12.0, DEFAULT_FONT.to_string());
+    let mut options = ImageOptions::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bus" => bus = number(&arg, args.next())?,
+            "--address" => address = number(&arg, args.next())?,
+            "--reset" => reset = Some(number(&arg, args.next())?),
+            "--no-reset" => reset = None,
+            "--brightness" => brightness = Some(number(&arg, args.next())?),
+            "--x" => x = number(&arg, args.next())?,
+            "--y" => y = number(&arg, args.next())?,
+            "--size" => size = args.next().and_then(|size| size.parse().ok()).ok_or("invalid value for --size")?,
+            "--font" => font = args.next().ok_or("--font needs a value")?,
+            "--fit" => options.fit = match args.next().as_deref() {
+                Some("stretch") => Fit::Stretch,
+                Some("letterbox") => Fit::Letterbox,
+                Some("crop") => Fit::Crop,
+                _ => return Err("--fit must be stretch, letterbox or crop".to_string()),
+            },
+            "--auto-contrast" => options.auto_contrast = true,
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            flag if flag.starts_with("--") => return Err(format!("unknown flag {}", flag)),
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let command = match positional.next().as_deref() {
+        Some("show-text") => Command::ShowText { text: positional.next().ok_or("show-text needs text")?, x, y, size, font },
+        Some("show-image") => Command::ShowImage { path: positional.next().ok_or("show-image needs a path")?, options },
+        Some("clear") => Command::Clear,
+        Some("brightness") => Command::Brightness(number("brightness", positional.next())?),
+        Some("sleep") => Command::Sleep,
+        Some("probe") => Command::Probe,
+        Some(command) => return Err(format!("unknown command {}\n{}", command, USAGE)),
+        None => return Err(USAGE.to_string()),
+    };
+    if let Some(extra) = positional.next() {
+        return Err(format!("unexpected argument {}", extra))
+    }
+
+    Ok(Args { bus, address, reset, brightness, command })
+}
+
+fn run(args: Args) -> Result<(), Error> {
+    if let Command::Probe = args.command {
+        println!("{:#04x}", I2cInterface::probe(args.bus)?);
+        return Ok(())
+    }
+
+    let mut display = match args.reset {
+        Some(reset) => WS1in5::new(args.address, args.bus, reset)?,
+        None => WS1in5::new_without_reset(args.address, args.bus)?,
+    };
+    if let Some(brightness) = args.brightness {
+        display.set_contrast(brightness)?;
+    }
+
+    match args.command {
+        Command::ShowText { text, x, y, size, font } => {
+            let font = Font::try_from_vec(fs::read(font).map_err(Error::from)?).ok_or(Error::InvalidFont)?;
+            display.clear_all()?;
+            display.draw_text(x, y, &text, &Scale::uniform(size), &font)?;
+        },
+        Command::ShowImage { path, options } => {
+            display.clear_all()?;
+            display.show_image_file_with(path, &options)?;
+        },
+        Command::Clear => display.clear_all()?,
+        Command::Brightness(brightness) => display.set_contrast(brightness)?,
+        Command::Sleep => display.suspend()?,
+        Command::Probe => {},
+    }
+    Ok(())
+}
+
+fn main() {
+    let args = match parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        },
+    };
+
+    if let Err(e) = run(args) {
+        eprintln!("ws1in5: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,34 @@
+//! Daemon owning the screen and running text commands from a Unix socket, see the `daemon` module for the protocol
+//!
+//! Configured with environment variables:
+//! - `WS1IN5_SOCKET`: socket path, defaults to `/run/ws1in5.sock`
+//! - `WS1IN5_FONT`: font used for text, defaults to `/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf`
+//! - `WS1IN5_BUS`, `WS1IN5_ADDRESS` and `WS1IN5_RESET`: the screen's i2c bus, address and reset pin, default to 1, 0x3d and 27
+
+use std::{env, fs, process, str::FromStr};
+
+use rusttype::Font;
+use ws_1in5_i2c::{WS1in5, Error, daemon::Daemon};
+
+fn var<T: FromStr>(name: &str, default: T) -> T {
+    env::var(name).ok().and_then(|value| value.parse().ok()).unwrap_or(default)
+}
+
+fn run() -> Result<(), Error> {
+    let socket = env::var("WS1IN5_SOCKET").unwrap_or_else(|_| "/run/ws1in5.sock".to_string());
+    let font = env::var("WS1IN5_FONT").unwrap_or_else(|_| "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf".to_string());
+    let font = Font::try_from_vec(fs::read(font).map_err(Error::from)?).ok_or(Error::InvalidFont)?;
+
+    let mut display = WS1in5::new(var("WS1IN5_ADDRESS", 0x3d), var("WS1IN5_BUS", 1), var("WS1IN5_RESET", 27))?;
+    display.clear_all()?;
+
+    let daemon = Daemon::bind(&socket, font)?;
+    daemon.serve(&mut display)
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("ws1in5d: {}", e);
+        process::exit(1);
+    }
+}
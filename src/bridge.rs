@@ -0,0 +1,162 @@
+//! Mirroring another display onto the screen, e.g. a Linux framebuffer running a GUI
+
+use std::{fs::{self, File}, io::{self, Read, Seek, SeekFrom}, path::{Path, PathBuf}, process, thread, time::{Duration, Instant}};
+
+use image::{GrayImage, Luma, imageops};
+
+use crate::{WS1in5, Error, DriverError, Rect, Framebuffer, ImageOptions, images};
+
+/// Something which can be captured as an 8 bit greyscale image, e.g. a framebuffer device or a screen capture tool
+pub trait Capture {
+    /// Capture the whole source
+    fn capture(&mut self) -> Result<GrayImage, Error>;
+}
+
+/// A Linux framebuffer device (`/dev/fbN`), read as 16, 24 or 32 bit colour
+pub struct LinuxFramebuffer {
+    file: File,
+    width: usize,
+    height: usize,
+    stride: usize,
+    bits_per_pixel: usize,
+    buffer: Vec<u8>,
+}
+
+impl LinuxFramebuffer {
+    /// Open `/dev/fbN`, reading its size and format from sysfs
+    pub fn open(index: u8) -> Result<LinuxFramebuffer, Error> {
+        let sysfs = PathBuf::from(format!("/sys/class/graphics/fb{}", index));
+        let (width, height) = read_sysfs(&sysfs.join("virtual_size"))?
+            .split_once(',')
+            .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+            .ok_or_else(|| invalid_data("invalid framebuffer size"))?;
+        let bits_per_pixel = read_sysfs(&sysfs.join("bits_per_pixel"))?.parse().map_err(|_| invalid_data("invalid framebuffer depth"))?;
+        let stride = read_sysfs(&sysfs.join("stride"))?.parse().map_err(|_| invalid_data("invalid framebuffer stride"))?;
+
+        LinuxFramebuffer::with_format(format!("/dev/fb{}", index), width, height, stride, bits_per_pixel)
+    }
+
+    /// Open a framebuffer device or a file holding a dump of one, with its size, bytes per row and depth (16 for RGB565, 24 or 32 for BGR(A))
+    pub fn with_format<P: AsRef<Path>>(path: P, width: usize, height: usize, stride: usize, bits_per_pixel: usize) -> Result<LinuxFramebuffer, Error> {
+        if !matches!(bits_per_pixel, 16 | 24 | 32) {
+            return Err(DriverError::Unsupported("framebuffer depths other than 16, 24 or 32 bits").into())
+        }
+        if stride < width * bits_per_pixel / 8 {
+            return Err(invalid_data("framebuffer stride is shorter than a row"))
+        }
+        let file = File::open(path).map_err(Error::from)?;
+        Ok(LinuxFramebuffer { file, width, height, stride, bits_per_pixel, buffer: vec![0; stride * height] })
+    }
+
+    /// Size of the framebuffer in pixels
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+}
+
+fn read_sysfs(path: &Path) -> Result<String, Error> {
+    fs::read_to_string(path).map(|value| value.trim().to_string()).map_err(Error::from)
+}
+
+fn invalid_data(message: &str) -> Error {
+    io::Error::new(io::ErrorKind::InvalidData, message).into()
+}
+
+impl Capture for LinuxFramebuffer {
+    fn capture(&mut self) -> Result<GrayImage, Error> {
+        self.file.seek(SeekFrom::Start(0)).map_err(Error::from)?;
+        self.file.read_exact(&mut self.buffer).map_err(Error::from)?;
+
+        let bytes = self.bits_per_pixel / 8;
+        Ok(GrayImage::from_fn(self.width as u32, self.height as u32, |x, y| {
+            let offset = y as usize * self.stride + x as usize * bytes;
+            let pixel = &self.buffer[offset..offset + bytes];
+            let (r, g, b) = match bytes {
+                2 => {
+                    let color = u16::from_le_bytes([pixel[0], pixel[1]]);
+                    (((color >> 11) & 0x1f) as u32 * 255 / 31, ((color >> 5) & 0x3f) as u32 * 255 / 63, (color & 0x1f) as u32 * 255 / 31)
+                },
+                _ => (pixel[2] as u32, pixel[1] as u32, pixel[0] as u32),
+            };
+            Luma([((r * 299 + g * 587 + b * 114) / 1000) as u8])
+        }))
+    }
+}
+
+/// A screen capture tool run for every frame, printing an image (PNG, BMP, ...) to stdout, e.g. `grim -` on Wayland or `import -window root png:-` on X
+pub struct CommandCapture {
+    program: String,
+    args: Vec<String>,
+}
+
+impl CommandCapture {
+    /// Create new from a program and its arguments
+    pub fn new(program: &str, args: &[&str]) -> CommandCapture {
+        CommandCapture { program: program.to_string(), args: args.iter().map(|arg| arg.to_string()).collect() }
+    }
+}
+
+impl Capture for CommandCapture {
+    fn capture(&mut self) -> Result<GrayImage, Error> {
+        let output = process::Command::new(&self.program).args(&self.args).output().map_err(Error::from)?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!("{} failed: {}", self.program, output.status)).into())
+        }
+        Ok(image::load_from_memory(&output.stdout).map_err(Error::from)?.to_luma8())
+    }
+}
+
+/// Mirrors a capture source (or a region of it) onto the screen at a steady rate, scaled and converted to 4 bit greyscale
+pub struct Bridge<C: Capture> {
+    source: C,
+    region: Option<Rect>,
+    interval: Duration,
+    options: ImageOptions,
+}
+
+impl<C: Capture> Bridge<C> {
+    /// Create new, mirroring the whole source ten times a second
+    pub fn new(source: C) -> Bridge<C> {
+        Bridge { source, region: None, interval: Duration::from_millis(100), options: ImageOptions::default() }
+    }
+
+    /// Only mirror a region of the source
+    pub fn with_region(mut self, region: Rect) -> Bridge<C> {
+        self.region = Some(region);
+        self
+    }
+
+    /// Set how many frames are mirrored a second
+    pub fn with_fps(mut self, fps: u32) -> Bridge<C> {
+        self.interval = Duration::from_secs(1) / fps.max(1);
+        self
+    }
+
+    /// Set how captures are scaled and adjusted for the screen
+    pub fn with_options(mut self, options: ImageOptions) -> Bridge<C> {
+        self.options = options;
+        self
+    }
+
+    /// Capture a frame and show it, only writing what changed. Returns the area written
+    pub fn mirror(&mut self, display: &mut WS1in5) -> Result<Option<Rect>, Error> {
+        let mut capture = self.source.capture()?;
+        if let Some(region) = self.region {
+            capture = imageops::crop_imm(&capture, region.x as u32, region.y as u32, region.w as u32, region.h as u32).to_image();
+        }
+
+        let image = images::prepare_image(&capture, display.width() as u32, display.height() as u32, &self.options);
+        let mut frame = Framebuffer::new(display.width(), display.height());
+        frame.draw_image(0, 0, &image);
+        Ok(display.present(frame)?)
+    }
+
+    /// Mirror frames forever, or until capturing or drawing fails. Frames which take longer than the interval delay the next rather than piling up
+    pub fn run(&mut self, display: &mut WS1in5) -> Result<(), Error> {
+        loop {
+            let start = Instant::now();
+            self.mirror(display)?;
+            thread::sleep(self.interval.saturating_sub(start.elapsed()));
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Deprecated methods kept for projects written against older versions, enabled by the `compat` feature (on by default)
+//!
+//! Each method is deprecated with a note pointing at its replacement:
+//!
+//! | Old | New |
+//! | --- | --- |
+//! | `WS1in5::get_buffer(image.enumerate_pixels(), w, h)` | [`images::pack`]`(&image)` |
+//! | `WS1in5::size_to_pow_2(size)` | sizes from [`text::text_size`] are already even |
+//! | `WS1in5::get_text_size_full(text, scale, font)` | [`text::text_size_full`]`(text, scale, font)` |
+//! | `WS1in5::get_text_size(text, scale, font)` | [`text::text_size`]`(text, scale, font)` |
+//! | `WS1in5::create_text(text, scale, font, flip)` | [`text::text_image`]`(text, scale, font, 15)`, rotated with `imageops::rotate180` if flipped |
+//! | `draw_text(.., flip)`, `draw_image(.., flip)` and the other draws' `flip` argument | [`Driver::set_orientation`](crate::Driver::set_orientation)`(Orientation::Rotated180)` once, then draw without it |
+//! | `has_cleared()` (still available in the core, deprecated) | [`Driver::is_clean`](crate::Driver::is_clean)`(rect)` or [`Driver::damage`](crate::Driver::damage) |
+//!
+//! Disable default features and leave `compat` out to make sure a project no longer uses any of them.
+
+#[cfg(feature = "image")]
+use image::{buffer::EnumeratePixels, Luma};
+#[cfg(feature = "text")]
+use image::{GrayImage, imageops};
+#[cfg(feature = "text")]
+use rusttype::{Scale, Font};
+
+#[cfg(feature = "image")]
+use crate::{WS1in5, Error, DriverError, Rect, images};
+#[cfg(feature = "text")]
+use crate::text;
+
+#[cfg(feature = "image")]
+impl WS1in5 {
+    #[deprecated(note = "use images::pack")]
+    /// Convert image to buffer data
+    pub fn get_buffer(&self, pixels: EnumeratePixels<Luma<u8>>, width: usize, height: usize) -> Result<Vec<u8>, Error> {
+        if pixels.len() != height * width {
+            return Err(DriverError::OutOfBounds { operation: "get_buffer", rect: Rect::new(0, 0, width, height), len: pixels.len() }.into())
+        }
+
+        let values: Vec<u8> = pixels.map(|(_, _, pixel)| pixel.0[0]).collect();
+        Ok(images::pack_pixels(&values, width, height))
+    }
+
+    #[deprecated(note = "text sizes are already rounded up to be even")]
+    /// Round a size up to be even
+    pub fn size_to_pow_2(mut size: (i32, i32)) -> (i32, i32) {
+        if size.0 % 2 != 0 {
+            size.0 += 1
+        }
+        if size.1 % 2 != 0 {
+            size.1 += 1
+        }
+        size
+    }
+
+    #[cfg(feature = "text")]
+    #[deprecated(note = "use text::text_size_full")]
+    /// Get the size of some text (ignoring new lines), returns the size of each character too.
+    pub fn get_text_size_full(&self, text: &str, scale: &Scale, font: &Font) -> (usize, usize, usize) {
+        text::text_size_full(text, scale, font)
+    }
+
+    #[cfg(feature = "text")]
+    #[deprecated(note = "use text::text_size")]
+    /// Get the size of some text (ignoring new lines)
+    pub fn get_text_size(&self, text: &str, scale: &Scale, font: &Font) -> (usize, usize) {
+        text::text_size(text, scale, font)
+    }
+
+    #[cfg(feature = "text")]
+    #[deprecated(note = "use text::text_image")]
+    /// Create image from text
+    pub fn create_text(&self, text: &str, scale: &Scale, font: &Font, flip: bool) -> (GrayImage, usize, usize) {
+        let image = text::text_image(text, scale, font, 15);
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        if flip {
+            (imageops::rotate180(&image), width, height)
+        } else {
+            (image, width, height)
+        }
+    }
+}
@@ -0,0 +1,444 @@
+//! Compositing pages from multiple clients
+
+use std::{collections::BTreeMap, time::{Duration, Instant}};
+
+use crate::{WS1in5, Error, DriverError, Rect, Framebuffer, Geometry, BlendMode, assets::{AssetCache, AssetCommand}};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// A client of a [`Compositor`]
+pub struct ClientId(u32);
+
+impl ClientId {
+    /// The id as a number
+    pub fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+/// Limits on how often and how much a client can update the screen
+pub struct Quota {
+    /// Most swaps shown per second
+    pub max_fps: Option<f32>,
+    /// Most bytes of changed page written to the screen per second, averaged over time
+    pub max_bytes_per_sec: Option<usize>,
+}
+
+impl Quota {
+    /// No limits
+    pub const UNLIMITED: Quota = Quota { max_fps: None, max_bytes_per_sec: None };
+}
+
+struct Client {
+    region: Rect,
+    z: i32,
+    visible: bool,
+    blend: BlendMode,
+    front: Framebuffer,
+    back: Framebuffer,
+
+    quota: Quota,
+    pending: bool,
+    last_shown: Option<Instant>,
+    budget: f64,
+    budget_updated: Instant,
+    served: u64,
+}
+
+impl Client {
+    /// The bandwidth budget refilled up to a point in time, holding at most one second's worth
+    fn budget_at(&self, now: Instant) -> f64 {
+        match self.quota.max_bytes_per_sec {
+            Some(rate) => {
+                let elapsed = now.duration_since(self.budget_updated).as_secs_f64();
+                (self.budget + elapsed * rate as f64).min(rate as f64)
+            },
+            None => 0.0,
+        }
+    }
+
+    /// Refill the bandwidth budget
+    fn refill(&mut self, now: Instant) {
+        self.budget = self.budget_at(now);
+        self.budget_updated = now;
+    }
+
+    /// Time until the quota allows the client's next swap to be shown
+    fn wait(&self, now: Instant) -> Duration {
+        let mut wait = Duration::ZERO;
+        if let (Some(fps), Some(last)) = (self.quota.max_fps, self.last_shown) {
+            if fps > 0.0 {
+                let interval = Duration::from_secs_f64(1.0 / fps as f64);
+                wait = wait.max(interval.saturating_sub(now.duration_since(last)));
+            }
+        }
+        if let Some(rate) = self.quota.max_bytes_per_sec {
+            let budget = self.budget_at(now);
+            if budget < 0.0 && rate > 0 {
+                wait = wait.max(Duration::from_secs_f64(-budget / rate as f64));
+            }
+        }
+        wait
+    }
+
+    /// Bytes written to the screen if the back page were shown
+    fn cost(&self) -> usize {
+        self.back.diff(&self.front)
+            .map(|rect| rect.align_even())
+            .map(|rect| rect.w / 2 * rect.h)
+            .unwrap_or(0)
+    }
+}
+
+/// Composites pages from multiple clients onto the screen
+///
+/// Each client owns a region of the screen and draws into an off screen back page.
+/// When the page is complete the client swaps it in, and the screen is updated in a single write so a half built page from one client is never shown alongside another client's.
+///
+/// Assets are shared between clients and kept for the life of the compositor, so they only need to be uploaded once.
+///
+/// Clients can be given a [`Quota`], swaps beyond it are held back until [`Compositor::pump`] finds the quota allows them.
+/// Held back swaps are shown least recently served client first, and a per update byte budget stops one chatty client from monopolising the bus.
+pub struct Compositor {
+    clients: BTreeMap<ClientId, Client>,
+    screen: Rect,
+    next_id: u32,
+    background: u8,
+    assets: AssetCache,
+    default_quota: Quota,
+    bytes_per_present: Option<usize>,
+    served: u64,
+}
+
+impl Default for Compositor {
+    fn default() -> Self {
+        Compositor::new()
+    }
+}
+
+impl Compositor {
+    /// Create new
+    pub fn new() -> Compositor {
+        Compositor {
+            clients: BTreeMap::new(),
+            screen: Geometry::default().bounds(),
+            next_id: 0,
+            background: 0,
+            assets: AssetCache::new(),
+            default_quota: Quota::UNLIMITED,
+            bytes_per_present: None,
+            served: 0,
+        }
+    }
+
+    /// Composite for a panel of another size
+    pub fn with_geometry(mut self, geometry: Geometry) -> Compositor {
+        self.screen = geometry.bounds();
+        self
+    }
+
+    /// Set the colour (0-15) shown where no client draws
+    pub fn with_background(mut self, background: u8) -> Compositor {
+        self.background = background;
+        self
+    }
+
+    /// Set the quota given to new clients
+    pub fn with_default_quota(mut self, quota: Quota) -> Compositor {
+        self.default_quota = quota;
+        self
+    }
+
+    /// Limit the bytes of client updates combined into one screen update, clients that don't fit wait for the next one.
+    /// At least one client is always served
+    pub fn with_bytes_per_present(mut self, bytes: usize) -> Compositor {
+        self.bytes_per_present = Some(bytes);
+        self
+    }
+
+    /// Add a client owning a region of the screen, clients with a higher z are drawn on top. The region is clipped to the screen
+    pub fn add_client(&mut self, region: Rect, z: i32) -> ClientId {
+        let region = region.align_even()
+            .intersection(&self.screen)
+            .unwrap_or_default();
+
+        let id = ClientId(self.next_id);
+        self.next_id += 1;
+        self.clients.insert(id, Client {
+            region,
+            z,
+            visible: true,
+            blend: BlendMode::Replace,
+            front: Framebuffer::new(region.w, region.h),
+            back: Framebuffer::new(region.w, region.h),
+            quota: self.default_quota,
+            pending: false,
+            last_shown: None,
+            budget: self.default_quota.max_bytes_per_sec.unwrap_or(0) as f64,
+            budget_updated: Instant::now(),
+            served: 0,
+        });
+        id
+    }
+
+    /// Remove a client and redraw the area it covered
+    pub fn remove_client(&mut self, display: &mut WS1in5, id: ClientId) -> Result<bool, Error> {
+        if self.clients.remove(&id).is_none() {
+            return Ok(false)
+        }
+        self.present(display)?;
+        Ok(true)
+    }
+
+    /// Ids of every client
+    pub fn clients(&self) -> Vec<ClientId> {
+        self.clients.keys().copied().collect()
+    }
+
+    /// The region of the screen a client owns
+    pub fn region(&self, id: ClientId) -> Option<Rect> {
+        self.clients.get(&id).map(|c| c.region)
+    }
+
+    /// Show or hide a client, takes effect on the next present
+    pub fn set_visible(&mut self, id: ClientId, visible: bool) {
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.visible = visible;
+        }
+    }
+
+    /// Set a client's quota
+    pub fn set_quota(&mut self, id: ClientId, quota: Quota) {
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.quota = quota;
+            client.budget = client.budget.min(quota.max_bytes_per_sec.unwrap_or(0) as f64);
+        }
+    }
+
+    /// Returns true if a client has a swap held back by its quota
+    pub fn is_pending(&self, id: ClientId) -> bool {
+        self.clients.get(&id).map(|c| c.pending).unwrap_or(false)
+    }
+
+    /// Set how a client's page is combined with the clients below it, e.g. [`BlendMode::Mix`] for a translucent overlay.
+    /// Clients replace what is below them by default, takes effect on the next present
+    pub fn set_blend(&mut self, id: ClientId, blend: BlendMode) {
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.blend = blend;
+        }
+    }
+
+    /// Change a client's z order, takes effect on the next present
+    pub fn set_z(&mut self, id: ClientId, z: i32) {
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.z = z;
+        }
+    }
+
+    /// The off screen page a client draws into, the size of its region. It starts as a copy of the page on screen
+    pub fn back_page(&mut self, id: ClientId) -> Option<&mut Framebuffer> {
+        self.clients.get_mut(&id).map(|c| &mut c.back)
+    }
+
+    /// The page a client has on screen
+    pub fn front_page(&self, id: ClientId) -> Option<&Framebuffer> {
+        self.clients.get(&id).map(|c| &c.front)
+    }
+
+    /// Named assets available to every client's draw commands
+    pub fn assets(&self) -> &AssetCache {
+        &self.assets
+    }
+
+    /// Named assets available to every client's draw commands
+    pub fn assets_mut(&mut self) -> &mut AssetCache {
+        &mut self.assets
+    }
+
+    /// Draw commands into a client's back page, coords are relative to the client's region. Returns the area of the page drawn to
+    pub fn draw(&mut self, id: ClientId, commands: &[AssetCommand]) -> Result<Option<Rect>, Error> {
+        let client = self.clients.get_mut(&id).ok_or(Error::UnknownClient)?;
+
+        let mut dirty: Option<Rect> = None;
+        for command in commands {
+            if let Some(rect) = self.assets.draw(&mut client.back, command)? {
+                dirty = Some(dirty.map(|d| d.union(&rect)).unwrap_or(rect));
+            }
+        }
+        Ok(dirty)
+    }
+
+    /// Replace a client's back page with a complete page, which must be the size of its region
+    pub fn submit(&mut self, id: ClientId, page: Framebuffer) -> Result<(), Error> {
+        let client = self.clients.get_mut(&id).ok_or(Error::UnknownClient)?;
+        if page.width() != client.region.w || page.height() != client.region.h {
+            return Err(DriverError::OutOfBounds { operation: "submit", rect: client.region, len: page.as_bytes().len() }.into())
+        }
+        client.back = page;
+        Ok(())
+    }
+
+    /// Swap a client's back page onto the screen. Returns false if the client's quota held the swap back, it will be shown by a later [`Compositor::pump`]
+    pub fn swap(&mut self, display: &mut WS1in5, id: ClientId) -> Result<bool, Error> {
+        self.swap_all(display, &[id])
+    }
+
+    /// Swap several clients' back pages onto the screen in a single update. Returns false if any swap was held back by a quota
+    pub fn swap_all(&mut self, display: &mut WS1in5, ids: &[ClientId]) -> Result<bool, Error> {
+        for id in ids {
+            self.clients.get_mut(id).ok_or(Error::UnknownClient)?.pending = true;
+        }
+
+        self.pump(display)?;
+        Ok(ids.iter().all(|id| !self.is_pending(*id)))
+    }
+
+    /// Show every held back swap the quotas now allow, in a single update. Returns the area written
+    pub fn pump(&mut self, display: &mut WS1in5) -> Result<Option<Rect>, Error> {
+        let now = Instant::now();
+        let mut ready: Vec<(u64, ClientId)> = vec![];
+        for (id, client) in self.clients.iter_mut() {
+            client.refill(now);
+            if client.pending && client.wait(now).is_zero() {
+                ready.push((client.served, *id));
+            }
+        }
+        if ready.is_empty() {
+            return Ok(None)
+        }
+
+        // Least recently served first
+        ready.sort();
+        let mut bytes = 0;
+        for (i, (_, id)) in ready.into_iter().enumerate() {
+            let client = self.clients.get_mut(&id).expect("Ready client exists");
+            let cost = client.cost();
+            if let Some(limit) = self.bytes_per_present {
+                if i > 0 && bytes + cost > limit {
+                    continue
+                }
+            }
+            bytes += cost;
+
+            self.served += 1;
+            client.front = client.back.clone();
+            client.pending = false;
+            client.last_shown = Some(now);
+            client.served = self.served;
+            if client.quota.max_bytes_per_sec.is_some() {
+                client.budget -= cost as f64;
+            }
+        }
+
+        self.present(display)
+    }
+
+    /// Time until the next held back swap can be shown, if there are any
+    pub fn next_due(&self) -> Option<Duration> {
+        let now = Instant::now();
+        self.clients.values()
+            .filter(|c| c.pending)
+            .map(|c| c.wait(now))
+            .min()
+    }
+
+    /// Composite every visible client's front page into a full screen framebuffer
+    pub fn compose(&self) -> Framebuffer {
+        let mut screen = Framebuffer::new(self.screen.w, self.screen.h);
+        screen.fill(self.background);
+
+        let mut clients: Vec<&Client> = self.clients.values().filter(|c| c.visible).collect();
+        clients.sort_by_key(|c| c.z);
+        for client in clients {
+            screen.blend_framebuffer(client.region.x, client.region.y, &client.front, client.blend);
+        }
+        screen
+    }
+
+    /// Composite every client and write whatever changed to the screen in a single update. Returns the area written
+    pub fn present(&self, display: &mut WS1in5) -> Result<Option<Rect>, Error> {
+        Ok(display.present(self.compose())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::emulator::Emulator;
+
+    fn display() -> WS1in5 {
+        WS1in5::with_interface(Box::new(Emulator::new())).unwrap()
+    }
+
+    /// Change a client's whole back page, so showing it costs the page's size
+    fn redraw(compositor: &mut Compositor, id: ClientId, level: u8) {
+        compositor.back_page(id).unwrap().fill(level);
+    }
+
+    #[test]
+    fn swaps_over_the_present_budget_wait_for_the_next_present() {
+        let mut display = display();
+        let mut compositor = Compositor::new().with_bytes_per_present(8 * 16);
+        let a = compositor.add_client(Rect::new(0, 0, 16, 16), 0);
+        let b = compositor.add_client(Rect::new(16, 0, 16, 16), 0);
+
+        redraw(&mut compositor, a, 3);
+        redraw(&mut compositor, b, 5);
+        assert!(!compositor.swap_all(&mut display, &[a, b]).unwrap());
+        assert!(!compositor.is_pending(a));
+        assert!(compositor.is_pending(b));
+        assert_eq!(compositor.front_page(b).unwrap().get_pixel(0, 0), 0);
+
+        assert!(compositor.pump(&mut display).unwrap().is_some());
+        assert!(!compositor.is_pending(b));
+        assert_eq!(compositor.front_page(b).unwrap().get_pixel(0, 0), 5);
+        assert_eq!(display.framebuffer().get_pixel(16, 0), 5);
+    }
+
+    #[test]
+    fn least_recently_served_client_goes_first() {
+        let mut display = display();
+        let mut compositor = Compositor::new().with_bytes_per_present(8 * 16);
+        let a = compositor.add_client(Rect::new(0, 0, 16, 16), 0);
+        let b = compositor.add_client(Rect::new(16, 0, 16, 16), 0);
+
+        redraw(&mut compositor, a, 3);
+        redraw(&mut compositor, b, 5);
+        compositor.swap_all(&mut display, &[a, b]).unwrap();
+
+        // a was just served, so b's waiting swap goes ahead of a's new one
+        redraw(&mut compositor, a, 7);
+        assert!(!compositor.swap(&mut display, a).unwrap());
+        assert!(!compositor.is_pending(b));
+        assert!(compositor.is_pending(a));
+
+        compositor.pump(&mut display).unwrap();
+        assert!(!compositor.is_pending(a));
+        assert_eq!(compositor.front_page(a).unwrap().get_pixel(0, 0), 7);
+    }
+
+    #[test]
+    fn swaps_over_the_bandwidth_quota_are_held_back() {
+        let mut display = display();
+        let mut compositor = Compositor::new();
+        let id = compositor.add_client(Rect::new(0, 0, 128, 128), 0);
+        compositor.set_quota(id, Quota { max_fps: None, max_bytes_per_sec: Some(40_000) });
+
+        redraw(&mut compositor, id, 3);
+        assert!(compositor.swap(&mut display, id).unwrap());
+
+        // The first page spent 8192 bytes of a budget which started empty
+        redraw(&mut compositor, id, 5);
+        assert!(!compositor.swap(&mut display, id).unwrap());
+        assert_eq!(display.framebuffer().get_pixel(0, 0), 3);
+        let due = compositor.next_due().unwrap();
+        assert!(due > Duration::ZERO && due <= Duration::from_secs_f64(8192.0 / 40_000.0));
+
+        thread::sleep(due);
+        compositor.pump(&mut display).unwrap();
+        assert!(!compositor.is_pending(id));
+        assert_eq!(display.framebuffer().get_pixel(0, 0), 5);
+    }
+}
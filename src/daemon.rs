@@ -0,0 +1,200 @@
+//! A line based text protocol over a Unix socket, so shell scripts and other languages can drive the screen
+//!
+//! Each line is a command, answered with `ok` or `error <message>`:
+//!
+//! - `clear`
+//! - `text <x> <y> "<text>" [size]`, size defaults to 12
+//! - `image <path> [<x> <y>]`, letterboxed to the screen without a position
+//! - `fill <x> <y> <width> <height> <level>`
+//! - `suspend`, `resume`
+//! - `ping`
+//!
+//! Arguments are separated by spaces, double quotes group words and `\"` and `\\` escape inside them.
+
+use std::{fs, io::{BufRead, BufReader, ErrorKind, Write}, os::unix::net::{UnixListener, UnixStream}, path::{Path, PathBuf}};
+
+use rusttype::{Font, Scale};
+
+use crate::{WS1in5, Error, DriverError, Rect, Fit, images};
+
+#[derive(Debug, Clone, PartialEq)]
+/// A command in the text protocol
+pub enum Command {
+    /// Clear the screen
+    Clear,
+    /// Draw text at a coord with a font size
+    Text { x: usize, y: usize, text: String, size: f32 },
+    /// Show an image file, letterboxed to the screen or at a coord
+    Image { path: PathBuf, at: Option<(usize, usize)> },
+    /// Fill an area with a level (0-15)
+    Fill { rect: Rect, level: u8 },
+    /// Switch the panel off
+    Suspend,
+    /// Switch the panel back on and restore what was shown
+    Resume,
+    /// Do nothing, to check the daemon is running
+    Ping,
+}
+
+impl Command {
+    /// Parse a line of the protocol
+    pub fn parse(line: &str) -> Result<Command, Error> {
+        let args = split(line)?;
+        let (name, args) = match args.split_first() {
+            Some((name, args)) => (name.as_str(), args),
+            None => return Err(Error::Protocol("Empty command".to_string())),
+        };
+
+        let command = match (name, args.len()) {
+            ("clear", 0) => Command::Clear,
+            ("text", 3 | 4) => Command::Text {
+                x: number(&args[0])?,
+                y: number(&args[1])?,
+                text: args[2].clone(),
+                size: match args.get(3) {
+                    Some(size) => size.parse().map_err(|_| Error::Protocol(format!("Invalid size {}", size)))?,
+                    None => 12.0,
+                },
+            },
+            ("image", 1) => Command::Image { path: PathBuf::from(&args[0]), at: None },
+            ("image", 3) => Command::Image { path: PathBuf::from(&args[0]), at: Some((number(&args[1])?, number(&args[2])?)) },
+            ("fill", 5) => Command::Fill {
+                rect: Rect::new(number(&args[0])?, number(&args[1])?, number(&args[2])?, number(&args[3])?),
+                level: number(&args[4])?.min(15) as u8,
+            },
+            ("suspend", 0) => Command::Suspend,
+            ("resume", 0) => Command::Resume,
+            ("ping", 0) => Command::Ping,
+            ("clear" | "text" | "image" | "fill" | "suspend" | "resume" | "ping", _) => {
+                return Err(Error::Protocol(format!("Wrong number of arguments for {}", name)))
+            },
+            _ => return Err(Error::Protocol(format!("Unknown command {}", name))),
+        };
+        Ok(command)
+    }
+
+    /// Run the command on the screen
+    pub fn apply(&self, display: &mut WS1in5, font: &Font) -> Result<(), Error> {
+        match self {
+            Command::Clear => display.clear_all()?,
+            Command::Text { x, y, text, size } => { display.draw_text(*x, *y, text, &Scale::uniform(*size), font)?; },
+            Command::Image { path, at: None } => display.show_image_file(path, Fit::Letterbox)?,
+            Command::Image { path, at: Some((x, y)) } => {
+                let image = images::quantize(&images::load_image(path)?);
+                display.draw_image(*x, *y, &image)?;
+            },
+            Command::Fill { rect, level } => display.fill_rect(*rect, *level)?,
+            Command::Suspend => display.suspend()?,
+            Command::Resume => display.resume()?,
+            Command::Ping => {},
+        }
+        Ok(())
+    }
+}
+
+/// Split a line into arguments, keeping quoted arguments together
+fn split(line: &str) -> Result<Vec<String>, Error> {
+    let mut args = vec![];
+    let mut chars = line.trim().chars().peekable();
+    while let Some(&char) = chars.peek() {
+        if char.is_whitespace() {
+            chars.next();
+            continue
+        }
+
+        let mut arg = String::new();
+        if char == '"' {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some(escaped) => arg.push(escaped),
+                        None => return Err(Error::Protocol("Unterminated escape".to_string())),
+                    },
+                    Some(char) => arg.push(char),
+                    None => return Err(Error::Protocol("Unterminated quote".to_string())),
+                }
+            }
+        } else {
+            while let Some(&char) = chars.peek() {
+                if char.is_whitespace() {
+                    break
+                }
+                arg.push(char);
+                chars.next();
+            }
+        }
+        args.push(arg);
+    }
+    Ok(args)
+}
+
+fn number(arg: &str) -> Result<usize, Error> {
+    arg.parse().map_err(|_| Error::Protocol(format!("Invalid number {}", arg)))
+}
+
+/// A Unix socket server owning the screen, running text protocol commands from each client in turn
+pub struct Daemon<'a> {
+    listener: UnixListener,
+    path: PathBuf,
+    font: Font<'a>,
+}
+
+impl<'a> Daemon<'a> {
+    /// Listen on a socket path, replacing a socket left behind by a previous run. Text is drawn with the font
+    pub fn bind<P: AsRef<Path>>(path: P, font: Font<'a>) -> Result<Daemon<'a>, Error> {
+        let path = path.as_ref().to_path_buf();
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+            _ => {},
+        }
+        let listener = UnixListener::bind(&path).map_err(Error::from)?;
+        Ok(Daemon { listener, path, font })
+    }
+
+    /// The socket path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Serve clients one at a time, forever. A client disconnecting doesn't stop the daemon
+    pub fn serve(&self, display: &mut WS1in5) -> Result<(), Error> {
+        loop {
+            self.serve_one(display)?;
+        }
+    }
+
+    /// Accept a single client and run its commands until it disconnects
+    pub fn serve_one(&self, display: &mut WS1in5) -> Result<(), Error> {
+        let (stream, _) = self.listener.accept().map_err(Error::from)?;
+        match handle_client(stream, display, &self.font) {
+            Err(Error::Driver(DriverError::IO(_))) | Ok(()) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<'a> Drop for Daemon<'a> {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Run commands from a client until it disconnects, answering each line with `ok` or `error <message>`
+pub fn handle_client(stream: UnixStream, display: &mut WS1in5, font: &Font) -> Result<(), Error> {
+    let mut writer = stream.try_clone().map_err(Error::from)?;
+    for line in BufReader::new(stream).lines() {
+        let line = line.map_err(Error::from)?;
+        if line.trim().is_empty() {
+            continue
+        }
+
+        let reply = match Command::parse(&line).and_then(|command| command.apply(display, font)) {
+            Ok(()) => "ok".to_string(),
+            Err(e) => format!("error {}", e),
+        };
+        writeln!(writer, "{}", reply).map_err(Error::from)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+//! Errors
+
+use std::fmt::Display;
+
+pub use ws_1in5_i2c_core::Error as DriverError;
+
+#[derive(Debug)]
+#[non_exhaustive]
+/// Screen Error, from the driver or the toolkit built on it
+pub enum Error {
+    /// Error from the driver, e.g. writing to the bus
+    Driver(DriverError),
+    /// Compositor client doesn't exist
+    UnknownClient,
+    /// Named asset doesn't exist or is the wrong kind
+    UnknownAsset(String),
+    /// Named page isn't registered with the router
+    UnknownPage(String),
+    /// Font data couldn't be parsed
+    InvalidFont,
+    /// Malformed protocol message
+    Protocol(String),
+    /// Error reported by a remote display
+    Remote(String),
+    /// Authentication with a remote display failed
+    Unauthorized,
+    /// The render thread has stopped
+    Stopped,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Driver(e) => f.write_fmt(format_args!("{}", e)),
+            Error::UnknownClient => f.write_str("Unknown compositor client"),
+            Error::UnknownAsset(name) => f.write_fmt(format_args!("Unknown asset {}", name)),
+            Error::UnknownPage(name) => f.write_fmt(format_args!("Unknown page {}", name)),
+            Error::InvalidFont => f.write_str("Invalid font data"),
+            Error::Protocol(e) => f.write_fmt(format_args!("Protocol error: {}", e)),
+            Error::Remote(e) => f.write_fmt(format_args!("Remote error: {}", e)),
+            Error::Unauthorized => f.write_str("Authentication failed"),
+            Error::Stopped => f.write_str("Render thread has stopped"),
+        }
+    }
+}
+
+impl Error {
+    /// Returns true for bus errors, which are worth retrying
+    pub fn is_bus_error(&self) -> bool {
+        matches!(self, Error::Driver(e) if e.is_bus_error())
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Driver(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<DriverError> for Error {
+    fn from(e: DriverError) -> Self {
+        Error::Driver(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Driver(DriverError::IO(e))
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<image::ImageError> for Error {
+    fn from(e: image::ImageError) -> Self {
+        Error::Driver(DriverError::Image(e))
+    }
+}
@@ -0,0 +1,205 @@
+//! The joystick and buttons on Waveshare's OLED HAT, read from the Pi's GPIOs
+
+use std::{collections::VecDeque, thread, time::{Duration, Instant}};
+
+use rppal::gpio::{Gpio, InputPin};
+
+use crate::{Error, DriverError};
+#[cfg(feature = "ui")]
+use crate::ui::{Input, InputEvent};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A key on the HAT, the joystick's five directions and the three buttons
+pub enum HatKey {
+    /// Joystick up
+    Up,
+    /// Joystick down
+    Down,
+    /// Joystick left
+    Left,
+    /// Joystick right
+    Right,
+    /// Pushing the joystick in
+    Press,
+    /// The top button
+    Key1,
+    /// The middle button
+    Key2,
+    /// The bottom button
+    Key3,
+}
+
+impl HatKey {
+    /// Every key, in the order they're polled
+    pub const ALL: [HatKey; 8] = [HatKey::Up, HatKey::Down, HatKey::Left, HatKey::Right, HatKey::Press, HatKey::Key1, HatKey::Key2, HatKey::Key3];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A key changing state
+pub enum HatEvent {
+    /// A key was pushed
+    Pressed(HatKey),
+    /// A key was let go
+    Released(HatKey),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The GPIO pins (BCM numbering) each key is wired to, every key connects its pin to ground
+pub struct HatPins {
+    /// Joystick up
+    pub up: u8,
+    /// Joystick down
+    pub down: u8,
+    /// Joystick left
+    pub left: u8,
+    /// Joystick right
+    pub right: u8,
+    /// Joystick pushed in
+    pub press: u8,
+    /// The top button
+    pub key1: u8,
+    /// The middle button
+    pub key2: u8,
+    /// The bottom button
+    pub key3: u8,
+}
+
+impl Default for HatPins {
+    fn default() -> Self {
+        HatPins::WAVESHARE
+    }
+}
+
+impl HatPins {
+    /// The wiring of Waveshare's OLED HATs, as in their demo code. Check your HAT's schematic and set the pins if it differs
+    pub const WAVESHARE: HatPins = HatPins { up: 6, down: 19, left: 5, right: 26, press: 13, key1: 21, key2: 20, key3: 16 };
+
+    /// The pin a key is wired to
+    pub fn pin(&self, key: HatKey) -> u8 {
+        match key {
+            HatKey::Up => self.up,
+            HatKey::Down => self.down,
+            HatKey::Left => self.left,
+            HatKey::Right => self.right,
+            HatKey::Press => self.press,
+            HatKey::Key1 => self.key1,
+            HatKey::Key2 => self.key2,
+            HatKey::Key3 => self.key3,
+        }
+    }
+}
+
+struct Key {
+    key: HatKey,
+    pin: InputPin,
+    pressed: bool,
+    changed: Instant,
+}
+
+/// The HAT's joystick and buttons, debounced
+///
+/// [`HatInput::poll`] reads them without blocking, [`HatInput::events`] and [`HatInput::run`] poll until there's something to report.
+/// With the `ui` feature it's also an [`Input`] for a [`Ui`](crate::Ui): up and left move to the previous widget, down and right to the next,
+/// the joystick and key 1 select and key 3 goes back.
+pub struct HatInput {
+    keys: Vec<Key>,
+    debounce: Duration,
+    queue: VecDeque<HatEvent>,
+}
+
+impl HatInput {
+    /// Create new for Waveshare's OLED HAT, with the pins' pull ups enabled
+    pub fn new() -> Result<HatInput, Error> {
+        HatInput::with_pins(HatPins::WAVESHARE)
+    }
+
+    /// Create new for keys wired to other pins, with the pins' pull ups enabled
+    pub fn with_pins(pins: HatPins) -> Result<HatInput, Error> {
+        let gpio = Gpio::new().map_err(DriverError::GPIO)?;
+        let mut keys = Vec::with_capacity(HatKey::ALL.len());
+        for key in HatKey::ALL {
+            let pin = gpio.get(pins.pin(key)).map_err(DriverError::GPIO)?.into_input_pullup();
+            keys.push(Key { key, pressed: pin.is_low(), pin, changed: Instant::now() });
+        }
+        Ok(HatInput { keys, debounce: Duration::from_millis(20), queue: VecDeque::new() })
+    }
+
+    /// Ignore changes within a time of the last one, from the contacts bouncing
+    pub fn with_debounce(mut self, debounce: Duration) -> HatInput {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Returns true if a key is held down
+    pub fn is_pressed(&self, key: HatKey) -> bool {
+        self.keys.iter().any(|state| state.key == key && state.pressed)
+    }
+
+    /// Add any key presses and releases since the last poll to the events, without blocking
+    pub fn poll(&mut self, events: &mut Vec<HatEvent>) {
+        events.extend(self.queue.drain(..));
+        for state in self.keys.iter_mut() {
+            let pressed = state.pin.is_low();
+            if pressed != state.pressed && state.changed.elapsed() >= self.debounce {
+                state.pressed = pressed;
+                state.changed = Instant::now();
+                events.push(if pressed { HatEvent::Pressed(state.key) } else { HatEvent::Released(state.key) });
+            }
+        }
+    }
+
+    /// Wait for key presses and releases, polling at an interval (e.g. 10ms). The iterator never ends
+    pub fn events(&mut self, poll_interval: Duration) -> HatEvents<'_> {
+        HatEvents { input: self, poll_interval }
+    }
+
+    /// Call a function with every key press and release, polling at an interval, until it returns false
+    pub fn run<F: FnMut(HatEvent) -> bool>(&mut self, poll_interval: Duration, mut callback: F) {
+        for event in self.events(poll_interval) {
+            if !callback(event) {
+                break
+            }
+        }
+    }
+}
+
+/// Key presses and releases from [`HatInput::events`]
+pub struct HatEvents<'a> {
+    input: &'a mut HatInput,
+    poll_interval: Duration,
+}
+
+impl Iterator for HatEvents<'_> {
+    type Item = HatEvent;
+
+    fn next(&mut self) -> Option<HatEvent> {
+        let mut events = vec![];
+        loop {
+            if let Some(event) = self.input.queue.pop_front() {
+                return Some(event)
+            }
+            self.input.poll(&mut events);
+            // Events past the first wait for the next call
+            self.input.queue.extend(events.drain(..));
+            if self.input.queue.is_empty() {
+                thread::sleep(self.poll_interval);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "ui")]
+impl Input for HatInput {
+    fn poll(&mut self, events: &mut Vec<InputEvent>) -> Result<(), Error> {
+        let mut hat_events = vec![];
+        HatInput::poll(self, &mut hat_events);
+        events.extend(hat_events.into_iter().filter_map(|event| match event {
+            HatEvent::Pressed(HatKey::Up | HatKey::Left) => Some(InputEvent::Prev),
+            HatEvent::Pressed(HatKey::Down | HatKey::Right) => Some(InputEvent::Next),
+            HatEvent::Pressed(HatKey::Press | HatKey::Key1) => Some(InputEvent::Select),
+            HatEvent::Pressed(HatKey::Key3) => Some(InputEvent::Back),
+            _ => None,
+        }));
+        Ok(())
+    }
+}
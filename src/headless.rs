@@ -0,0 +1,92 @@
+//! Simulator which saves frames to PNG files, for testing rendering without the hardware
+
+use std::{fs, path::{Path, PathBuf}};
+
+use image::imageops::{self, FilterType};
+
+use crate::{Error, DriverError, Geometry, Interface, emulator::Emulator, images};
+
+/// An interface which saves what the screen would show after every write as a numbered PNG (`frame_00000.png`, `frame_00001.png`, ...)
+///
+/// A write is saved once its window has been filled, however many chunks the data was sent in.
+///
+/// ```no_run
+/// use ws_1in5_i2c::{WS1in5, headless::HeadlessSimulator};
+///
+/// let mut screen = WS1in5::with_interface(Box::new(HeadlessSimulator::new("frames").unwrap())).unwrap();
+/// screen.clear_all().unwrap();
+/// ```
+pub struct HeadlessSimulator {
+    emulator: Emulator,
+    directory: PathBuf,
+    scale: u32,
+    index: usize,
+}
+
+impl HeadlessSimulator {
+    /// Create new, saving frames into a directory (created if it doesn't exist)
+    pub fn new<P: AsRef<Path>>(directory: P) -> Result<HeadlessSimulator, Error> {
+        fs::create_dir_all(&directory).map_err(Error::from)?;
+        Ok(HeadlessSimulator { emulator: Emulator::new(), directory: directory.as_ref().to_path_buf(), scale: 1, index: 0 })
+    }
+
+    /// Emulate a panel of another size
+    pub fn with_geometry(mut self, geometry: Geometry) -> HeadlessSimulator {
+        self.emulator = Emulator::with_geometry(geometry);
+        self
+    }
+
+    /// Save each screen pixel as a square of scale by scale pixels
+    pub fn with_scale(mut self, scale: u32) -> HeadlessSimulator {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// The emulated controller
+    pub fn emulator(&self) -> &Emulator {
+        &self.emulator
+    }
+
+    /// Number of frames saved
+    pub fn frame_count(&self) -> usize {
+        self.index
+    }
+
+    /// Path of a saved frame
+    pub fn frame_path(&self, index: usize) -> PathBuf {
+        self.directory.join(format!("frame_{:05}.png", index))
+    }
+
+    fn capture(&mut self) -> Result<(), DriverError> {
+        let mut image = images::expand(&self.emulator.frame().to_image());
+        if self.scale > 1 {
+            image = imageops::resize(&image, image.width() * self.scale, image.height() * self.scale, FilterType::Nearest);
+        }
+
+        image.save(self.frame_path(self.index)).map_err(DriverError::Image)?;
+        self.index += 1;
+        Ok(())
+    }
+}
+
+impl Interface for HeadlessSimulator {
+    fn command(&mut self, cmd: u8) -> Result<(), DriverError> {
+        self.emulator.command(cmd)
+    }
+
+    fn data(&mut self, data: &[u8]) -> Result<(), DriverError> {
+        self.emulator.data(data)?;
+        if data.is_empty() || !self.emulator.at_window_start() {
+            return Ok(())
+        }
+        self.capture()
+    }
+
+    fn status(&mut self) -> Result<Option<u8>, DriverError> {
+        self.emulator.status()
+    }
+
+    fn reset(&mut self) {
+        self.emulator.reset();
+    }
+}
@@ -0,0 +1,157 @@
+//! Image loading and conversion
+
+use std::path::Path;
+
+use image::{GrayImage, DynamicImage, imageops::{self, FilterType}};
+
+use crate::{Error, DriverError, Rect};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// How an image is scaled to fit an area
+pub enum Fit {
+    /// Scale to exactly the area, ignoring the aspect ratio
+    Stretch,
+    /// Scale to fit inside the area keeping the aspect ratio, centered with black bars
+    #[default]
+    Letterbox,
+    /// Scale to cover the area keeping the aspect ratio, centered and cropped
+    Crop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// How photos are prepared for the screen
+pub struct ImageOptions {
+    /// How the image is scaled to the screen
+    pub fit: Fit,
+    /// Stretch the levels so the darkest pixels are black and the brightest white before quantizing, photos mapped straight to 16 levels come out murky
+    pub auto_contrast: bool,
+    /// Equalize the histogram instead of stretching it, spreading the pixels evenly over the levels
+    pub equalize: bool,
+}
+
+/// Load an image file (PNG, JPEG, BMP, ...) as a greyscale image
+pub fn load_image<P: AsRef<Path>>(path: P) -> Result<GrayImage, Error> {
+    Ok(image::open(path).map_err(Error::from)?.to_luma8())
+}
+
+/// Scale a greyscale image to a size
+pub fn fit_image(image: &GrayImage, width: u32, height: u32, fit: Fit) -> GrayImage {
+    if image.width() == 0 || image.height() == 0 {
+        return GrayImage::new(width, height)
+    }
+
+    match fit {
+        Fit::Stretch => imageops::resize(image, width, height, FilterType::Triangle),
+        Fit::Letterbox => {
+            let scale = f32::min(width as f32 / image.width() as f32, height as f32 / image.height() as f32);
+            let w = ((image.width() as f32 * scale).round() as u32).clamp(1, width);
+            let h = ((image.height() as f32 * scale).round() as u32).clamp(1, height);
+            let scaled = imageops::resize(image, w, h, FilterType::Triangle);
+
+            let mut canvas = GrayImage::new(width, height);
+            imageops::replace(&mut canvas, &scaled, ((width - w) / 2) as i64, ((height - h) / 2) as i64);
+            canvas
+        },
+        Fit::Crop => {
+            let scaled = DynamicImage::ImageLuma8(image.clone()).resize_to_fill(width, height, FilterType::Triangle);
+            scaled.to_luma8()
+        },
+    }
+}
+
+/// Stretch an 8 bit greyscale image's levels to the full range, ignoring the darkest and brightest 1% of pixels so a few outliers don't hold it back
+pub fn stretch_contrast(image: &GrayImage) -> GrayImage {
+    let histogram = histogram(image);
+    let total = image.pixels().len() as u64;
+    let cutoff = total / 100;
+
+    let low = level_past(histogram.iter(), cutoff);
+    let high = 255 - level_past(histogram.iter().rev(), cutoff);
+    if high <= low {
+        return image.clone()
+    }
+
+    let mut image = image.clone();
+    let (low, range) = (low as u32, (high - low) as u32);
+    image.pixels_mut().for_each(|p| p.0[0] = ((p.0[0] as u32).saturating_sub(low) * 255 / range).min(255) as u8);
+    image
+}
+
+/// Equalize an 8 bit greyscale image's histogram, spreading its pixels evenly over the levels
+pub fn equalize(image: &GrayImage) -> GrayImage {
+    let histogram = histogram(image);
+    let total = image.pixels().len() as u64;
+    if total == 0 {
+        return image.clone()
+    }
+
+    let mut lookup = [0u8; 256];
+    let mut sum = 0;
+    for (level, count) in histogram.iter().enumerate() {
+        sum += count;
+        lookup[level] = (sum * 255 / total) as u8;
+    }
+
+    let mut image = image.clone();
+    image.pixels_mut().for_each(|p| p.0[0] = lookup[p.0[0] as usize]);
+    image
+}
+
+/// Scale, adjust and quantize an 8 bit greyscale image for the screen (pixel values 0-15)
+pub fn prepare_image(image: &GrayImage, width: u32, height: u32, options: &ImageOptions) -> GrayImage {
+    let image = fit_image(image, width, height, options.fit);
+    let image = if options.equalize {
+        equalize(&image)
+    } else if options.auto_contrast {
+        stretch_contrast(&image)
+    } else {
+        image
+    };
+    quantize(&image)
+}
+
+/// The first level where the running count passes the cutoff
+fn level_past<'a>(counts: impl Iterator<Item = &'a u64>, cutoff: u64) -> usize {
+    let mut sum = 0;
+    for (level, count) in counts.enumerate() {
+        sum += count;
+        if sum > cutoff {
+            return level
+        }
+    }
+    0
+}
+
+fn histogram(image: &GrayImage) -> [u64; 256] {
+    let mut histogram = [0u64; 256];
+    image.pixels().for_each(|p| histogram[p.0[0] as usize] += 1);
+    histogram
+}
+
+/// Quantize an 8 bit greyscale image to 4 bit (pixel values 0-15)
+pub fn quantize(image: &GrayImage) -> GrayImage {
+    let mut image = image.clone();
+    image.pixels_mut().for_each(|p| p.0[0] >>= 4);
+    image
+}
+
+/// Expand a 4 bit greyscale image (pixel values 0-15) to the full 8 bit range
+pub fn expand(image: &GrayImage) -> GrayImage {
+    let mut image = image.clone();
+    image.pixels_mut().for_each(|p| p.0[0] = p.0[0].min(15) * 17);
+    image
+}
+
+pub use crate::pack::{pack_pixels, unpack_pixels};
+
+/// Pack a greyscale image (pixel values 0-15) two pixels per byte, in the screen's layout. An odd last column is dropped
+pub fn pack(image: &GrayImage) -> Vec<u8> {
+    pack_pixels(image.as_raw(), image.width() as usize, image.height() as usize)
+}
+
+/// Unpack data in the screen's layout to a greyscale image (pixel values 0-15), the inverse of [`pack`]
+pub fn unpack_buffer(buffer: &[u8], width: usize, height: usize) -> Result<GrayImage, Error> {
+    let pixels = unpack_pixels(buffer, width, height)
+        .ok_or(DriverError::OutOfBounds { operation: "unpack_buffer", rect: Rect::new(0, 0, width, height), len: buffer.len() })?;
+    Ok(GrayImage::from_raw(width as u32, height as u32, pixels).expect("Unpacked one value per pixel"))
+}
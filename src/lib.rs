@@ -1,317 +1,438 @@
 #![doc = include_str!("../README.md")]
 
-use std::{fmt::{Display}, thread, time::Duration};
-
-use image::{buffer::{EnumeratePixels}, Luma, GrayImage, DynamicImage, ImageBuffer};
-use imageproc::drawing;
-use rppal::{gpio::{Gpio, OutputPin, self}, i2c::{I2c, self}};
-use rusttype::{Scale, Font, point};
-
-#[derive(Debug)]
-/// Screen Error
-pub enum Error {
-    /// GPIO error
-    GPIO(gpio::Error),
-    /// i2c error
-    I2C(i2c::Error),
-    /// Out of bounds error
-    OutOfBounds,
+use std::ops::{Deref, DerefMut};
+#[cfg(feature = "image")]
+use std::path::Path;
+
+#[cfg(feature = "image")]
+use image::{GrayImage, imageops};
+use rppal::{gpio::OutputPin, i2c::I2c};
+#[cfg(feature = "text")]
+use rusttype::{Scale, Font};
+
+pub use ws_1in5_i2c_core::{alloc_check, clip, commands, damage, dirty, driver, emulator, fixed, framebuffer, geometry, gray, interface, lock, pack, power, remap, screensaver, scroll, state, stats, swap, sync};
+#[cfg(feature = "ssd1351")]
+pub use ws_1in5_i2c_core::ssd1351;
+
+pub mod animation;
+pub mod assets;
+pub mod batch;
+#[cfg(feature = "image")]
+pub mod bridge;
+#[cfg(feature = "compat")]
+pub mod compat;
+pub mod compositor;
+#[cfg(feature = "daemon")]
+pub mod daemon;
+pub mod error;
+#[cfg(feature = "hat_input")]
+pub mod hat_input;
+#[cfg(feature = "image")]
+pub mod headless;
+#[cfg(feature = "image")]
+pub mod images;
+#[cfg(feature = "image")]
+pub mod nav;
+#[cfg(feature = "text")]
+pub mod notify;
+pub mod pipeline;
+#[cfg(feature = "text")]
+pub mod progress;
+pub mod protocol;
+pub mod remote;
+pub mod render_thread;
+pub mod router;
+#[cfg(feature = "scene")]
+pub mod scene;
+#[cfg(feature = "image")]
+pub mod segments;
+pub mod shared;
+#[cfg(feature = "simulator")]
+pub mod simulator;
+#[cfg(feature = "text")]
+pub mod text;
+pub mod theme;
+pub mod transition;
+#[cfg(feature = "ui")]
+pub mod ui;
+#[cfg(feature = "text")]
+pub mod widgets;
+
+pub use ws_1in5_i2c_core::{
+    Driver, Controller, CommandBatch, Damage, DirtyRegion, Framebuffer, FixedFramebuffer, PackedBuffer, Gray4, BlendMode, Rect, Geometry, Anchor, Orientation, Interface, I2cInterface, SpiInterface, SharedBus, SharedI2cInterface, STATUS_DISPLAY_OFF,
+    PowerConfig, Regulator, Remap, RetryPolicy, TransferConfig, FrameSync, SwapAction, SwapInfo, Stats, FlushStats, Screensaver, BurnInProtection, ScrollDirection, ScrollSpeed, OLED_WIDTH, OLED_HEIGHT,
+};
+#[cfg(feature = "ssd1351")]
+pub use ws_1in5_i2c_core::{Palette, rgb565};
+
+pub use animation::{Animator, AnimationStats};
+pub use assets::{AssetCache, Asset, AssetCommand};
+pub use batch::DrawOp;
+pub use compositor::{Compositor, ClientId, Quota};
+pub use error::{Error, DriverError};
+#[cfg(feature = "hat_input")]
+pub use hat_input::{HatInput, HatKey, HatEvent, HatEvents, HatPins};
+#[cfg(feature = "image")]
+pub use images::{Fit, ImageOptions};
+pub use pipeline::FramePipeline;
+pub use remote::{RemoteServer, RemoteDisplay};
+pub use render_thread::{RenderThread, RenderSender, RenderCommand};
+pub use router::{Router, Page};
+#[cfg(feature = "scene")]
+pub use scene::{Scene, SceneData};
+#[cfg(feature = "image")]
+pub use segments::{SegmentDisplay, SegmentStyle, Segments};
+pub use shared::SharedWS1in5;
+#[cfg(feature = "text")]
+pub use text::{TextStyle, Shadow, GlyphCache, TextPage};
+pub use theme::{Theme, FocusStyle};
+pub use transition::Transition;
+#[cfg(feature = "ui")]
+pub use ui::{Ui, UiEvent, Input, InputEvent, GpioButton, RotaryEncoder};
+
+/// The screen, with text and image drawing on top of the [`Driver`] core
+///
+/// Everything the driver does is available directly through deref.
+pub struct WS1in5 {
+    driver: Driver,
 }
 
-impl Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Error::GPIO(e) => f.write_fmt(format_args!("{}", e)),
-            Error::I2C(e) => f.write_fmt(format_args!("{}", e)),
-            Error::OutOfBounds => f.write_str("Buffer index out of bounds"),
-        }
+impl Deref for WS1in5 {
+    type Target = Driver;
+
+    fn deref(&self) -> &Driver {
+        &self.driver
     }
 }
 
+impl DerefMut for WS1in5 {
+    fn deref_mut(&mut self) -> &mut Driver {
+        &mut self.driver
+    }
+}
 
-/// Screen height
-pub const OLED_WIDTH: usize = 128;
-/// Screen height
-pub const OLED_HEIGHT: usize = 128; 
-
-pub struct WS1in5 {
-    reset_pin: OutputPin,
-    i2c_bus: I2c,
-
-    cleared: bool,
+impl From<Driver> for WS1in5 {
+    fn from(driver: Driver) -> WS1in5 {
+        WS1in5 { driver }
+    }
 }
 
 impl WS1in5 {
     /// Create new
     pub fn new(address: u16, bus: u8, reset: u8) -> Result<WS1in5, Error> {
-        let gpio = Gpio::new().map_err(|e| Error::GPIO(e))?;
-        let mut reset_pin = gpio.get(reset).map_err(|e| Error::GPIO(e))?.into_output();
-        reset_pin.set_low();
-
-        let mut i2c_bus = I2c::with_bus(bus).map_err(|e| Error::I2C(e))?;
-        i2c_bus.set_slave_address(address).map_err(|e| Error::I2C(e))?;
-
-        let mut this = WS1in5 { reset_pin, i2c_bus, cleared: true };
-        this.init()?;
-
-        Ok(this)
+        WS1in5::with_interface(Box::new(I2cInterface::new(address, bus, reset)?))
     }
 
-    fn command(&self, cmd: u8) -> Result<(), Error> {
-        self.i2c_bus.smbus_write_byte(0x00, cmd).map_err(|e| Error::I2C(e))
+    /// Create new without a reset pin, for boards where reset is tied high or handled by another controller
+    pub fn new_without_reset(address: u16, bus: u8) -> Result<WS1in5, Error> {
+        WS1in5::with_interface(Box::new(I2cInterface::without_reset(address, bus)?))
     }
 
-    fn init(&mut self) -> Result<(), Error> {
-        self.reset();
-
-        self.command(0xae)?;
-
-        self.command(0x15)?;
-        self.command(0x00)?;
-        self.command(0x7f)?;
-
-        self.command(0x75)?;
-        self.command(0x00)?;
-        self.command(0x7f)?;
-
-        self.command(0x81)?;
-        self.command(0x80)?;
-
-        self.command(0xa0)?;
-        self.command(0x51)?;
-
-        self.command(0xa1)?;
-        self.command(0x00)?;
+    /// Find the screen on an i2c bus at either of its addresses (see [`I2cInterface::probe`]) and create new for it, without a reset pin
+    pub fn probe(bus: u8) -> Result<WS1in5, Error> {
+        WS1in5::new_without_reset(I2cInterface::probe(bus)?, bus)
+    }
 
-        self.command(0xa2)?;
-        self.command(0x00)?;
+    /// Create new from an i2c bus and reset pin the application has already set up (e.g. with a custom clock speed), see [`I2cInterface::from_parts`]
+    pub fn from_parts(i2c: I2c, reset: Option<OutputPin>) -> Result<WS1in5, Error> {
+        WS1in5::with_interface(Box::new(I2cInterface::from_parts(i2c, reset)))
+    }
 
-        self.command(0xa4)?;
-        self.command(0xa8)?;
-        self.command(0x7f)?;
+    /// Create new for the screen at an address on a bus shared with other screens or devices
+    pub fn on_shared_bus(bus: &SharedBus, address: u16, reset: Option<u8>) -> Result<WS1in5, Error> {
+        WS1in5::with_interface(Box::new(SharedI2cInterface::new(bus, address, reset)?))
+    }
 
-        self.command(0xb1)?;
-        self.command(0xf1)?;
+    /// Create new, connected through any interface (e.g. a simulator)
+    pub fn with_interface(interface: Box<dyn Interface>) -> Result<WS1in5, Error> {
+        Ok(WS1in5 { driver: Driver::new(interface)? })
+    }
 
-        self.command(0xb3)?;
-        self.command(0x00)?;
- 
-        self.command(0xab)?;
-        self.command(0x01)?;
+    /// Create new for a panel driven by another controller (e.g. the RGB OLED's SSD1351), connected through any interface
+    pub fn with_controller(interface: Box<dyn Interface>, controller: Controller, geometry: Geometry) -> Result<WS1in5, Error> {
+        Ok(WS1in5 { driver: Driver::with_controller(interface, controller, geometry)? })
+    }
 
-        self.command(0xb6)?;
-        self.command(0x0f)?;
+    /// Create new for another SSD1327 panel size (e.g. [`Geometry::SIZE_128X96`]), connected through any interface
+    pub fn with_geometry(interface: Box<dyn Interface>, geometry: Geometry) -> Result<WS1in5, Error> {
+        Ok(WS1in5 { driver: Driver::with_geometry(interface, geometry)? })
+    }
 
-        self.command(0xbe)?;
-        self.command(0x0f)?;
+    /// Check the panel is on or off after switching it, returning [`DriverError::Desync`] if it isn't. Ignored when the interface can't read status
+    pub fn with_verification(self, verify: bool) -> WS1in5 {
+        WS1in5 { driver: self.driver.with_verification(verify) }
+    }
 
-        self.command(0xbc)?;
-        self.command(0x08)?;
+    /// Re-initialise the screen and restore the framebuffer after a number of i2c errors in a row, see [`Driver::with_recovery`]
+    pub fn with_recovery(self, max_failures: u32) -> WS1in5 {
+        WS1in5 { driver: self.driver.with_recovery(max_failures) }
+    }
 
-        self.command(0xd5)?;
-        self.command(0x62)?;
+    /// Retry failed command and data writes, see [`Driver::with_retry_policy`]
+    pub fn with_retry_policy(self, retry: RetryPolicy) -> WS1in5 {
+        WS1in5 { driver: self.driver.with_retry_policy(retry) }
+    }
 
-        self.command(0xfd)?;
-        self.command(0x12)?;
+    /// Split display data into chunks, see [`Driver::with_transfer_config`]
+    pub fn with_transfer_config(self, transfer: TransferConfig) -> WS1in5 {
+        WS1in5 { driver: self.driver.with_transfer_config(transfer) }
+    }
 
-        thread::sleep(Duration::from_millis(100));
-        self.command(0xAF)?;
-        Ok(())
+    /// Dim the screen once nothing has been drawn for a while, see [`Driver::with_screensaver`]
+    pub fn with_screensaver(self, screensaver: Screensaver) -> WS1in5 {
+        WS1in5 { driver: self.driver.with_screensaver(screensaver) }
     }
 
-    pub fn reset(&mut self) {
-        self.reset_pin.set_high();
-        thread::sleep(Duration::from_millis(100));
-        self.reset_pin.set_low();
-        thread::sleep(Duration::from_millis(100));
-        self.reset_pin.set_high();
-        thread::sleep(Duration::from_millis(100));
-        
-        self.cleared = true;
+    /// Count bytes, transactions and time spent writing to the screen, see [`Driver::with_stats`]
+    pub fn with_stats(self, enabled: bool) -> WS1in5 {
+        WS1in5 { driver: self.driver.with_stats(enabled) }
     }
 
-    fn set_windows(&self, xstart: u8, ystart: u8, xend: u8, yend: u8) -> Result<(), Error>{
-        if (xstart > OLED_WIDTH as u8) || (ystart > OLED_HEIGHT as u8) || (xend > OLED_WIDTH as u8) || (yend > OLED_HEIGHT as u8) {
-            return Ok(())
-        }
+    /// Count statistics and call a function after every write to the screen, see [`Driver::with_stats_callback`]
+    pub fn with_stats_callback<F: FnMut(&FlushStats) + Send + 'static>(self, callback: F) -> WS1in5 {
+        WS1in5 { driver: self.driver.with_stats_callback(callback) }
+    }
 
-        self.command(0x15)?;
-        self.command(xstart/2)?;
-        self.command(xend/2 - 1)?;
+    /// Clip windows and draws which run past the edge of the screen rather than returning an error, see [`Driver::set_clipping`]
+    pub fn with_clipping(self, clip: bool) -> WS1in5 {
+        WS1in5 { driver: self.driver.with_clipping(clip) }
+    }
 
-        self.command(0x75)?;
-        self.command(ystart)?;
-        self.command(yend - 1)?;
+    /// Call a function before each swap of the back buffer, which can present, defer or cancel the frame, see [`Driver::with_swap_callback`]
+    pub fn with_swap_callback<F: FnMut(&SwapInfo) -> SwapAction + Send + 'static>(self, callback: F) -> WS1in5 {
+        WS1in5 { driver: self.driver.with_swap_callback(callback) }
+    }
 
-        Ok(())
+    /// Assert that [`Driver::present_from`] doesn't allocate in debug builds, needs [`alloc_check::CountingAllocator`] installed
+    pub fn with_allocation_check(self, check: bool) -> WS1in5 {
+        WS1in5 { driver: self.driver.with_allocation_check(check) }
     }
 
-    /// Returns true if the screen been cleared since it was last written too
-    pub fn has_cleared(&self) -> bool {
-        self.cleared
+    /// The driver core
+    pub fn driver(&self) -> &Driver {
+        &self.driver
     }
 
-    /// Clear a section of the screen
-    pub fn clear(&mut self, x: usize, y: usize, width: usize, height: usize) -> Result<(), Error> {
-        self.cleared = true;
+    /// The driver core
+    pub fn driver_mut(&mut self) -> &mut Driver {
+        &mut self.driver
+    }
 
-        let buffer: Vec<u8> = vec![0x00; (width  /2) * height];
-        self.show_image(buffer, x, y, width, height)
+    /// Take the driver core, dropping the text and image layer
+    pub fn into_driver(self) -> Driver {
+        self.driver
     }
 
-    /// Clear the whole screen
-    pub fn clear_all(&mut self) -> Result<(), Error> {
-        self.clear(0, 0, OLED_WIDTH, OLED_HEIGHT)
+    #[cfg(feature = "image")]
+    /// Capture what is on the screen as an 8 bit greyscale image
+    pub fn screenshot(&self) -> GrayImage {
+        images::expand(&self.framebuffer().to_image())
     }
 
-    /// Convert image to buffer data
-    pub fn get_buffer(&self, pixels: EnumeratePixels<Luma<u8>>, width: usize, height: usize) -> Result<Vec<u8>, Error> {
-        let mut buf: Vec<u8> = vec![0xff; (width/2) * height];
-        
-        if pixels.len() != height * width {
-            return Err(Error::OutOfBounds)
-        }
-        
-        for (x, y, pixel) in pixels {
-            let (x, y, pixel) = (x as usize, y as usize, pixel.0[0]);
-
-            let addr = x/2 + y*(width/2);
-            let color = pixel % 16;
-            let data: u8 = buf[addr] & ((!0xf0u8).rotate_right((x as u32 % 2) *4));
-            buf[addr] &= data | ((color<<4) >> ((x%2)*4));
-        }
-        Ok(buf)   
+    #[cfg(feature = "image")]
+    /// Save what is on the screen to an image file, the format is chosen from the extension
+    pub fn save_screenshot<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.screenshot().save(path).map_err(Error::from)
     }
 
-    /// Show an image of a certain size on the screen at the specified coord
-    pub fn show_image(&mut self, buffer: Vec<u8>, x: usize, y: usize, width: usize, height: usize) -> Result<(), Error> {
-        self.set_windows(x as u8, y as u8, x as u8 + width as u8, y as u8 + height as u8)?;
-        if buffer.len() < (width /2) * height {
-            return Err(Error::OutOfBounds)
-        }
+    #[cfg(feature = "text")]
+    /// Draw text to the screen at the specified coord (ignores new lines). Returns the area drawn
+    pub fn draw_text(&mut self, x: usize, y: usize, text: &str, scale: &Scale, font: &Font) -> Result<Rect, Error> {
+        self.draw_image_area(x, y, &text::text_image(text, scale, font, 15))
+    }
 
-        self.cleared = false;
+    #[cfg(feature = "text")]
+    /// Draw text at a whole multiple of a glyph cache's size (e.g. 2 or 3 for headlines), repeating the cached glyphs' pixels rather than rasterising
+    /// big glyphs. Returns the area drawn
+    pub fn draw_text_scaled(&mut self, x: usize, y: usize, text: &str, glyphs: &mut GlyphCache, factor: usize) -> Result<Rect, Error> {
+        self.draw_image_area(x, y, &glyphs.text_image(text, factor, 15))
+    }
 
-        for i in 0..height {
-            for j in 0..(width/2) {
-                self.i2c_bus.smbus_write_byte(0x40, buffer[j + width / 2 * i])
-                    .map_err(|e| Error::I2C(e))?;
-            }
-        }
-            
-        Ok(())
+    #[cfg(feature = "image")]
+    /// Draw a greyscale image (pixel values 0-15) to the screen at the specified coord
+    pub fn draw_image(&mut self, x: usize, y: usize, image: &GrayImage) -> Result<(usize, usize), Error> {
+        self.draw_image_area(x, y, image)?;
+        Ok((x + image.width() as usize, y + image.height() as usize))
     }
 
-    pub fn size_to_pow_2(mut size: (i32, i32)) -> (i32, i32) {
-        if size.0 % 2 != 0 {
-            size.0 += 1
+    #[cfg(feature = "image")]
+    /// Draw a greyscale image (pixel values 0-15) at a coord which can be off the screen, e.g. for text sliding in from an edge. Returns the area drawn.
+    ///
+    /// With [`Driver::set_clipping`] the part of the image on the screen is drawn, possibly nothing. Otherwise an image running past an edge
+    /// returns [`DriverError::InvalidWindow`]
+    pub fn draw_image_at(&mut self, x: i32, y: i32, image: &GrayImage) -> Result<Rect, Error> {
+        let (width, height) = (image.width() as i64, image.height() as i64);
+        let (screen_width, screen_height) = (self.width() as i64, self.height() as i64);
+        let (left, top) = ((x as i64).clamp(0, screen_width), (y as i64).clamp(0, screen_height));
+        let (right, bottom) = ((x as i64 + width).clamp(0, screen_width), (y as i64 + height).clamp(0, screen_height));
+        let visible = Rect::new(left as usize, top as usize, (right - left) as usize, (bottom - top) as usize);
+
+        if !self.is_clipping() && (visible.w as i64) * (visible.h as i64) != width * height {
+            return Err(DriverError::InvalidWindow { rect: visible, reason: "image runs past the edge of the screen" }.into())
         }
-        if size.1 % 2 != 0 {
-            size.1 += 1
+        if visible.is_empty() {
+            return Ok(visible)
         }
-        size
+        let crop = imageops::crop_imm(image, (left - x as i64) as u32, (top - y as i64) as u32, visible.w as u32, visible.h as u32).to_image();
+        // Drawn through the framebuffer, so an odd width left by clipping keeps its last column
+        self.framebuffer_mut().draw_image(visible.x, visible.y, &crop);
+        self.flush_rect(visible)?;
+        Ok(visible)
     }
 
-    /// Get the size of some text (ignoring new lines), returns the size of each character too.
-    pub fn get_text_size_full(&self, text: &str, scale: &Scale, font: &Font) -> (usize, usize, usize) {
-        let v_metrics = font.v_metrics(*scale);
-        let height = (v_metrics.ascent - v_metrics.descent).ceil() as i32;
-
-        let layout = font.layout("_", *scale,  point(0.0, 0.0));
-        let min_x = layout.clone()
-            .next()
-            .map(|g| g.pixel_bounding_box().unwrap().min.x)
-            .unwrap();
-        let max_x = layout.clone()
-            .last()
-            .map(|g| g.pixel_bounding_box().unwrap().max.x)
-            .unwrap();
-        let width = max_x - min_x;
-
-        let (w, h) = WS1in5::size_to_pow_2((width, height));
-        (w as usize * text.chars().count(), h as usize, w as usize)
+    #[cfg(feature = "image")]
+    /// Draw a greyscale image (pixel values 0-15) at the specified coord with its levels remapped, e.g. an inverted icon on a
+    /// selected row. The framebuffer holds the remapped levels, and the driver's own [`Remap`] still applies on top. Returns the area drawn
+    pub fn draw_image_remapped(&mut self, x: usize, y: usize, image: &GrayImage, remap: &Remap) -> Result<Rect, Error> {
+        let mut packed = images::pack(image);
+        remap.apply(&mut packed);
+        self.show_image(packed, x, y, image.width() as usize, image.height() as usize)?;
+        let rect = Rect::new(x, y, image.width() as usize, image.height() as usize);
+        Ok(rect.intersection(&Rect::new(0, 0, self.width(), self.height())).unwrap_or(Rect::new(x, y, 0, 0)))
     }
 
-    /// Get the size of some text (ignoring new lines)
-    pub fn get_text_size(&self, text: &str, scale: &Scale, font: &Font) -> (usize, usize) {
-        let (w, h, _) =self.get_text_size_full(text, scale, font);
-        (w, h)
+    #[cfg(feature = "image")]
+    /// Draw a greyscale image (pixel values 0-15) against a point of the screen, moved in from the anchored edges by an offset.
+    /// Returns the area drawn
+    pub fn draw_image_anchored(&mut self, anchor: Anchor, offset: (usize, usize), image: &GrayImage) -> Result<Rect, Error> {
+        let screen = Rect::new(0, 0, self.width(), self.height());
+        let rect = anchor.place(screen, image.width() as usize, image.height() as usize, offset);
+        self.draw_image_area(rect.x, rect.y, image)
     }
 
-    /// Create image from text
-    pub fn create_text(&self, text: &str, scale: &Scale, font: &Font, flip: bool) -> (ImageBuffer<Luma<u8>, Vec<u8>>, usize, usize) {
-        let (width, height, char_width) = self.get_text_size_full(text, scale, font);
-        let mut image = GrayImage::new(width as u32, height as u32);
-        for (i, char) in text.chars().enumerate() {
-            image = drawing::draw_text(&image, Luma([15]), (i * char_width) as i32, 0, *scale, font, &char.to_string());
-        }
-        let mut img = DynamicImage::ImageLuma8(image);
-        if flip {
-            img = img.rotate180();
-        }
-        (img.to_luma8(), width as usize, height as usize)
+    #[cfg(feature = "image")]
+    /// Draw into an area with `image` or `imageproc`, on a canvas (pixel values 0-15) holding what the area shows now, then show the canvas.
+    /// The area is clipped to the screen, so the canvas can be smaller than asked for. Returns the area drawn
+    ///
+    /// ```rust,ignore
+    /// screen.draw_into(Rect::new(0, 0, 64, 32), |canvas| {
+    ///     drawing::draw_filled_circle_mut(canvas, (16, 16), 10, Luma([15]));
+    /// })?;
+    /// ```
+    pub fn draw_into<F: FnOnce(&mut GrayImage)>(&mut self, rect: Rect, draw: F) -> Result<Rect, Error> {
+        let screen = Rect::new(0, 0, self.width(), self.height());
+        let Some(area) = rect.intersection(&screen) else {
+            return Ok(Rect::new(rect.x, rect.y, 0, 0))
+        };
+
+        let framebuffer = self.framebuffer();
+        let mut canvas = GrayImage::from_fn(area.w as u32, area.h as u32, |x, y| image::Luma([framebuffer.get_pixel(area.x + x as usize, area.y + y as usize)]));
+        draw(&mut canvas);
+
+        self.framebuffer_mut().draw_image(area.x, area.y, &canvas);
+        self.flush_rect(area)?;
+        Ok(area)
     }
 
-    /// Draw text to the screen at the specified coord (ignores new lines) (when flip = true, the screen is assumed to be upside down)
-    pub fn draw_text(&mut self, x: usize, y: usize, text: &str, scale: &Scale, font: &Font, flip: bool) -> Result<(usize, usize), Error> {
-        let (image, width, height) = self.create_text(text, scale, font, flip);
-        let buffer = self.get_buffer(image.enumerate_pixels(), width, height)?;
+    #[cfg(feature = "image")]
+    /// Load an image file (PNG, JPEG, BMP, ...), scale it to the screen, convert it to 4 bit greyscale and show it
+    pub fn show_image_file<P: AsRef<Path>>(&mut self, path: P, fit: Fit) -> Result<(), Error> {
+        self.show_image_file_with(path, &ImageOptions { fit, ..Default::default() })
+    }
 
-        if flip {
-            self.show_image(buffer, OLED_WIDTH - width - x, OLED_HEIGHT - height - y, width, height)?;
-        } else {
-            self.show_image(buffer, x, y, width, height)?;
-        }
+    #[cfg(feature = "image")]
+    /// Load an image file, prepare it with the given options (fit, auto contrast) and show it
+    pub fn show_image_file_with<P: AsRef<Path>>(&mut self, path: P, options: &ImageOptions) -> Result<(), Error> {
+        let image = images::load_image(path)?;
+        let image = images::prepare_image(&image, self.width() as u32, self.height() as u32, options);
+        self.draw_image(0, 0, &image)?;
+        Ok(())
+    }
 
-        Ok((x + width, y + height))
+    #[cfg(feature = "text")]
+    /// Draw text centered on the screen, moved up and left by an offset (ignores new lines). Returns the area drawn,
+    /// or [`DriverError::InvalidWindow`] if the text is larger than the screen or the offset moves it past the edge
+    pub fn draw_centered_text(&mut self, x: usize, y: usize, text: &str, scale: &Scale, font: &Font) -> Result<Rect, Error> {
+        let (screen_width, screen_height) = (self.width(), self.height());
+        let (width, height) = text::text_size(text, scale, font);
+        let left = (screen_width / 2).checked_sub((width / 2).saturating_add(x));
+        let top = (screen_height / 2).checked_sub((height / 2).saturating_add(y));
+        let (Some(left), Some(top)) = (left, top) else {
+            return Err(DriverError::InvalidWindow { rect: Rect::new(0, 0, width, height), reason: "centered text runs past the edge of the screen" }.into())
+        };
+        self.draw_text(left, top, text, scale, font)
     }
 
-    /// Draw text centered on the screen with a given offset (ignores new lines) (when flip = true, the screen is assumed to be upside down)
-    pub fn draw_centered_text(&mut self, x: usize, y: usize, text: &str, scale: &Scale, font: &Font, flip: bool) -> Result<(usize, usize), Error> {
-        let (image, width, height) = self.create_text(text, scale, font, flip);
-        let buffer = self.get_buffer(image.enumerate_pixels(), width, height)?;
+    #[cfg(feature = "text")]
+    /// Draw text against a point of the screen, moved in from the anchored edges by an offset (ignores new lines),
+    /// e.g. `Anchor::BottomRight` for a battery percentage in the corner. Returns the area drawn
+    pub fn draw_text_anchored(&mut self, anchor: Anchor, offset: (usize, usize), text: &str, scale: &Scale, font: &Font) -> Result<Rect, Error> {
+        let screen = Rect::new(0, 0, self.width(), self.height());
+        let (width, height) = text::text_size(text, scale, font);
+        let rect = anchor.place(screen, width, height, offset);
+        self.draw_text(rect.x, rect.y, text, scale, font)
+    }
 
-        if flip {
-            self.show_image(buffer, OLED_WIDTH - width - (OLED_WIDTH / 2 - width / 2 - x), OLED_HEIGHT - height - (OLED_HEIGHT / 2 - height / 2 - y), width, height)?;
-        } else {
-            self.show_image(buffer, OLED_WIDTH / 2 - width / 2 - x, OLED_HEIGHT / 2 - height / 2 - y, width, height)?;
-        }
+    #[cfg(feature = "text")]
+    /// Draw text over what is already on the screen (ignores new lines), with an outline or shadow to keep it readable on photos and busy backgrounds.
+    /// Returns the area drawn, including the effects
+    pub fn draw_styled_text(&mut self, x: usize, y: usize, text: &str, scale: &Scale, font: &Font, style: &TextStyle) -> Result<Rect, Error> {
+        let screen = Rect::new(0, 0, self.width(), self.height());
+        let (width, height) = text::text_size(text, scale, font);
+        let Some(area) = style.bounds(Rect::new(x, y, width, height)).align_even().intersection(&screen) else {
+            return Ok(Rect::new(x, y, 0, 0))
+        };
+
+        let mut canvas = self.framebuffer().region(area).to_image();
+        text::render_styled_text(&mut canvas, x as i32 - area.x as i32, y as i32 - area.y as i32, text, scale, font, style);
+        self.draw_image(area.x, area.y, &canvas)?;
+        Ok(area)
+    }
 
-        Ok((x + width, y + height))
+    #[cfg(feature = "text")]
+    /// Draw text turned a quarter clockwise, reading from top to bottom, with its top left at a coord (ignores new lines), e.g. for axis labels and side columns.
+    /// Returns the area drawn
+    pub fn draw_text_vertical(&mut self, x: usize, y: usize, text: &str, scale: &Scale, font: &Font) -> Result<Rect, Error> {
+        self.draw_image_area(x, y, &text::vertical_text_image(text, scale, font, 15))
     }
 
-    /// Draw a paragraph, wraps text across the screen (ignores new lines) (when flip = true, the screen is assumed to be upside down)
-    pub fn draw_paragraph(&mut self, text: &str, scale: &Scale, font: &Font, flip: bool) -> Result<(usize, usize), Error> {
-        self.draw_paragraph_at(0, 0, text, scale, font, flip)
+    #[cfg(feature = "text")]
+    /// Draw a paragraph, wraps text across the screen (ignores new lines). Returns the area drawn
+    pub fn draw_paragraph(&mut self, text: &str, scale: &Scale, font: &Font) -> Result<Rect, Error> {
+        self.draw_paragraph_at(0, 0, text, scale, font)
     }
 
-    /// Draw a paragraph starting at a coord, wraps text across the screen (ignores new lines) (when flip = true, the screen is assumed to be upside down)
-    pub fn draw_paragraph_at(&mut self, mut x: usize, mut y: usize, text: &str, scale: &Scale, font: &Font, flip: bool) -> Result<(usize, usize), Error> {
+    #[cfg(feature = "text")]
+    /// Draw a paragraph starting at a coord, wraps text across the screen (ignores new lines). Returns the area drawn
+    ///
+    /// Text past the bottom of the screen is lost, see [`text::paginate_text`] to split long text into pages
+    pub fn draw_paragraph_at(&mut self, mut x: usize, mut y: usize, text: &str, scale: &Scale, font: &Font) -> Result<Rect, Error> {
+        let screen_width = self.width();
+        let (width, height) = text::text_size("_", scale, font);
+        let mut drawn = Rect::new(x, y, 0, 0);
+        let mut utf8 = [0u8; 4];
         for char in text.chars() {
-             let (image, width, height) = if char.is_whitespace() {
-                self.create_text("_", scale, font, flip)
-            } else {
-                self.create_text(&format!("{}", char), scale, font, flip)
-            };
-
-            let buffer = self.get_buffer(image.enumerate_pixels(), width, height)?;
             if !char.is_whitespace() {
-                if flip {
-                    self.show_image(buffer, OLED_WIDTH - width - x, OLED_HEIGHT - height - y, width, height)?;
-                } else {
-                    self.show_image(buffer, x, y, width, height)?;
-                }
+                let rect = self.draw_text(x, y, char.encode_utf8(&mut utf8), scale, font)?;
+                drawn = drawn.union(&rect);
             }
 
             x += width;
 
-            if x+width > OLED_WIDTH {
+            if x+width > screen_width {
                 x = 0;
                 y += height;
             }
         }
 
-        Ok((x, y))
+        Ok(drawn)
     }
-}
 
+    #[cfg(feature = "text")]
+    /// Draw a page from [`text::paginate_text`] over its region, clearing what was there. Returns false if there's no page n
+    pub fn draw_page(&mut self, pages: &[TextPage], n: usize, scale: &Scale, font: &Font) -> Result<bool, Error> {
+        let Some(page) = pages.get(n) else {
+            return Ok(false)
+        };
+        self.draw_image_area(page.region.x, page.region.y, &page.render(scale, font, 15))?;
+        Ok(true)
+    }
+
+    #[cfg(feature = "image")]
+    /// Draw an image at a coord, returning the area drawn on the screen
+    fn draw_image_area(&mut self, x: usize, y: usize, image: &GrayImage) -> Result<Rect, Error> {
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        self.show_image(images::pack(image), x, y, width, height)?;
+        Ok(Rect::new(x, y, width, height).intersection(&Rect::new(0, 0, self.width(), self.height())).unwrap_or(Rect::new(x, y, 0, 0)))
+    }
+}
@@ -0,0 +1,144 @@
+//! Screen navigation
+
+use std::time::Duration;
+
+use image::GrayImage;
+
+use crate::{WS1in5, Error, Framebuffer, Transition};
+
+/// A full screen page managed by a [`NavStack`]
+pub trait Screen {
+    /// Render the screen onto a blank full size canvas (pixel values 0-15)
+    fn render(&mut self, canvas: &mut GrayImage);
+
+    /// Called when the screen becomes the top of the stack
+    fn on_enter(&mut self) {}
+
+    /// Called when the screen is covered by another screen or popped
+    fn on_leave(&mut self) {}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Transition used when moving between screens
+pub enum NavTransition {
+    /// Hard cut to the new screen
+    #[default]
+    None,
+    /// Slide the new screen in over a number of steps, with a delay between each step
+    Slide { steps: usize, delay: Duration },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Forward,
+    Back,
+}
+
+/// A stack of screens with back handling
+pub struct NavStack {
+    screens: Vec<Box<dyn Screen>>,
+    transition: NavTransition,
+}
+
+impl NavStack {
+    /// Create a new stack with a root screen, the root screen can't be popped
+    pub fn new(root: Box<dyn Screen>) -> NavStack {
+        NavStack { screens: vec![root], transition: NavTransition::None }
+    }
+
+    /// Set the transition used when pushing and popping screens
+    pub fn with_transition(mut self, transition: NavTransition) -> NavStack {
+        self.transition = transition;
+        self
+    }
+
+    /// Number of screens on the stack
+    pub fn depth(&self) -> usize {
+        self.screens.len()
+    }
+
+    /// Returns true if the current screen is the root screen
+    pub fn is_root(&self) -> bool {
+        self.screens.len() == 1
+    }
+
+    /// The current screen
+    pub fn current(&self) -> &dyn Screen {
+        self.screens.last().expect("Stack always has a root").as_ref()
+    }
+
+    /// The current screen
+    pub fn current_mut(&mut self) -> &mut dyn Screen {
+        self.screens.last_mut().expect("Stack always has a root").as_mut()
+    }
+
+    /// Push a screen onto the stack and draw it
+    pub fn push(&mut self, display: &mut WS1in5, screen: Box<dyn Screen>) -> Result<(), Error> {
+        self.current_mut().on_leave();
+
+        self.screens.push(screen);
+        self.current_mut().on_enter();
+        let to = self.render_current(display);
+
+        self.present(display, &to, Direction::Forward)
+    }
+
+    /// Pop the current screen and draw the one below it. Returns false if already at the root screen
+    pub fn back(&mut self, display: &mut WS1in5) -> Result<bool, Error> {
+        if self.is_root() {
+            return Ok(false)
+        }
+
+        if let Some(mut screen) = self.screens.pop() {
+            screen.on_leave();
+        }
+        self.current_mut().on_enter();
+        let to = self.render_current(display);
+
+        self.present(display, &to, Direction::Back)?;
+        Ok(true)
+    }
+
+    /// Pop every screen down to the root screen and draw it
+    pub fn back_to_root(&mut self, display: &mut WS1in5) -> Result<(), Error> {
+        if self.is_root() {
+            return Ok(())
+        }
+
+        while self.screens.len() > 1 {
+            if let Some(mut screen) = self.screens.pop() {
+                screen.on_leave();
+            }
+        }
+        self.current_mut().on_enter();
+        let to = self.render_current(display);
+
+        self.present(display, &to, Direction::Back)
+    }
+
+    /// Redraw the current screen
+    pub fn redraw(&mut self, display: &mut WS1in5) -> Result<(), Error> {
+        let canvas = self.render_current(display);
+        display.draw_image(0, 0, &canvas)?;
+        Ok(())
+    }
+
+    fn render_current(&mut self, display: &WS1in5) -> GrayImage {
+        let mut canvas = GrayImage::new(display.width() as u32, display.height() as u32);
+        self.current_mut().render(&mut canvas);
+        canvas
+    }
+
+    fn present(&self, display: &mut WS1in5, to: &GrayImage, direction: Direction) -> Result<(), Error> {
+        let frame = Framebuffer::from_image(to);
+        let transition = match direction {
+            Direction::Forward => Transition::SlideLeft,
+            Direction::Back => Transition::SlideRight,
+        };
+
+        match self.transition {
+            NavTransition::None => display.transition_steps(&frame, Transition::Cut, 0, Duration::ZERO),
+            NavTransition::Slide { steps, delay } => display.transition_steps(&frame, transition, steps, delay),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! Notifications shown over the screen's content for a while
+
+use std::time::Duration;
+
+use image::{GrayImage, Luma};
+use imageproc::{drawing, rect::Rect as DrawRect};
+use rusttype::{Scale, Font};
+
+use crate::{WS1in5, Error, Rect, Anchor, Framebuffer, text};
+
+/// Space between a notification and the edges of the screen
+const MARGIN: usize = 2;
+/// Space between a notification's border and its text
+const PADDING: usize = 3;
+
+impl WS1in5 {
+    /// Show a message in a box over the screen for a while, then put back what was underneath. The text is wrapped to the screen,
+    /// a second notification replaces the first. Returns the area covered
+    ///
+    /// Doesn't block, call [`Driver::update_overlay`](crate::Driver::update_overlay) regularly to remove it once the time is up
+    /// ([`RenderThread`](crate::render_thread::RenderThread) does this itself). See [`Driver::show_overlay`](crate::Driver::show_overlay).
+    pub fn notify(&mut self, text: &str, duration: Duration, position: Anchor, scale: &Scale, font: &Font) -> Result<Rect, Error> {
+        let screen = Rect::new(0, 0, self.width(), self.height());
+        let image = notification_image(text, screen, scale, font);
+        let area = Rect::new(MARGIN, MARGIN, screen.w.saturating_sub(MARGIN * 2), screen.h.saturating_sub(MARGIN * 2));
+        let rect = position.place(area, image.width() as usize, image.height() as usize, (0, 0));
+        Ok(self.show_overlay(rect.x, rect.y, Framebuffer::from_image(&image), duration)?)
+    }
+}
+
+/// A bordered box holding the text, wrapped to fit a screen
+fn notification_image(text: &str, screen: Rect, scale: &Scale, font: &Font) -> GrayImage {
+    let inset = MARGIN + PADDING + 1;
+    let region = Rect::new(0, 0, screen.w.saturating_sub(inset * 2), screen.h.saturating_sub(inset * 2));
+    let lines = text::paginate_text(text, scale, font, region).into_iter().next().map(|page| page.lines).unwrap_or_default();
+    let (_, line_height) = text::text_size("_", scale, font);
+    let width = lines.iter().map(|line| text::text_size(line, scale, font).0).max().unwrap_or(0);
+
+    let (box_width, box_height) = (width + (PADDING + 1) * 2, lines.len().max(1) * line_height + (PADDING + 1) * 2);
+    let mut image = GrayImage::new(box_width as u32, box_height as u32);
+    drawing::draw_hollow_rect_mut(&mut image, DrawRect::at(0, 0).of_size(box_width as u32, box_height as u32), Luma([15]));
+    for (i, line) in lines.iter().enumerate() {
+        text::render_text(&mut image, (PADDING + 1) as i32, (PADDING + 1 + i * line_height) as i32, line, scale, font, 15);
+    }
+    image
+}
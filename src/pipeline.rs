@@ -0,0 +1,109 @@
+//! Two stage frame pipeline, converting the next frame while the current one is written
+
+use std::{sync::mpsc, thread, time::{Duration, Instant}};
+
+#[cfg(feature = "image")]
+use image::GrayImage;
+
+use crate::{WS1in5, Error, Framebuffer, AnimationStats};
+#[cfg(feature = "image")]
+use crate::{Fit, images};
+
+/// Plays a sequence of frames (e.g. video or GIF frames) with conversion on a second thread
+///
+/// While frame N is being written to the screen, frame N+1 is already being scaled and converted,
+/// so the frame rate is limited by the slower of the two stages rather than their sum.
+/// Only a few frames are converted ahead, so frames are shown as soon as they are due.
+pub struct FramePipeline {
+    frame_time: Option<Duration>,
+    depth: usize,
+}
+
+impl Default for FramePipeline {
+    fn default() -> Self {
+        FramePipeline::new()
+    }
+}
+
+impl FramePipeline {
+    /// Create new, showing frames as fast as they can be converted and written
+    pub fn new() -> FramePipeline {
+        FramePipeline { frame_time: None, depth: 1 }
+    }
+
+    /// Show frames at a target frame rate, skipping frames to keep up
+    pub fn with_fps(mut self, fps: f32) -> FramePipeline {
+        let fps = if fps.is_finite() && fps > 0.0 { fps } else { 1.0 };
+        self.frame_time = Some(Duration::from_secs_f32(1.0 / fps));
+        self
+    }
+
+    /// Number of converted frames that can wait to be written (at least 1)
+    pub fn with_depth(mut self, depth: usize) -> FramePipeline {
+        self.depth = depth.max(1);
+        self
+    }
+
+    /// Convert each frame with a closure on a second thread and show the results in order
+    pub fn run<I, F>(&self, display: &mut WS1in5, frames: I, convert: F) -> Result<AnimationStats, Error>
+    where
+        I: IntoIterator,
+        I::IntoIter: Send,
+        F: FnMut(I::Item) -> Framebuffer + Send,
+    {
+        let start = Instant::now();
+        let mut stats = AnimationStats::default();
+        let (sender, receiver) = mpsc::sync_channel(self.depth);
+
+        thread::scope(|scope| {
+            let frames = frames.into_iter();
+            let mut convert = convert;
+            scope.spawn(move || {
+                for frame in frames {
+                    let converted = convert(frame);
+                    // The receiver is gone if writing failed
+                    if sender.send(converted).is_err() {
+                        break
+                    }
+                }
+            });
+
+            let mut n = 0;
+            for frame in receiver {
+                if let Some(frame_time) = self.frame_time {
+                    let due = Duration::from_secs_f64(frame_time.as_secs_f64() * n as f64);
+                    let elapsed = start.elapsed();
+                    if elapsed < due {
+                        thread::sleep(due - elapsed);
+                    } else if elapsed > due + frame_time {
+                        // Too late to be worth showing
+                        n += 1;
+                        stats.dropped += 1;
+                        continue
+                    }
+                }
+
+                display.present(frame)?;
+                stats.frames += 1;
+                n += 1;
+            }
+            Ok::<(), Error>(())
+        })?;
+
+        stats.elapsed = start.elapsed();
+        Ok(stats)
+    }
+
+    #[cfg(feature = "image")]
+    /// Show 8 bit greyscale images, scaled to the screen and converted to 4 bit on a second thread
+    pub fn run_images<I>(&self, display: &mut WS1in5, images: I, fit: Fit) -> Result<AnimationStats, Error>
+    where
+        I: IntoIterator<Item = GrayImage>,
+        I::IntoIter: Send,
+    {
+        let (width, height) = (display.width() as u32, display.height() as u32);
+        self.run(display, images, move |image| {
+            Framebuffer::from_image(&images::quantize(&images::fit_image(&image, width, height, fit)))
+        })
+    }
+}
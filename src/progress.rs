@@ -0,0 +1,91 @@
+//! Progress reporting from background tasks
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::{SharedWS1in5, Error, Theme, widgets::{ProgressBar, draw_widget}};
+
+struct State {
+    bar: ProgressBar<'static>,
+    theme: Theme,
+    finished: bool,
+}
+
+#[derive(Clone)]
+/// A handle for background threads to report progress, drawn into a bound [`ProgressBar`] through a shared display
+///
+/// Cloning the handle shares the same progress bar.
+pub struct TaskProgress {
+    state: Arc<Mutex<State>>,
+    display: SharedWS1in5,
+}
+
+impl TaskProgress {
+    /// Bind a progress bar to a shared display and draw it
+    pub fn new(display: SharedWS1in5, bar: ProgressBar<'static>, theme: Theme) -> Result<TaskProgress, Error> {
+        let progress = TaskProgress {
+            state: Arc::new(Mutex::new(State { bar, theme, finished: false })),
+            display,
+        };
+        progress.redraw()?;
+        Ok(progress)
+    }
+
+    /// Set the progress (0 to 1) and redraw
+    pub fn set_progress(&self, progress: f32) -> Result<(), Error> {
+        self.update(|bar| bar.set_progress(progress))
+    }
+
+    /// Set the message and redraw
+    pub fn set_message(&self, message: &str) -> Result<(), Error> {
+        self.update(|bar| bar.set_message(message))
+    }
+
+    /// Fill the bar and stop accepting updates
+    pub fn finish(&self) -> Result<(), Error> {
+        self.update(|bar| bar.set_progress(1.0))?;
+        self.lock_state().finished = true;
+        Ok(())
+    }
+
+    /// Fill the bar, set a final message and stop accepting updates
+    pub fn finish_with_message(&self, message: &str) -> Result<(), Error> {
+        self.update(|bar| {
+            bar.set_progress(1.0);
+            bar.set_message(message);
+        })?;
+        self.lock_state().finished = true;
+        Ok(())
+    }
+
+    /// Returns true if the task has finished
+    pub fn is_finished(&self) -> bool {
+        self.lock_state().finished
+    }
+
+    /// The current progress (0 to 1)
+    pub fn progress(&self) -> f32 {
+        self.lock_state().bar.progress()
+    }
+
+    /// Redraw the progress bar
+    pub fn redraw(&self) -> Result<(), Error> {
+        let state = self.lock_state();
+        let mut display = self.display.lock();
+        draw_widget(&mut display, &state.bar, &state.theme, false)
+    }
+
+    fn update<F: FnOnce(&mut ProgressBar<'static>)>(&self, f: F) -> Result<(), Error> {
+        let mut state = self.lock_state();
+        if state.finished {
+            return Ok(())
+        }
+        f(&mut state.bar);
+
+        let mut display = self.display.lock();
+        draw_widget(&mut display, &state.bar, &state.theme, false)
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,228 @@
+//! Binary protocol for driving the screen over a stream (TCP, Unix sockets, pipes)
+//!
+//! Every message is a 6 byte header (kind, flags, big endian u32 payload length) followed by the payload.
+//! Frames start with their width and height (big endian u16s) so any panel size can be sent, regions with their rectangle.
+//! The rest of a frame or region payload may be compressed, which is marked in the flags.
+
+use std::io::{Read, Write};
+
+use crate::{Error, Rect, Framebuffer, OLED_WIDTH, OLED_HEIGHT};
+
+const KIND_FRAME: u8 = 0x01;
+const KIND_REGION: u8 = 0x02;
+const KIND_CLEAR: u8 = 0x03;
+const KIND_AUTH: u8 = 0x04;
+const KIND_SUSPEND: u8 = 0x05;
+const KIND_RESUME: u8 = 0x06;
+const KIND_ACK: u8 = 0x80;
+const KIND_ERROR: u8 = 0x81;
+
+const FLAG_DEFLATE: u8 = 0x01;
+const FLAG_LZ4: u8 = 0x02;
+
+/// Largest payload accepted, a little over an uncompressed frame filling the controller's memory
+pub const MAX_PAYLOAD: usize = OLED_WIDTH / 2 * OLED_HEIGHT + 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Compression applied to frame and region payloads
+pub enum Compression {
+    /// Send payloads as is
+    #[default]
+    None,
+    /// Deflate payloads, falling back to uncompressed when that would be smaller (requires the `compression` feature to send or receive)
+    Deflate,
+    /// LZ4 payloads, which compress less than deflate but take much less time, for frequent frames.
+    /// Falls back to uncompressed when that would be smaller (requires the `compression` feature to send or receive)
+    Lz4,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A protocol message
+pub enum Message {
+    /// Show a full screen frame
+    Frame(Framebuffer),
+    /// Write packed data to a section of the screen
+    Region { rect: Rect, data: Vec<u8> },
+    /// Clear the screen
+    Clear,
+    /// Switch the panel off ahead of a system suspend
+    Suspend,
+    /// Re-initialise the panel after a system resume and restore what was shown
+    Resume,
+    /// Authenticate with a token, must be the first message when the server requires a token
+    Auth(String),
+    /// The last request succeeded
+    Ack,
+    /// The last request failed
+    Error(String),
+}
+
+/// Write a message to a stream
+pub fn write_message<W: Write>(writer: &mut W, message: &Message, compression: Compression) -> Result<(), Error> {
+    let (kind, payload, compressible) = match message {
+        Message::Frame(frame) => {
+            let mut payload = Vec::with_capacity(4 + frame.as_bytes().len());
+            for value in [frame.width(), frame.height()] {
+                payload.extend_from_slice(&(value as u16).to_be_bytes());
+            }
+            payload.extend_from_slice(frame.as_bytes());
+            (KIND_FRAME, payload, 4)
+        },
+        Message::Region { rect, data } => {
+            let mut payload = Vec::with_capacity(8 + data.len());
+            for value in [rect.x, rect.y, rect.w, rect.h] {
+                payload.extend_from_slice(&(value as u16).to_be_bytes());
+            }
+            payload.extend_from_slice(data);
+            (KIND_REGION, payload, 8)
+        },
+        Message::Clear => (KIND_CLEAR, vec![], 0),
+        Message::Suspend => (KIND_SUSPEND, vec![], 0),
+        Message::Resume => (KIND_RESUME, vec![], 0),
+        Message::Auth(token) => (KIND_AUTH, token.as_bytes().to_vec(), 0),
+        Message::Ack => (KIND_ACK, vec![], 0),
+        Message::Error(error) => (KIND_ERROR, error.as_bytes().to_vec(), 0),
+    };
+
+    let (flags, payload) = match (kind, compression) {
+        (KIND_FRAME | KIND_REGION, Compression::Deflate | Compression::Lz4) => {
+            let mut compressed = payload[..compressible].to_vec();
+            let flag = match compression {
+                Compression::Lz4 => {
+                    compressed.extend(lz4_compress(&payload[compressible..])?);
+                    FLAG_LZ4
+                },
+                _ => {
+                    compressed.extend(deflate(&payload[compressible..])?);
+                    FLAG_DEFLATE
+                },
+            };
+            if compressed.len() < payload.len() {
+                (flag, compressed)
+            } else {
+                (0, payload)
+            }
+        },
+        _ => (0, payload),
+    };
+
+    let mut header = [kind, flags, 0, 0, 0, 0];
+    header[2..].copy_from_slice(&(payload.len() as u32).to_be_bytes());
+    writer.write_all(&header).map_err(Error::from)?;
+    writer.write_all(&payload).map_err(Error::from)?;
+    writer.flush().map_err(Error::from)
+}
+
+/// Read a message from a stream
+pub fn read_message<R: Read>(reader: &mut R) -> Result<Message, Error> {
+    let mut header = [0u8; 6];
+    reader.read_exact(&mut header).map_err(Error::from)?;
+    let (kind, flags) = (header[0], header[1]);
+    let len = u32::from_be_bytes([header[2], header[3], header[4], header[5]]) as usize;
+    if len > MAX_PAYLOAD {
+        return Err(Error::Protocol(format!("Payload of {} bytes is too large", len)))
+    }
+
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload).map_err(Error::from)?;
+
+    match kind {
+        KIND_FRAME => {
+            if payload.len() < 4 {
+                return Err(Error::Protocol("Frame is missing its size".to_string()))
+            }
+            let value = |i: usize| u16::from_be_bytes([payload[i * 2], payload[i * 2 + 1]]) as usize;
+            let (width, height) = (value(0), value(1));
+            if width > OLED_WIDTH || height > OLED_HEIGHT {
+                return Err(Error::Protocol(format!("Frame of {}x{} is larger than the controller", width, height)))
+            }
+            let data = decompress(flags, payload[4..].to_vec())?;
+            let frame = Framebuffer::from_packed(width, height, data)
+                .map_err(|_| Error::Protocol(format!("Frame data doesn't fill {}x{}", width, height)))?;
+            Ok(Message::Frame(frame))
+        },
+        KIND_REGION => {
+            if payload.len() < 8 {
+                return Err(Error::Protocol("Region is missing its rectangle".to_string()))
+            }
+            let value = |i: usize| u16::from_be_bytes([payload[i * 2], payload[i * 2 + 1]]) as usize;
+            let rect = Rect::new(value(0), value(1), value(2), value(3));
+            let data = decompress(flags, payload[8..].to_vec())?;
+            Ok(Message::Region { rect, data })
+        },
+        KIND_CLEAR => Ok(Message::Clear),
+        KIND_SUSPEND => Ok(Message::Suspend),
+        KIND_RESUME => Ok(Message::Resume),
+        KIND_AUTH => Ok(Message::Auth(String::from_utf8_lossy(&payload).to_string())),
+        KIND_ACK => Ok(Message::Ack),
+        KIND_ERROR => Ok(Message::Error(String::from_utf8_lossy(&payload).to_string())),
+        kind => Err(Error::Protocol(format!("Unknown message kind {:#04x}", kind))),
+    }
+}
+
+fn decompress(flags: u8, payload: Vec<u8>) -> Result<Vec<u8>, Error> {
+    if flags & FLAG_LZ4 != 0 {
+        lz4_decompress(&payload)
+    } else if flags & FLAG_DEFLATE != 0 {
+        inflate(&payload)
+    } else {
+        Ok(payload)
+    }
+}
+
+#[cfg(feature = "compression")]
+fn deflate(data: &[u8]) -> Result<Vec<u8>, Error> {
+    use flate2::{write::DeflateEncoder, Compression};
+
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+    encoder.write_all(data).map_err(Error::from)?;
+    encoder.finish().map_err(Error::from)
+}
+
+#[cfg(feature = "compression")]
+fn inflate(data: &[u8]) -> Result<Vec<u8>, Error> {
+    use flate2::read::DeflateDecoder;
+
+    let mut decoded = Vec::new();
+    DeflateDecoder::new(data)
+        .take(MAX_PAYLOAD as u64)
+        .read_to_end(&mut decoded)
+        .map_err(Error::from)?;
+    Ok(decoded)
+}
+
+#[cfg(feature = "compression")]
+fn lz4_compress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    Ok(lz4_flex::block::compress_prepend_size(data))
+}
+
+#[cfg(feature = "compression")]
+fn lz4_decompress(data: &[u8]) -> Result<Vec<u8>, Error> {
+    // The decompressed size comes first, checked before anything is allocated for it
+    let size = data.get(..4).map(|size| u32::from_le_bytes([size[0], size[1], size[2], size[3]]) as usize)
+        .ok_or_else(|| Error::Protocol("LZ4 payload is missing its size".to_string()))?;
+    if size > MAX_PAYLOAD {
+        return Err(Error::Protocol(format!("Payload of {} bytes is too large", size)))
+    }
+    lz4_flex::block::decompress(&data[4..], size).map_err(|e| Error::Protocol(format!("Invalid LZ4 payload: {}", e)))
+}
+
+#[cfg(not(feature = "compression"))]
+fn lz4_compress(_data: &[u8]) -> Result<Vec<u8>, Error> {
+    Err(Error::Protocol("Compression requires the compression feature".to_string()))
+}
+
+#[cfg(not(feature = "compression"))]
+fn lz4_decompress(_data: &[u8]) -> Result<Vec<u8>, Error> {
+    Err(Error::Protocol("Compression requires the compression feature".to_string()))
+}
+
+#[cfg(not(feature = "compression"))]
+fn deflate(_data: &[u8]) -> Result<Vec<u8>, Error> {
+    Err(Error::Protocol("Compression requires the compression feature".to_string()))
+}
+
+#[cfg(not(feature = "compression"))]
+fn inflate(_data: &[u8]) -> Result<Vec<u8>, Error> {
+    Err(Error::Protocol("Compression requires the compression feature".to_string()))
+}
@@ -0,0 +1,282 @@
+//! Driving the screen from another process or machine
+
+use std::{io::{Read, Write, ErrorKind}, net::{TcpListener, TcpStream, ToSocketAddrs, SocketAddr}, sync::{Arc, atomic::{AtomicBool, AtomicUsize, Ordering}, mpsc::{self, Sender}}, thread, time::Duration};
+
+use crate::{WS1in5, Error, DriverError, Rect, Framebuffer, protocol::{self, Message, Compression}};
+
+/// A TCP server which shows frames sent by [`RemoteDisplay`] clients
+///
+/// Anyone who can reach the port can draw on the screen unless a token is required with [`RemoteServer::with_token`].
+/// Clients which don't authenticate within a timeout are dropped, as are clients arriving while too many others are still authenticating.
+pub struct RemoteServer {
+    listener: TcpListener,
+    compression: Compression,
+    token: Option<String>,
+    handshake_timeout: Duration,
+    max_handshakes: usize,
+}
+
+impl RemoteServer {
+    /// Listen on an address
+    pub fn bind<A: ToSocketAddrs>(address: A) -> Result<RemoteServer, Error> {
+        let listener = TcpListener::bind(address).map_err(Error::from)?;
+        Ok(RemoteServer { listener, compression: Compression::None, token: None, handshake_timeout: Duration::from_secs(5), max_handshakes: 8 })
+    }
+
+    /// Set the compression used for replies
+    pub fn with_compression(mut self, compression: Compression) -> RemoteServer {
+        self.compression = compression;
+        self
+    }
+
+    /// Require clients to authenticate with a token before anything else
+    pub fn with_token(mut self, token: &str) -> RemoteServer {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Drop clients which haven't authenticated within a time (5 seconds by default)
+    pub fn with_handshake_timeout(mut self, timeout: Duration) -> RemoteServer {
+        self.handshake_timeout = timeout;
+        self
+    }
+
+    /// Drop clients arriving while a number of others are still authenticating (8 by default)
+    pub fn with_max_handshakes(mut self, max: usize) -> RemoteServer {
+        self.max_handshakes = max.max(1);
+        self
+    }
+
+    /// The address the server is listening on
+    pub fn local_addr(&self) -> Result<SocketAddr, Error> {
+        self.listener.local_addr().map_err(Error::from)
+    }
+
+    /// Serve clients one at a time, forever. Errors from a client only end that client's connection.
+    ///
+    /// Clients authenticate on their own threads while another is being served, so one that stalls doesn't hold up the rest.
+    pub fn serve(&self, display: &mut WS1in5) -> Result<(), Error> {
+        let listener = self.listener.try_clone().map_err(Error::from)?;
+        let (sender, receiver) = mpsc::channel();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (compression, token, timeout, max, accepting) = (self.compression, self.token.clone(), self.handshake_timeout, self.max_handshakes, stopped.clone());
+        thread::spawn(move || accept_clients(listener, sender, compression, token, timeout, max, &accepting));
+
+        for stream in receiver.iter() {
+            let _ = handle_connection(stream, display, self.compression, None);
+        }
+        stopped.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Accept a single client and serve it until it disconnects
+    pub fn serve_one(&self, display: &mut WS1in5) -> Result<(), Error> {
+        let (mut stream, _) = self.listener.accept().map_err(Error::from)?;
+        ignore_client_errors(
+            handshake(&mut stream, self.compression, self.token.as_deref(), self.handshake_timeout)
+                .and_then(|_| handle_connection(stream, display, self.compression, None))
+        )
+    }
+}
+
+/// Accept clients and authenticate each on its own thread, passing them on once they have. Clients arriving while max are authenticating are dropped.
+/// Stops at the next client once stopped is set
+fn accept_clients(listener: TcpListener, sender: Sender<TcpStream>, compression: Compression, token: Option<String>, timeout: Duration, max: usize, stopped: &AtomicBool) {
+    let pending = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        if stopped.load(Ordering::Relaxed) {
+            break
+        }
+        let Ok(mut stream) = stream else { continue };
+        if pending.fetch_add(1, Ordering::Relaxed) >= max {
+            pending.fetch_sub(1, Ordering::Relaxed);
+            continue
+        }
+        let (sender, token, pending) = (sender.clone(), token.clone(), Pending(pending.clone()));
+        thread::spawn(move || {
+            let _pending = pending;
+            if handshake(&mut stream, compression, token.as_deref(), timeout).is_ok() {
+                let _ = sender.send(stream);
+            }
+        });
+    }
+}
+
+/// A handshake in progress, counted until dropped
+struct Pending(Arc<AtomicUsize>);
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Authenticate a client if a token is required, giving up if it doesn't answer within a time
+fn handshake(stream: &mut TcpStream, compression: Compression, token: Option<&str>, timeout: Duration) -> Result<(), Error> {
+    let Some(token) = token else {
+        return Ok(())
+    };
+    stream.set_read_timeout(Some(timeout)).map_err(Error::from)?;
+    authenticate(stream, compression, token)?;
+    stream.set_read_timeout(None).map_err(Error::from)
+}
+
+/// Clients disconnecting, timing out or misbehaving only end their own connection
+fn ignore_client_errors(result: Result<(), Error>) -> Result<(), Error> {
+    match result {
+        Err(Error::Driver(DriverError::IO(_))) | Err(Error::Protocol(_)) | Err(Error::Unauthorized) | Ok(()) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Serve protocol messages from a stream until it closes
+///
+/// If a token is given the first message must be [`Message::Auth`] with that token, otherwise the connection is refused.
+pub fn handle_connection<S: Read + Write>(mut stream: S, display: &mut WS1in5, compression: Compression, token: Option<&str>) -> Result<(), Error> {
+    if let Some(token) = token {
+        authenticate(&mut stream, compression, token)?;
+    }
+
+    loop {
+        let message = match protocol::read_message(&mut stream) {
+            Ok(message) => message,
+            Err(Error::Driver(DriverError::IO(e))) if e.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        let reply = match apply(display, message) {
+            Ok(()) => Message::Ack,
+            Err(e) => Message::Error(e.to_string()),
+        };
+        protocol::write_message(&mut stream, &reply, compression)?;
+    }
+}
+
+/// Check the first message from a client is [`Message::Auth`] with a token
+fn authenticate<S: Read + Write>(stream: &mut S, compression: Compression, token: &str) -> Result<(), Error> {
+    match protocol::read_message(stream)? {
+        Message::Auth(given) if tokens_match(given.as_bytes(), token.as_bytes()) => {
+            protocol::write_message(stream, &Message::Ack, compression)
+        },
+        _ => {
+            protocol::write_message(stream, &Message::Error("Authentication failed".to_string()), compression)?;
+            Err(Error::Unauthorized)
+        },
+    }
+}
+
+/// Compare tokens in constant time, so the time taken doesn't leak how much of a guess was right
+fn tokens_match(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+fn apply(display: &mut WS1in5, message: Message) -> Result<(), Error> {
+    match message {
+        Message::Frame(frame) => {
+            if (frame.width(), frame.height()) != (display.width(), display.height()) {
+                return Err(Error::Protocol(format!("Frame is {}x{}, the screen is {}x{}", frame.width(), frame.height(), display.width(), display.height())))
+            }
+            display.present(frame)?;
+        },
+        Message::Region { rect, data } => display.show_image(data, rect.x, rect.y, rect.w, rect.h)?,
+        Message::Clear => display.clear_all()?,
+        Message::Suspend => display.suspend()?,
+        Message::Resume => display.resume()?,
+        Message::Auth(_) => return Err(Error::Protocol("Already authenticated".to_string())),
+        Message::Ack | Message::Error(_) => return Err(Error::Protocol("Unexpected reply message".to_string())),
+    }
+    Ok(())
+}
+
+/// A client for a [`RemoteServer`], or anything else speaking the [`protocol`]
+pub struct RemoteDisplay<S: Read + Write = TcpStream> {
+    stream: S,
+    compression: Compression,
+}
+
+impl RemoteDisplay<TcpStream> {
+    /// Connect to a remote server
+    pub fn connect<A: ToSocketAddrs>(address: A) -> Result<RemoteDisplay<TcpStream>, Error> {
+        let stream = TcpStream::connect(address).map_err(Error::from)?;
+        stream.set_nodelay(true).map_err(Error::from)?;
+        Ok(RemoteDisplay::new(stream))
+    }
+}
+
+impl<S: Read + Write> RemoteDisplay<S> {
+    /// Create new over an existing stream
+    pub fn new(stream: S) -> RemoteDisplay<S> {
+        RemoteDisplay { stream, compression: Compression::None }
+    }
+
+    /// Set the compression used for frames and regions
+    pub fn with_compression(mut self, compression: Compression) -> RemoteDisplay<S> {
+        self.compression = compression;
+        self
+    }
+
+    /// Authenticate with a server which requires a token, must be called before anything else
+    pub fn authenticate(&mut self, token: &str) -> Result<(), Error> {
+        self.request(&Message::Auth(token.to_string())).map_err(|e| match e {
+            Error::Remote(_) => Error::Unauthorized,
+            e => e,
+        })
+    }
+
+    /// Show a full screen frame
+    pub fn present(&mut self, frame: &Framebuffer) -> Result<(), Error> {
+        self.request(&Message::Frame(frame.clone()))
+    }
+
+    /// Write packed data of a certain size to the screen at the specified coord
+    pub fn show_image(&mut self, buffer: &[u8], x: usize, y: usize, width: usize, height: usize) -> Result<(), Error> {
+        self.request(&Message::Region { rect: Rect::new(x, y, width, height), data: buffer.to_vec() })
+    }
+
+    /// Clear the screen
+    pub fn clear_all(&mut self) -> Result<(), Error> {
+        self.request(&Message::Clear)
+    }
+
+    /// Switch the panel off ahead of a system suspend
+    pub fn suspend(&mut self) -> Result<(), Error> {
+        self.request(&Message::Suspend)
+    }
+
+    /// Re-initialise the panel after a system resume and restore what was shown
+    pub fn resume(&mut self) -> Result<(), Error> {
+        self.request(&Message::Resume)
+    }
+
+    fn request(&mut self, message: &Message) -> Result<(), Error> {
+        protocol::write_message(&mut self.stream, message, self.compression)?;
+        match protocol::read_message(&mut self.stream)? {
+            Message::Ack => Ok(()),
+            Message::Error(e) => Err(Error::Remote(e)),
+            _ => Err(Error::Protocol("Expected a reply".to_string())),
+        }
+    }
+}
+
+/// Handle a systemd sleep hook invocation by suspending or resuming a remote display.
+///
+/// systemd runs executables in `/usr/lib/systemd/system-sleep/` with `pre` before sleeping and `post` after waking, pass those arguments through.
+/// Other arguments are ignored.
+pub fn sleep_hook<A: ToSocketAddrs>(address: A, token: Option<&str>, args: &[String]) -> Result<(), Error> {
+    let suspend = match args.first().map(|a| a.as_str()) {
+        Some("pre") => true,
+        Some("post") => false,
+        _ => return Ok(()),
+    };
+
+    let mut remote = RemoteDisplay::connect(address)?;
+    if let Some(token) = token {
+        remote.authenticate(token)?;
+    }
+
+    if suspend {
+        remote.suspend()
+    } else {
+        remote.resume()
+    }
+}
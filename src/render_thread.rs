@@ -0,0 +1,182 @@
+//! A thread which owns the screen and draws commands sent to it
+
+#[cfg(feature = "text")]
+use std::time::Duration;
+use std::{sync::{mpsc::{self, Sender, Receiver, RecvTimeoutError}, Arc, Mutex}, thread::{self, JoinHandle}};
+
+#[cfg(feature = "image")]
+use image::GrayImage;
+#[cfg(feature = "text")]
+use rusttype::{Scale, Font};
+
+#[cfg(feature = "text")]
+use crate::Anchor;
+use crate::{WS1in5, Error, Rect, Framebuffer, batch::DrawOp};
+
+#[derive(Clone)]
+/// A drawing command for a [`RenderThread`]
+pub enum RenderCommand {
+    /// Clear the whole screen
+    ClearAll,
+    /// Clear a section of the screen
+    Clear(Rect),
+    /// Fill a section of the screen with a colour (0-15)
+    Fill(Rect, u8),
+    /// Draw a greyscale image (pixel values 0-15) at a coord
+    #[cfg(feature = "image")]
+    Image { x: usize, y: usize, image: GrayImage },
+    /// Draw text (ignores new lines) at a coord with a colour (0-15)
+    #[cfg(feature = "text")]
+    Text { x: usize, y: usize, text: String, scale: Scale, font: Font<'static>, color: u8 },
+    /// Replace the whole screen with a frame
+    Frame(Framebuffer),
+    /// Show a notification over the screen for a while, see [`WS1in5::notify`]
+    #[cfg(feature = "text")]
+    Notify { text: String, duration: Duration, position: Anchor, scale: Scale, font: Font<'static> },
+}
+
+enum Message {
+    Draw(RenderCommand),
+    Stop,
+}
+
+#[derive(Clone)]
+/// Sends commands to a [`RenderThread`], can be cloned and sent to other threads
+pub struct RenderSender {
+    sender: Sender<Message>,
+}
+
+impl RenderSender {
+    /// Queue a command, returns [`Error::Stopped`] if the render thread has stopped
+    pub fn send(&self, command: RenderCommand) -> Result<(), Error> {
+        self.sender.send(Message::Draw(command)).map_err(|_| Error::Stopped)
+    }
+}
+
+/// A thread which owns the screen, so slow i2c transfers don't hold up the threads drawing
+///
+/// Commands queued while the screen is being written are coalesced, they are all drawn into the framebuffer and only the area that changed is written, once.
+/// Errors are kept until taken with [`RenderThread::take_error`]. If the screen has a screensaver, the thread starts it when no commands arrive for long enough,
+/// and notifications are removed when their time is up.
+pub struct RenderThread {
+    sender: RenderSender,
+    error: Arc<Mutex<Option<Error>>>,
+    handle: JoinHandle<WS1in5>,
+}
+
+impl RenderThread {
+    /// Move the screen to a new render thread
+    pub fn spawn(display: WS1in5) -> RenderThread {
+        let (sender, receiver) = mpsc::channel();
+        let error = Arc::new(Mutex::new(None));
+
+        let thread_error = error.clone();
+        let handle = thread::spawn(move || run(display, receiver, thread_error));
+
+        RenderThread { sender: RenderSender { sender }, error, handle }
+    }
+
+    /// A sender for queuing commands from other threads
+    pub fn sender(&self) -> RenderSender {
+        self.sender.clone()
+    }
+
+    /// Queue a command
+    pub fn send(&self, command: RenderCommand) -> Result<(), Error> {
+        self.sender.send(command)
+    }
+
+    /// Take the last error the render thread hit, if any
+    pub fn take_error(&self) -> Option<Error> {
+        self.error.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+
+    /// Draw everything already queued, stop the thread and take the screen back
+    pub fn stop(self) -> WS1in5 {
+        let _ = self.sender.sender.send(Message::Stop);
+        match self.handle.join() {
+            Ok(display) => display,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+}
+
+fn run(mut display: WS1in5, receiver: Receiver<Message>, error: Arc<Mutex<Option<Error>>>) -> WS1in5 {
+    let mut stopping = false;
+    while !stopping {
+        let due = match (display.screensaver_due(), display.overlay_due()) {
+            (Some(screensaver), Some(overlay)) => Some(screensaver.min(overlay)),
+            (screensaver, overlay) => screensaver.or(overlay),
+        };
+        let received = match due {
+            Some(due) => receiver.recv_timeout(due),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        let first = match received {
+            Ok(message) => message,
+            Err(RecvTimeoutError::Timeout) => {
+                if let Err(e) = display.update_overlay().and_then(|_| display.update_screensaver()) {
+                    *error.lock().unwrap_or_else(|e| e.into_inner()) = Some(e.into());
+                }
+                continue
+            },
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        let mut frame = display.framebuffer().clone();
+        #[cfg(feature = "text")]
+        let mut notifications = vec![];
+        for message in std::iter::once(first).chain(receiver.try_iter()) {
+            match message {
+                #[cfg(feature = "text")]
+                Message::Draw(command @ RenderCommand::Notify { .. }) => notifications.push(command),
+                Message::Draw(command) => draw(&mut frame, &command),
+                Message::Stop => {
+                    stopping = true;
+                    break
+                },
+            }
+        }
+
+        if let Err(e) = display.present(frame) {
+            *error.lock().unwrap_or_else(|e| e.into_inner()) = Some(e.into());
+        }
+        // Notifications go over the frame, only the last one queued is seen
+        #[cfg(feature = "text")]
+        if let Some(command) = notifications.pop() {
+            if let Err(e) = notify(&mut display, &command) {
+                *error.lock().unwrap_or_else(|e| e.into_inner()) = Some(e);
+            }
+        }
+    }
+    display
+}
+
+#[cfg(feature = "text")]
+fn notify(display: &mut WS1in5, command: &RenderCommand) -> Result<(), Error> {
+    match command {
+        RenderCommand::Notify { text, duration, position, scale, font } => display.notify(text, *duration, *position, scale, font).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+fn draw(frame: &mut Framebuffer, command: &RenderCommand) {
+    match command {
+        RenderCommand::ClearAll => frame.fill(0),
+        RenderCommand::Clear(rect) => { DrawOp::Clear(*rect).apply(frame); },
+        RenderCommand::Fill(rect, color) => { DrawOp::Fill(*rect, *color).apply(frame); },
+        #[cfg(feature = "image")]
+        RenderCommand::Image { x, y, image } => { DrawOp::Image { x: *x, y: *y, image }.apply(frame); },
+        #[cfg(feature = "text")]
+        RenderCommand::Text { x, y, text, scale, font, color } => {
+            DrawOp::Text { x: *x, y: *y, text, scale: *scale, font, color: *color }.apply(frame);
+        },
+        RenderCommand::Frame(source) => {
+            if source.width() == frame.width() && source.height() == frame.height() {
+                *frame = source.clone();
+            }
+        },
+        #[cfg(feature = "text")]
+        RenderCommand::Notify { .. } => {},
+    }
+}
@@ -0,0 +1,196 @@
+//! Named pages with back and forward history
+
+use std::time::Duration;
+
+use crate::{WS1in5, Error, Framebuffer, Transition};
+
+/// A full screen page registered with a [`Router`]
+///
+/// Closures taking a `&mut Framebuffer` are pages, and so are widget [`FocusGroup`](crate::widgets::FocusGroup)s.
+pub trait Page {
+    /// Render the page onto a blank full size frame
+    fn render(&mut self, frame: &mut Framebuffer);
+
+    /// Returns true if the page has changed since it was left, so it's rendered again rather than shown as it was left
+    fn needs_render(&self) -> bool {
+        false
+    }
+
+    /// Called when the page is shown
+    fn on_enter(&mut self) {}
+
+    /// Called when another page is shown
+    fn on_leave(&mut self) {}
+}
+
+impl<F: FnMut(&mut Framebuffer)> Page for F {
+    fn render(&mut self, frame: &mut Framebuffer) {
+        self(frame)
+    }
+}
+
+#[cfg(feature = "text")]
+impl<'a> Page for crate::widgets::FocusGroup<'a> {
+    fn render(&mut self, frame: &mut Framebuffer) {
+        for index in 0..self.len() {
+            if let Some(widget) = self.widget(index) {
+                let bounds = widget.bounds();
+                let image = crate::widgets::render_widget(widget, self.theme(), self.focused() == Some(index));
+                frame.draw_image(bounds.x, bounds.y, &image);
+            }
+        }
+    }
+}
+
+struct Entry<'a> {
+    name: String,
+    page: Box<dyn Page + 'a>,
+    /// What the screen showed when the page was left, so returning to it is instant
+    saved: Option<Framebuffer>,
+}
+
+/// Named pages, switched between with a transition and back and forward history
+///
+/// Each page's framebuffer is kept when it's left, returning to it shows it as it was without rendering it again.
+pub struct Router<'a> {
+    pages: Vec<Entry<'a>>,
+    current: Option<usize>,
+    back: Vec<usize>,
+    forward: Vec<usize>,
+    transition: Transition,
+    duration: Duration,
+}
+
+impl<'a> Default for Router<'a> {
+    fn default() -> Self {
+        Router::new()
+    }
+}
+
+impl<'a> Router<'a> {
+    /// Create new, without pages
+    pub fn new() -> Router<'a> {
+        Router { pages: vec![], current: None, back: vec![], forward: vec![], transition: Transition::Cut, duration: Duration::ZERO }
+    }
+
+    /// Set the transition used between pages, going back uses it reversed
+    pub fn with_transition(mut self, transition: Transition, duration: Duration) -> Router<'a> {
+        self.transition = transition;
+        self.duration = duration;
+        self
+    }
+
+    /// Register a page under a name, replacing any page with the same name
+    pub fn add(&mut self, name: &str, page: Box<dyn Page + 'a>) {
+        let entry = Entry { name: name.to_string(), page, saved: None };
+        match self.index(name) {
+            Some(index) => self.pages[index] = entry,
+            None => self.pages.push(entry),
+        }
+    }
+
+    /// Name of the page being shown
+    pub fn current(&self) -> Option<&str> {
+        self.current.map(|index| self.pages[index].name.as_str())
+    }
+
+    /// Get a page, e.g. to change it before calling [`Router::redraw`]
+    pub fn page_mut(&mut self, name: &str) -> Option<&mut (dyn Page + 'a)> {
+        let index = self.index(name)?;
+        Some(self.pages[index].page.as_mut())
+    }
+
+    /// Returns true if there's a page to go back to
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    /// Returns true if there's a page to go forward to
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+
+    /// Forget a page's saved framebuffer, so it's rendered again next time it's shown
+    pub fn invalidate(&mut self, name: &str) {
+        if let Some(index) = self.index(name) {
+            self.pages[index].saved = None;
+        }
+    }
+
+    /// Show a page, clearing the forward history
+    pub fn go(&mut self, display: &mut WS1in5, name: &str) -> Result<(), Error> {
+        let index = self.index(name).ok_or_else(|| Error::UnknownPage(name.to_string()))?;
+        if self.current == Some(index) {
+            return Ok(())
+        }
+
+        if let Some(current) = self.current {
+            self.back.push(current);
+        }
+        self.forward.clear();
+        self.show(display, index, self.transition)
+    }
+
+    /// Show the previous page. Returns false if there's no page to go back to
+    pub fn back(&mut self, display: &mut WS1in5) -> Result<bool, Error> {
+        let Some(index) = self.back.pop() else {
+            return Ok(false)
+        };
+
+        if let Some(current) = self.current {
+            self.forward.push(current);
+        }
+        self.show(display, index, self.transition.reversed())?;
+        Ok(true)
+    }
+
+    /// Show the page gone back from. Returns false if there's no page to go forward to
+    pub fn forward(&mut self, display: &mut WS1in5) -> Result<bool, Error> {
+        let Some(index) = self.forward.pop() else {
+            return Ok(false)
+        };
+
+        if let Some(current) = self.current {
+            self.back.push(current);
+        }
+        self.show(display, index, self.transition)?;
+        Ok(true)
+    }
+
+    /// Render the current page again and show it
+    pub fn redraw(&mut self, display: &mut WS1in5) -> Result<(), Error> {
+        if let Some(index) = self.current {
+            let frame = self.render(display, index);
+            display.present(frame)?;
+        }
+        Ok(())
+    }
+
+    fn show(&mut self, display: &mut WS1in5, index: usize, transition: Transition) -> Result<(), Error> {
+        if let Some(current) = self.current.take() {
+            let entry = &mut self.pages[current];
+            entry.saved = Some(display.framebuffer().clone());
+            entry.page.on_leave();
+        }
+
+        let entry = &mut self.pages[index];
+        entry.page.on_enter();
+        let frame = match entry.saved.take() {
+            Some(saved) if !entry.page.needs_render() => saved,
+            _ => self.render(display, index),
+        };
+
+        self.current = Some(index);
+        display.transition_to(&frame, transition, self.duration)
+    }
+
+    fn render(&mut self, display: &WS1in5, index: usize) -> Framebuffer {
+        let mut frame = Framebuffer::new(display.width(), display.height());
+        self.pages[index].page.render(&mut frame);
+        frame
+    }
+
+    fn index(&self, name: &str) -> Option<usize> {
+        self.pages.iter().position(|entry| entry.name == name)
+    }
+}
@@ -0,0 +1,293 @@
+//! Screens described in JSON or TOML, with text, images, bars and charts bound to data
+//!
+//! ```toml
+//! font = "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf"
+//!
+//! [[elements]]
+//! type = "text"
+//! x = 0
+//! y = 0
+//! text = "CPU {cpu}%"
+//!
+//! [[elements]]
+//! type = "bar"
+//! x = 0
+//! y = 16
+//! width = 128
+//! height = 8
+//! value = "cpu"
+//!
+//! [[elements]]
+//! type = "chart"
+//! x = 0
+//! y = 32
+//! width = 128
+//! height = 64
+//! values = "history"
+//! ```
+//!
+//! Text substitutes `{name}` with the data's value, bars and charts name the value they show.
+//! Missing values render as empty text, an empty bar or an empty chart.
+
+use std::{collections::HashMap, fmt, fs, path::{Path, PathBuf}};
+
+use image::{GrayImage, Luma};
+use imageproc::drawing;
+use rusttype::{Font, Scale};
+use serde::Deserialize;
+
+use crate::{WS1in5, Error, DriverError, Rect, Framebuffer, Fit, images, text};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+/// A value elements can be bound to
+pub enum Value {
+    /// A number, for text and bars
+    Number(f64),
+    /// Text
+    Text(String),
+    /// Numbers, for charts
+    List(Vec<f64>),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(number) => number.fmt(f),
+            Value::Text(text) => text.fmt(f),
+            Value::List(list) => f.write_str(&list.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(", ")),
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(number: f64) -> Value {
+        Value::Number(number)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(text: &str) -> Value {
+        Value::Text(text.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(text: String) -> Value {
+        Value::Text(text)
+    }
+}
+
+impl From<Vec<f64>> for Value {
+    fn from(list: Vec<f64>) -> Value {
+        Value::List(list)
+    }
+}
+
+/// Values by name, bound to a scene's elements when it's rendered
+pub type SceneData = HashMap<String, Value>;
+
+fn default_size() -> f32 {
+    12.0
+}
+
+fn default_level() -> u8 {
+    15
+}
+
+fn default_max() -> f64 {
+    100.0
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+/// Something drawn in a scene
+pub enum Element {
+    /// Text, with `{name}` replaced by values
+    Text {
+        x: usize,
+        y: usize,
+        text: String,
+        #[serde(default = "default_size")]
+        size: f32,
+        #[serde(default = "default_level")]
+        level: u8,
+    },
+    /// An image file, letterboxed to a size if given. The path can contain `{name}`
+    Image {
+        x: usize,
+        y: usize,
+        path: String,
+        width: Option<u32>,
+        height: Option<u32>,
+    },
+    /// An outlined bar filled by a value between a min and max (0-100 by default)
+    Bar {
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        value: String,
+        #[serde(default)]
+        min: f64,
+        #[serde(default = "default_max")]
+        max: f64,
+        #[serde(default = "default_level")]
+        level: u8,
+    },
+    /// A line chart of a list of values, scaled to the min and max given or to the values
+    Chart {
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        values: String,
+        min: Option<f64>,
+        max: Option<f64>,
+        #[serde(default = "default_level")]
+        level: u8,
+    },
+}
+
+#[derive(Deserialize)]
+/// A full screen description, rendered with [`Scene::render`]
+pub struct Scene {
+    font: Option<PathBuf>,
+    elements: Vec<Element>,
+    #[serde(skip)]
+    loaded_font: Option<Font<'static>>,
+}
+
+impl Scene {
+    /// Parse a scene from JSON, loading its font
+    pub fn from_json(json: &str) -> Result<Scene, Error> {
+        serde_json::from_str::<Scene>(json).map_err(|e| Error::Protocol(e.to_string()))?.load_font()
+    }
+
+    /// Parse a scene from TOML, loading its font
+    pub fn from_toml(toml: &str) -> Result<Scene, Error> {
+        toml::from_str::<Scene>(toml).map_err(|e| Error::Protocol(e.to_string()))?.load_font()
+    }
+
+    /// Load a scene from a `.json` or `.toml` file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Scene, Error> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(Error::from)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Scene::from_json(&contents),
+            Some("toml") => Scene::from_toml(&contents),
+            _ => Err(DriverError::Unsupported("scene files other than .json or .toml").into()),
+        }
+    }
+
+    fn load_font(mut self) -> Result<Scene, Error> {
+        if let Some(path) = &self.font {
+            self.loaded_font = Some(Font::try_from_vec(fs::read(path).map_err(Error::from)?).ok_or(Error::InvalidFont)?);
+        } else if self.elements.iter().any(|element| matches!(element, Element::Text { .. })) {
+            return Err(Error::Protocol("Scene has text but no font".to_string()))
+        }
+        Ok(self)
+    }
+
+    /// The elements, in drawing order
+    pub fn elements(&self) -> &[Element] {
+        &self.elements
+    }
+
+    /// Render the scene with the data onto a full size frame
+    pub fn render_frame(&self, frame: &mut Framebuffer, data: &SceneData) -> Result<(), Error> {
+        for element in self.elements.iter() {
+            match element {
+                Element::Text { x, y, text, size, level } => {
+                    let Some(font) = &self.loaded_font else { continue };
+                    let text = substitute(text, data);
+                    let scale = Scale::uniform(*size);
+                    let (width, height) = text::text_size(&text, &scale, font);
+                    let mut canvas = GrayImage::new(width as u32, height as u32);
+                    text::render_text(&mut canvas, 0, 0, &text, &scale, font, (*level).min(15));
+                    frame.draw_image(*x, *y, &canvas);
+                },
+                Element::Image { x, y, path, width, height } => {
+                    let mut image = images::load_image(substitute(path, data))?;
+                    if width.is_some() || height.is_some() {
+                        let width = width.unwrap_or(image.width());
+                        let height = height.unwrap_or(image.height());
+                        image = images::fit_image(&image, width, height, Fit::Letterbox);
+                    }
+                    frame.draw_image(*x, *y, &images::quantize(&image));
+                },
+                Element::Bar { x, y, width, height, value, min, max, level } => {
+                    let rect = Rect::new(*x, *y, *width, *height);
+                    outline(frame, rect, *level);
+                    if let Some(Value::Number(value)) = data.get(value) {
+                        let fraction = ((value - min) / (max - min)).clamp(0.0, 1.0);
+                        let inner = width.saturating_sub(4);
+                        let filled = (inner as f64 * fraction).round() as usize;
+                        frame.fill_rect(Rect::new(x + 2, y + 2, filled, height.saturating_sub(4)), (*level).min(15));
+                    }
+                },
+                Element::Chart { x, y, width, height, values, min, max, level } => {
+                    let Some(Value::List(values)) = data.get(values) else { continue };
+                    let chart = chart(values, *width, *height, *min, *max, (*level).min(15));
+                    frame.draw_image(*x, *y, &chart);
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Render the scene with the data and show it, only writing what changed. Returns the area written
+    pub fn render(&self, display: &mut WS1in5, data: &SceneData) -> Result<Option<Rect>, Error> {
+        let mut frame = Framebuffer::new(display.width(), display.height());
+        self.render_frame(&mut frame, data)?;
+        Ok(display.present(frame)?)
+    }
+}
+
+/// Replace `{name}` with values, missing values with nothing
+fn substitute(template: &str, data: &SceneData) -> String {
+    let mut result = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else { break };
+        result.push_str(&rest[..start]);
+        if let Some(value) = data.get(&rest[start + 1..start + end]) {
+            result.push_str(&value.to_string());
+        }
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn outline(frame: &mut Framebuffer, rect: Rect, level: u8) {
+    if rect.w == 0 || rect.h == 0 {
+        return
+    }
+    let level = level.min(15);
+    frame.hline(rect.x, rect.y, rect.w, level);
+    frame.hline(rect.x, rect.y + rect.h - 1, rect.w, level);
+    frame.fill_rect(Rect::new(rect.x, rect.y, 1, rect.h), level);
+    frame.fill_rect(Rect::new(rect.x + rect.w - 1, rect.y, 1, rect.h), level);
+}
+
+fn chart(values: &[f64], width: usize, height: usize, min: Option<f64>, max: Option<f64>, level: u8) -> GrayImage {
+    let mut image = GrayImage::new(width as u32, height as u32);
+    if values.len() < 2 || width < 2 || height < 2 {
+        return image
+    }
+
+    let min = min.unwrap_or_else(|| values.iter().copied().fold(f64::INFINITY, f64::min));
+    let max = max.unwrap_or_else(|| values.iter().copied().fold(f64::NEG_INFINITY, f64::max));
+    let range = if max > min { max - min } else { 1.0 };
+    let point = |i: usize, value: f64| {
+        let x = i as f64 * (width - 1) as f64 / (values.len() - 1) as f64;
+        let y = (height - 1) as f64 * (1.0 - ((value - min) / range).clamp(0.0, 1.0));
+        (x as f32, y as f32)
+    };
+
+    for (i, pair) in values.windows(2).enumerate() {
+        drawing::draw_line_segment_mut(&mut image, point(i, pair[0]), point(i + 1, pair[1]), Luma([level]));
+    }
+    image
+}
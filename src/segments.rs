@@ -0,0 +1,352 @@
+//! Large seven and fourteen segment numbers, drawn without a font
+
+use image::{GrayImage, Luma};
+
+use crate::{WS1in5, Error};
+
+const A: u16 = 1 << 0;
+const B: u16 = 1 << 1;
+const C: u16 = 1 << 2;
+const D: u16 = 1 << 3;
+const E: u16 = 1 << 4;
+const F: u16 = 1 << 5;
+const G1: u16 = 1 << 6;
+const G2: u16 = 1 << 7;
+/// Diagonal from the top left corner to the centre
+const H: u16 = 1 << 8;
+/// Vertical from the top to the centre
+const I: u16 = 1 << 9;
+/// Diagonal from the top right corner to the centre
+const J: u16 = 1 << 10;
+/// Diagonal from the centre to the bottom left corner
+const K: u16 = 1 << 11;
+/// Vertical from the centre to the bottom
+const L: u16 = 1 << 12;
+/// Diagonal from the centre to the bottom right corner
+const M: u16 = 1 << 13;
+/// Decimal point
+const DP: u16 = 1 << 14;
+/// Colon, shown in a cell of its own
+const COLON: u16 = 1 << 15;
+const G: u16 = G1 | G2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// The segments making up each character
+pub enum Segments {
+    /// Digits, A-F and a few letters
+    #[default]
+    Seven,
+    /// Digits, every letter and a few symbols, with diagonals and centre verticals
+    Fourteen,
+}
+
+impl Segments {
+    /// The segments lit for a character, unknown characters are blank
+    fn mask(self, char: char) -> u16 {
+        match self {
+            Segments::Seven => seven_segment(char),
+            Segments::Fourteen => fourteen_segment(char),
+        }
+    }
+}
+
+fn seven_segment(char: char) -> u16 {
+    match char {
+        '0' | 'O' | 'D' => A | B | C | D | E | F,
+        '1' | 'l' => B | C,
+        '2' | 'Z' | 'z' => A | B | G | E | D,
+        '3' => A | B | G | C | D,
+        '4' => F | G | B | C,
+        '5' | 'S' | 's' => A | F | G | C | D,
+        '6' => A | F | G | E | D | C,
+        '7' => A | B | C,
+        '8' | 'B' => A | B | C | D | E | F | G,
+        '9' => A | B | C | D | F | G,
+        'A' | 'a' => A | B | C | E | F | G,
+        'b' => C | D | E | F | G,
+        'C' => A | D | E | F,
+        'c' => D | E | G,
+        'd' => B | C | D | E | G,
+        'E' | 'e' => A | D | E | F | G,
+        'F' | 'f' => A | E | F | G,
+        'H' => B | C | E | F | G,
+        'h' => C | E | F | G,
+        'J' | 'j' => B | C | D | E,
+        'L' => D | E | F,
+        'n' => C | E | G,
+        'o' => C | D | E | G,
+        'P' | 'p' => A | B | E | F | G,
+        'r' => E | G,
+        't' => D | E | F | G,
+        'U' => B | C | D | E | F,
+        'u' => C | D | E,
+        'y' => B | C | D | F | G,
+        '-' => G,
+        '_' => D,
+        '=' => D | G,
+        '°' => A | B | F | G,
+        _ => 0,
+    }
+}
+
+fn fourteen_segment(char: char) -> u16 {
+    match char.to_ascii_uppercase() {
+        '0' => A | B | C | D | E | F | J | K,
+        '1' => B | C | J,
+        '2' => A | B | G | E | D,
+        '3' => A | B | G2 | C | D,
+        '4' => F | G | B | C,
+        '5' => A | F | G | C | D,
+        '6' => A | F | G | E | D | C,
+        '7' => A | B | C,
+        '8' => A | B | C | D | E | F | G,
+        '9' => A | B | C | D | F | G,
+        'A' => A | B | C | E | F | G,
+        'B' => A | B | C | D | I | L | G2,
+        'C' => A | D | E | F,
+        'D' => A | B | C | D | I | L,
+        'E' => A | D | E | F | G1,
+        'F' => A | E | F | G1,
+        'G' => A | C | D | E | F | G2,
+        'H' => B | C | E | F | G,
+        'I' => A | D | I | L,
+        'J' => B | C | D | E,
+        'K' => E | F | G1 | J | M,
+        'L' => D | E | F,
+        'M' => B | C | E | F | H | J,
+        'N' => B | C | E | F | H | M,
+        'O' => A | B | C | D | E | F,
+        'P' => A | B | E | F | G,
+        'Q' => A | B | C | D | E | F | M,
+        'R' => A | B | E | F | G | M,
+        'S' => A | H | G2 | C | D,
+        'T' => A | I | L,
+        'U' => B | C | D | E | F,
+        'V' => E | F | K | J,
+        'W' => B | C | E | F | K | M,
+        'X' => H | J | K | M,
+        'Y' => H | J | L,
+        'Z' => A | D | J | K,
+        '-' => G,
+        '_' => D,
+        '=' => D | G,
+        '+' => G | I | L,
+        '*' => G | H | I | J | K | L | M,
+        '/' => J | K,
+        '\\' => H | M,
+        '°' => A | B | F | G,
+        _ => 0,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How segment characters look
+pub struct SegmentStyle {
+    /// Seven or fourteen segments
+    pub segments: Segments,
+    /// Width of each character
+    pub width: usize,
+    /// Height of each character
+    pub height: usize,
+    /// Thickness of each segment
+    pub thickness: usize,
+    /// Gap between characters, decimal points are drawn in it
+    pub spacing: usize,
+    /// Level of lit segments (0-15)
+    pub on: u8,
+    /// Level of unlit segments (0-15), e.g. 1 for the faint outline of a real display, 0 hides them
+    pub off: u8,
+}
+
+impl SegmentStyle {
+    /// Create new, with segments a sixth of the width thick
+    pub fn new(segments: Segments, width: usize, height: usize) -> SegmentStyle {
+        let thickness = (width / 6).max(1);
+        SegmentStyle { segments, width, height, thickness, spacing: thickness + 2, on: 15, off: 0 }
+    }
+
+    /// Set the thickness of each segment
+    pub fn with_thickness(mut self, thickness: usize) -> SegmentStyle {
+        self.thickness = thickness.max(1);
+        self
+    }
+
+    /// Set the gap between characters
+    pub fn with_spacing(mut self, spacing: usize) -> SegmentStyle {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Set the levels of lit and unlit segments
+    pub fn with_levels(mut self, on: u8, off: u8) -> SegmentStyle {
+        self.on = on.min(15);
+        self.off = off.min(15);
+        self
+    }
+
+    /// Distance from the start of one character to the next, rounded up to be even so every character can be written on its own
+    pub fn pitch(&self) -> usize {
+        let pitch = self.width + self.spacing;
+        pitch + pitch % 2
+    }
+
+    /// Split text into cells, a decimal point joins the character before it
+    fn cells(&self, text: &str) -> Vec<u16> {
+        let mut cells: Vec<u16> = Vec::with_capacity(text.len());
+        for char in text.chars() {
+            match char {
+                '.' | ',' => match cells.last_mut() {
+                    Some(cell) if *cell & (DP | COLON) == 0 => *cell |= DP,
+                    _ => cells.push(DP),
+                },
+                ':' => cells.push(COLON),
+                char => cells.push(self.segments.mask(char)),
+            }
+        }
+        cells
+    }
+
+    /// Render one cell, a character and the spacing after it
+    fn render_cell(&self, cell: u16) -> GrayImage {
+        let mut canvas = GrayImage::new(self.pitch() as u32, self.height as u32);
+        let t = self.thickness as f32;
+        let (w, h) = (self.width as f32, self.height as f32);
+        let (left, right, top, bottom) = (t / 2.0, w - t / 2.0, t / 2.0, h - t / 2.0);
+        let (centre, middle) = (w / 2.0, h / 2.0);
+
+        if cell & COLON != 0 {
+            for y in [h / 3.0, h * 2.0 / 3.0] {
+                fill_segment(&mut canvas, (centre, y), (centre, y), t / 2.0 + 0.5, self.on);
+            }
+            return canvas
+        }
+
+        // Ends are pulled in so neighbouring segments stay apart
+        let gap = t;
+        let diagonal = t * 1.5;
+        let mut segments = vec![
+            (A, (left + gap, top), (right - gap, top)),
+            (B, (right, top + gap), (right, middle - gap)),
+            (C, (right, middle + gap), (right, bottom - gap)),
+            (D, (left + gap, bottom), (right - gap, bottom)),
+            (E, (left, middle + gap), (left, bottom - gap)),
+            (F, (left, top + gap), (left, middle - gap)),
+            (G1, (left + gap, middle), (centre - gap / 2.0, middle)),
+            (G2, (centre + gap / 2.0, middle), (right - gap, middle)),
+        ];
+        if self.segments == Segments::Seven {
+            segments.truncate(6);
+            segments.push((G, (left + gap, middle), (right - gap, middle)));
+        } else {
+            segments.extend([
+                (H, (left + diagonal, top + diagonal), (centre - gap, middle - gap)),
+                (I, (centre, top + gap), (centre, middle - gap)),
+                (J, (right - diagonal, top + diagonal), (centre + gap, middle - gap)),
+                (K, (centre - gap, middle + gap), (left + diagonal, bottom - diagonal)),
+                (L, (centre, middle + gap), (centre, bottom - gap)),
+                (M, (centre + gap, middle + gap), (right - diagonal, bottom - diagonal)),
+            ]);
+        }
+
+        for (mask, start, end) in segments {
+            let level = if cell & mask != 0 { self.on } else { self.off };
+            if level != 0 {
+                fill_segment(&mut canvas, start, end, t / 2.0, level);
+            }
+        }
+
+        let point = if cell & DP != 0 { self.on } else { self.off };
+        if point != 0 && self.spacing > 0 {
+            let radius = (t.min(self.spacing as f32) / 2.0).max(0.5);
+            fill_segment(&mut canvas, (w + self.spacing as f32 / 2.0, bottom), (w + self.spacing as f32 / 2.0, bottom), radius, point);
+        }
+
+        canvas
+    }
+}
+
+/// Fill every pixel within a radius of the line from start to end
+fn fill_segment(canvas: &mut GrayImage, start: (f32, f32), end: (f32, f32), radius: f32, level: u8) {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length = dx * dx + dy * dy;
+    let x0 = (start.0.min(end.0) - radius).floor().max(0.0) as u32;
+    let y0 = (start.1.min(end.1) - radius).floor().max(0.0) as u32;
+    let x1 = ((start.0.max(end.0) + radius).ceil() as u32).min(canvas.width());
+    let y1 = ((start.1.max(end.1) + radius).ceil() as u32).min(canvas.height());
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            let along = if length == 0.0 { 0.0 } else { (((px - start.0) * dx + (py - start.1) * dy) / length).clamp(0.0, 1.0) };
+            let (nx, ny) = (start.0 + dx * along - px, start.1 + dy * along - py);
+            if nx * nx + ny * ny <= radius * radius {
+                canvas.put_pixel(x, y, Luma([level]));
+            }
+        }
+    }
+}
+
+/// Render text as segment characters (pixel values 0-15)
+pub fn render_segments(text: &str, style: &SegmentStyle) -> GrayImage {
+    let cells = style.cells(text);
+    let mut image = GrayImage::new((cells.len() * style.pitch()) as u32, style.height as u32);
+    for (i, cell) in cells.into_iter().enumerate() {
+        image::imageops::replace(&mut image, &style.render_cell(cell), (i * style.pitch()) as i64, 0);
+    }
+    image
+}
+
+/// Big segment style numbers which only redraw the characters that changed, e.g. a speed or temperature readout
+pub struct SegmentDisplay {
+    x: usize,
+    y: usize,
+    style: SegmentStyle,
+    last: Option<Vec<u16>>,
+}
+
+impl SegmentDisplay {
+    /// Create new at the specified coord (x should be even)
+    pub fn new(x: usize, y: usize, style: SegmentStyle) -> SegmentDisplay {
+        SegmentDisplay { x, y, style, last: None }
+    }
+
+    /// How the characters look
+    pub fn style(&self) -> &SegmentStyle {
+        &self.style
+    }
+
+    /// Size on screen of a number of characters
+    pub fn size(&self, chars: usize) -> (usize, usize) {
+        (chars * self.style.pitch(), self.style.height)
+    }
+
+    /// Force every character to be redrawn on the next update
+    pub fn invalidate(&mut self) {
+        self.last = None;
+    }
+
+    /// Draw text, only rewriting the characters that changed since the last update and blanking any left over from longer text.
+    /// A decimal point joins the character before it. Returns the number of characters drawn
+    pub fn update(&mut self, display: &mut WS1in5, text: &str) -> Result<usize, Error> {
+        let cells = self.style.cells(text);
+        let previous = self.last.take().unwrap_or_default();
+
+        let mut drawn = 0;
+        for i in 0..cells.len().max(previous.len()) {
+            let cell = cells.get(i).copied();
+            if cell.is_some() && cell == previous.get(i).copied() {
+                continue
+            }
+
+            let image = match cell {
+                Some(cell) => self.style.render_cell(cell),
+                None => GrayImage::new(self.style.pitch() as u32, self.style.height as u32),
+            };
+            display.draw_image(self.x + i * self.style.pitch(), self.y, &image)?;
+            drawn += 1;
+        }
+
+        self.last = Some(cells);
+        Ok(drawn)
+    }
+}
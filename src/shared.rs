@@ -0,0 +1,88 @@
+//! Sharing one screen between threads
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+#[cfg(feature = "image")]
+use image::GrayImage;
+#[cfg(feature = "text")]
+use rusttype::{Scale, Font};
+
+use crate::{WS1in5, Error, Rect, Framebuffer};
+
+#[derive(Clone)]
+/// A handle to a screen which can be cloned and sent to other threads, each call locks the screen for its duration
+///
+/// ```no_run
+/// use std::thread;
+/// use ws_1in5_i2c::{WS1in5, SharedWS1in5};
+///
+/// let screen = SharedWS1in5::new(WS1in5::new(0x3c, 1, 27).unwrap());
+///
+/// let background = screen.clone();
+/// thread::spawn(move || background.clear_all().unwrap());
+///
+/// screen.with(|screen| screen.clear(0, 0, 16, 16)).unwrap();
+/// ```
+pub struct SharedWS1in5 {
+    display: Arc<Mutex<WS1in5>>,
+}
+
+impl SharedWS1in5 {
+    /// Share a screen
+    pub fn new(display: WS1in5) -> SharedWS1in5 {
+        SharedWS1in5 { display: Arc::new(Mutex::new(display)) }
+    }
+
+    /// Lock the screen, other threads wait until the guard is dropped
+    pub fn lock(&self) -> MutexGuard<'_, WS1in5> {
+        self.display.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Run a closure with the screen locked, for drawing several things without another thread drawing in between
+    pub fn with<R, F: FnOnce(&mut WS1in5) -> R>(&self, f: F) -> R {
+        f(&mut self.lock())
+    }
+
+    /// Clear the whole screen
+    pub fn clear_all(&self) -> Result<(), Error> {
+        Ok(self.lock().clear_all()?)
+    }
+
+    /// Show packed data of a certain size on the screen at the specified coord
+    pub fn show_image<B: AsRef<[u8]>>(&self, buffer: B, x: usize, y: usize, width: usize, height: usize) -> Result<(), Error> {
+        Ok(self.lock().show_image(buffer, x, y, width, height)?)
+    }
+
+    #[cfg(feature = "image")]
+    /// Draw a greyscale image (pixel values 0-15) to the screen at the specified coord
+    pub fn draw_image(&self, x: usize, y: usize, image: &GrayImage) -> Result<(usize, usize), Error> {
+        self.lock().draw_image(x, y, image)
+    }
+
+    #[cfg(feature = "text")]
+    /// Draw text to the screen at the specified coord (ignores new lines). Returns the area drawn
+    pub fn draw_text(&self, x: usize, y: usize, text: &str, scale: &Scale, font: &Font) -> Result<Rect, Error> {
+        self.lock().draw_text(x, y, text, scale, font)
+    }
+
+    /// Replace the framebuffer with a full screen frame and write the area that changed to the screen. Returns the area written
+    pub fn present(&self, frame: Framebuffer) -> Result<Option<Rect>, Error> {
+        Ok(self.lock().present(frame)?)
+    }
+
+    /// Write the whole framebuffer to the screen
+    pub fn flush(&self) -> Result<(), Error> {
+        Ok(self.lock().flush()?)
+    }
+
+    /// A copy of what is on the screen
+    pub fn framebuffer(&self) -> Framebuffer {
+        self.lock().framebuffer().clone()
+    }
+}
+
+impl From<WS1in5> for SharedWS1in5 {
+    fn from(display: WS1in5) -> SharedWS1in5 {
+        SharedWS1in5::new(display)
+    }
+}
@@ -0,0 +1,109 @@
+//! Desktop window simulating the screen
+
+use std::{sync::mpsc::{self, Sender, Receiver, TryRecvError}, thread, time::Duration};
+
+use minifb::{Window, WindowOptions, Key};
+
+use crate::{DriverError, Framebuffer, Geometry, Interface, emulator::Emulator};
+
+/// A desktop window which shows what the screen would, for trying out layouts without the hardware
+///
+/// The window runs on its own thread and is redrawn whenever the emulated display changes.
+///
+/// ```no_run
+/// use ws_1in5_i2c::{WS1in5, simulator::Simulator};
+///
+/// let mut screen = WS1in5::with_interface(Box::new(Simulator::new(4))).unwrap();
+/// screen.clear_all().unwrap();
+/// ```
+pub struct Simulator {
+    emulator: Emulator,
+    frames: Sender<Framebuffer>,
+}
+
+impl Simulator {
+    /// Open a simulator window, each screen pixel is drawn as a square of scale by scale pixels
+    pub fn new(scale: usize) -> Simulator {
+        Simulator::with_geometry(scale, Geometry::default())
+    }
+
+    /// Open a simulator window for a panel of another size
+    pub fn with_geometry(scale: usize, geometry: Geometry) -> Simulator {
+        let (frames, receiver) = mpsc::channel();
+        let scale = scale.max(1);
+        thread::spawn(move || run_window(receiver, scale, geometry));
+
+        Simulator { emulator: Emulator::with_geometry(geometry), frames }
+    }
+
+    /// The emulated controller
+    pub fn emulator(&self) -> &Emulator {
+        &self.emulator
+    }
+
+    fn update(&self) {
+        // The window may have been closed, in which case frames are dropped
+        let _ = self.frames.send(self.emulator.frame());
+    }
+}
+
+impl Interface for Simulator {
+    fn command(&mut self, cmd: u8) -> Result<(), DriverError> {
+        self.emulator.command(cmd)?;
+        self.update();
+        Ok(())
+    }
+
+    fn commands(&mut self, cmds: &[u8]) -> Result<(), DriverError> {
+        self.emulator.commands(cmds)?;
+        self.update();
+        Ok(())
+    }
+
+    fn data(&mut self, data: &[u8]) -> Result<(), DriverError> {
+        self.emulator.data(data)?;
+        self.update();
+        Ok(())
+    }
+
+    fn status(&mut self) -> Result<Option<u8>, DriverError> {
+        self.emulator.status()
+    }
+
+    fn reset(&mut self) {
+        self.emulator.reset();
+        self.update();
+    }
+}
+
+fn run_window(frames: Receiver<Framebuffer>, scale: usize, geometry: Geometry) {
+    let (width, height) = (geometry.width * scale, geometry.height * scale);
+    let mut window = match Window::new("WS 1inch5 Simulator", width, height, WindowOptions::default()) {
+        Ok(window) => window,
+        Err(_) => return,
+    };
+
+    let mut pixels = vec![0u32; width * height];
+    while window.is_open() && !window.is_key_down(Key::Escape) {
+        let mut latest = None;
+        loop {
+            match frames.try_recv() {
+                Ok(frame) => latest = Some(frame),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return,
+            }
+        }
+
+        if let Some(frame) = latest {
+            for (i, pixel) in pixels.iter_mut().enumerate() {
+                let level = frame.get_pixel(i % width / scale, i / width / scale) as u32 * 17;
+                *pixel = level << 16 | level << 8 | level;
+            }
+        }
+
+        if window.update_with_buffer(&pixels, width, height).is_err() {
+            return
+        }
+        thread::sleep(Duration::from_millis(16));
+    }
+}
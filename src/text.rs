@@ -0,0 +1,290 @@
+//! Text layout and rasterisation
+
+use std::collections::HashMap;
+
+use image::{GrayImage, Luma, imageops};
+use imageproc::drawing;
+use rusttype::{Scale, Font, point};
+
+use crate::Rect;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A drop shadow behind text
+pub struct Shadow {
+    /// How far right and down the shadow falls
+    pub offset: (usize, usize),
+    /// Shadow colour (0-15)
+    pub color: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Colour and effects for text drawn over other content, so it stays readable on photos and busy backgrounds
+pub struct TextStyle {
+    /// Text colour (0-15)
+    pub color: u8,
+    /// Colour (0-15) of a one pixel outline around each glyph
+    pub outline: Option<u8>,
+    /// Drop shadow
+    pub shadow: Option<Shadow>,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle::new(15)
+    }
+}
+
+impl TextStyle {
+    /// Create new, plain text in a colour (0-15)
+    pub fn new(color: u8) -> TextStyle {
+        TextStyle { color, outline: None, shadow: None }
+    }
+
+    /// Outline each glyph in a colour (0-15)
+    pub fn with_outline(mut self, color: u8) -> TextStyle {
+        self.outline = Some(color);
+        self
+    }
+
+    /// Add a drop shadow offset right and down, in a colour (0-15)
+    pub fn with_shadow(mut self, offset: (usize, usize), color: u8) -> TextStyle {
+        self.shadow = Some(Shadow { offset, color });
+        self
+    }
+
+    /// The area covered by text drawn in an area, including its effects
+    pub fn bounds(&self, text: Rect) -> Rect {
+        let mut bounds = text;
+        if let Some(shadow) = self.shadow {
+            bounds = bounds.union(&Rect::new(text.x + shadow.offset.0, text.y + shadow.offset.1, text.w, text.h));
+        }
+        if self.outline.is_some() {
+            let (x, y) = (bounds.x.saturating_sub(1), bounds.y.saturating_sub(1));
+            bounds = Rect::new(x, y, bounds.right() + 1 - x, bounds.bottom() + 1 - y);
+        }
+        bounds
+    }
+}
+
+/// Get the size of some text (ignoring new lines), returns the size of each character too.
+///
+/// Every character is laid out in a cell the width of an underscore.
+pub fn text_size_full(text: &str, scale: &Scale, font: &Font) -> (usize, usize, usize) {
+    let v_metrics = font.v_metrics(*scale);
+    let height = (v_metrics.ascent - v_metrics.descent).ceil() as i32;
+
+    let layout = font.layout("_", *scale,  point(0.0, 0.0));
+    let min_x = layout.clone()
+        .next()
+        .map(|g| g.pixel_bounding_box().unwrap().min.x)
+        .unwrap();
+    let max_x = layout.clone()
+        .last()
+        .map(|g| g.pixel_bounding_box().unwrap().max.x)
+        .unwrap();
+    let width = max_x - min_x;
+
+    // Round up to even sizes, so text always starts on a whole byte
+    let (w, h) = ((width + width % 2) as usize, (height + height % 2) as usize);
+    (w * text.chars().count(), h, w)
+}
+
+/// Get the size of some text (ignoring new lines)
+pub fn text_size(text: &str, scale: &Scale, font: &Font) -> (usize, usize) {
+    let (w, h, _) = text_size_full(text, scale, font);
+    (w, h)
+}
+
+/// Render text onto a canvas of its own size with a colour (0-15)
+pub fn text_image(text: &str, scale: &Scale, font: &Font, color: u8) -> GrayImage {
+    let (width, height) = text_size(text, scale, font);
+    let mut image = GrayImage::new(width as u32, height as u32);
+    render_text(&mut image, 0, 0, text, scale, font, color);
+    image
+}
+
+/// Render text turned a quarter clockwise, so it reads from top to bottom, onto a canvas of its own size with a colour (0-15)
+pub fn vertical_text_image(text: &str, scale: &Scale, font: &Font, color: u8) -> GrayImage {
+    imageops::rotate90(&text_image(text, scale, font, color))
+}
+
+/// Draw styled text onto a canvas at the specified coord, blending it with what is already there (pixel values 0-15)
+pub fn render_styled_text(canvas: &mut GrayImage, x: i32, y: i32, text: &str, scale: &Scale, font: &Font, style: &TextStyle) {
+    // Glyph coverage, 0-15
+    let mask = text_image(text, scale, font, 15);
+    let (w, h) = (mask.width() as i32, mask.height() as i32);
+    let coverage = |px: i32, py: i32| if px >= 0 && py >= 0 && px < w && py < h { mask.get_pixel(px as u32, py as u32).0[0] } else { 0 };
+
+    if let Some(shadow) = style.shadow {
+        let (dx, dy) = (shadow.offset.0 as i32, shadow.offset.1 as i32);
+        for (px, py, pixel) in mask.enumerate_pixels() {
+            blend(canvas, x + px as i32 + dx, y + py as i32 + dy, shadow.color, pixel.0[0]);
+        }
+    }
+
+    if let Some(outline) = style.outline {
+        for py in -1..=h {
+            for px in -1..=w {
+                let around = (-1..=1).flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+                    .map(|(dx, dy)| coverage(px + dx, py + dy))
+                    .max()
+                    .unwrap_or(0);
+                blend(canvas, x + px, y + py, outline, around);
+            }
+        }
+    }
+
+    for (px, py, pixel) in mask.enumerate_pixels() {
+        blend(canvas, x + px as i32, y + py as i32, style.color, pixel.0[0]);
+    }
+}
+
+/// Mix a colour into a pixel by a coverage (0-15), ignoring pixels off the canvas
+fn blend(canvas: &mut GrayImage, x: i32, y: i32, color: u8, coverage: u8) {
+    if coverage == 0 || x < 0 || y < 0 || x >= canvas.width() as i32 || y >= canvas.height() as i32 {
+        return
+    }
+
+    let pixel = canvas.get_pixel_mut(x as u32, y as u32);
+    let (background, coverage) = (pixel.0[0].min(15) as u16, coverage.min(15) as u16);
+    pixel.0[0] = ((background * (15 - coverage) + color.min(15) as u16 * coverage + 7) / 15) as u8;
+}
+
+/// Draw text onto a canvas at the specified coord with a colour (0-15)
+pub fn render_text(canvas: &mut GrayImage, x: i32, y: i32, text: &str, scale: &Scale, font: &Font, color: u8) {
+    let (_, _, char_width) = text_size_full(text, scale, font);
+    let mut utf8 = [0u8; 4];
+    for (i, char) in text.chars().enumerate() {
+        drawing::draw_text_mut(canvas, Luma([color]), x + (i * char_width) as i32, y, *scale, font, char.encode_utf8(&mut utf8));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A region's worth of wrapped lines from [`paginate_text`]
+pub struct TextPage {
+    /// Where the page is drawn
+    pub region: Rect,
+    /// The lines, top to bottom
+    pub lines: Vec<String>,
+}
+
+impl TextPage {
+    /// Render the page onto a canvas the size of its region with a colour (0-15)
+    pub fn render(&self, scale: &Scale, font: &Font, color: u8) -> GrayImage {
+        let (_, height) = text_size("_", scale, font);
+        let mut image = GrayImage::new(self.region.w as u32, self.region.h as u32);
+        for (i, line) in self.lines.iter().enumerate() {
+            render_text(&mut image, 0, (i * height) as i32, line, scale, font, color);
+        }
+        image
+    }
+}
+
+/// Split text into pages which fit in a region, wrapping lines at spaces and new lines. Words longer than a line are broken.
+///
+/// There's always at least one page, empty text gives an empty page
+pub fn paginate_text(text: &str, scale: &Scale, font: &Font, region: Rect) -> Vec<TextPage> {
+    let (char_width, height) = text_size("_", scale, font);
+    let columns = (region.w / char_width.max(1)).max(1);
+    let rows = (region.h / height.max(1)).max(1);
+
+    let mut lines = vec![];
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        let mut len = 0;
+        for word in paragraph.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+            if len > 0 && len + 1 + word.len() > columns {
+                lines.push(core::mem::take(&mut line));
+                len = 0;
+            }
+            if len > 0 {
+                line.push(' ');
+                len += 1;
+            }
+            while len + word.len() > columns {
+                let rest = word.split_off(columns - len);
+                line.extend(word);
+                lines.push(core::mem::take(&mut line));
+                len = 0;
+                word = rest;
+            }
+            len += word.len();
+            line.extend(word);
+        }
+        lines.push(line);
+    }
+
+    let mut pages: Vec<TextPage> = lines.chunks(rows).map(|lines| TextPage { region, lines: lines.to_vec() }).collect();
+    if pages.is_empty() {
+        pages.push(TextPage { region, lines: vec![] });
+    }
+    pages
+}
+
+/// Characters rasterised once at a small size, then drawn at whole multiples of it by repeating pixels,
+/// so large text doesn't rasterise big glyphs every frame
+pub struct GlyphCache<'a> {
+    font: Font<'a>,
+    scale: Scale,
+    cell: (usize, usize),
+    glyphs: HashMap<char, GrayImage>,
+}
+
+impl<'a> GlyphCache<'a> {
+    /// Create new, rasterising characters from a font at a size as they're first drawn
+    pub fn new(font: Font<'a>, scale: Scale) -> GlyphCache<'a> {
+        let (_, height, width) = text_size_full("_", &scale, &font);
+        GlyphCache { font, scale, cell: (width, height), glyphs: HashMap::new() }
+    }
+
+    /// Size of some text drawn at a multiple of the cached size
+    pub fn text_size(&self, text: &str, factor: usize) -> (usize, usize) {
+        (self.cell.0 * factor * text.chars().count(), self.cell.1 * factor)
+    }
+
+    /// Number of characters rasterised so far
+    pub fn len(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    /// Returns true if nothing has been rasterised yet
+    pub fn is_empty(&self) -> bool {
+        self.glyphs.is_empty()
+    }
+
+    /// Render text at a multiple of the cached size (1 draws it as rasterised) onto a canvas of its own size with a colour (0-15)
+    pub fn text_image(&mut self, text: &str, factor: usize, color: u8) -> GrayImage {
+        let factor = factor.max(1);
+        let (width, height) = self.text_size(text, factor);
+        let mut image = GrayImage::new(width as u32, height as u32);
+        let color = color.min(15) as u16;
+        let cell_width = self.cell.0;
+
+        for (i, char) in text.chars().enumerate() {
+            let left = i * cell_width * factor;
+            let glyph = self.glyph(char);
+            for (gx, gy, coverage) in glyph.enumerate_pixels() {
+                let level = ((coverage.0[0] as u16 * color + 7) / 15) as u8;
+                if level == 0 {
+                    continue
+                }
+                for y in 0..factor {
+                    for x in 0..factor {
+                        image.put_pixel((left + gx as usize * factor + x) as u32, (gy as usize * factor + y) as u32, Luma([level]));
+                    }
+                }
+            }
+        }
+        image
+    }
+
+    fn glyph(&mut self, char: char) -> &GrayImage {
+        let (font, scale, cell) = (&self.font, &self.scale, self.cell);
+        self.glyphs.entry(char).or_insert_with(|| {
+            let mut glyph = GrayImage::new(cell.0 as u32, cell.1 as u32);
+            render_text(&mut glyph, 0, 0, char.encode_utf8(&mut [0u8; 4]), scale, font, 15);
+            glyph
+        })
+    }
+}
@@ -0,0 +1,32 @@
+//! Widget themes
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// How the focused widget is highlighted
+pub enum FocusStyle {
+    /// Invert the widget's pixels
+    #[default]
+    Invert,
+    /// Draw a border around the widget
+    Outline,
+    /// Draw a line along the bottom of the widget
+    Underline,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Colours (0-15) and styles shared by widgets
+pub struct Theme {
+    /// Text and line colour
+    pub foreground: u8,
+    /// Fill colour
+    pub background: u8,
+    /// Focus highlight style
+    pub focus: FocusStyle,
+    /// Colour of focus outlines and underlines
+    pub focus_color: u8,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme { foreground: 15, background: 0, focus: FocusStyle::Invert, focus_color: 15 }
+    }
+}
@@ -0,0 +1,115 @@
+//! Transitions between full screen frames
+
+use std::{thread, time::{Duration, Instant}};
+
+use crate::{WS1in5, Error, Framebuffer};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// An effect used to move from one frame to another
+pub enum Transition {
+    /// Switch straight to the new frame
+    #[default]
+    Cut,
+    /// Fade between the frames by interpolating each pixel's grey level
+    Fade,
+    /// The new frame pushes the old frame out to the left
+    SlideLeft,
+    /// The new frame pushes the old frame out to the right
+    SlideRight,
+    /// The new frame pushes the old frame out the top
+    SlideUp,
+    /// The new frame pushes the old frame out the bottom
+    SlideDown,
+    /// The new frame is revealed from right to left
+    WipeLeft,
+    /// The new frame is revealed from left to right
+    WipeRight,
+    /// The new frame is revealed from bottom to top
+    WipeUp,
+    /// The new frame is revealed from top to bottom
+    WipeDown,
+}
+
+impl Transition {
+    /// The same transition moving the opposite way
+    pub fn reversed(&self) -> Transition {
+        match self {
+            Transition::SlideLeft => Transition::SlideRight,
+            Transition::SlideRight => Transition::SlideLeft,
+            Transition::SlideUp => Transition::SlideDown,
+            Transition::SlideDown => Transition::SlideUp,
+            Transition::WipeLeft => Transition::WipeRight,
+            Transition::WipeRight => Transition::WipeLeft,
+            Transition::WipeUp => Transition::WipeDown,
+            Transition::WipeDown => Transition::WipeUp,
+            other => *other,
+        }
+    }
+
+    /// Render the transition between two frames of the same size at a point in time, from 0 (the old frame) to 1 (the new frame)
+    pub fn frame(&self, from: &Framebuffer, to: &Framebuffer, t: f32) -> Framebuffer {
+        let t = if t.is_nan() { 1.0 } else { t.clamp(0.0, 1.0) };
+        let (width, height) = (to.width(), to.height());
+        let dx = (width as f32 * t).round() as usize;
+        let dy = (height as f32 * t).round() as usize;
+
+        let mut frame = Framebuffer::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let pixel = match self {
+                    Transition::Cut => to.get_pixel(x, y),
+                    Transition::Fade => {
+                        let (a, b) = (from.get_pixel(x, y) as f32, to.get_pixel(x, y) as f32);
+                        (a + (b - a) * t).round() as u8
+                    },
+                    Transition::SlideLeft => if x + dx < width { from.get_pixel(x + dx, y) } else { to.get_pixel(x + dx - width, y) },
+                    Transition::SlideRight => if x >= dx { from.get_pixel(x - dx, y) } else { to.get_pixel(x + width - dx, y) },
+                    Transition::SlideUp => if y + dy < height { from.get_pixel(x, y + dy) } else { to.get_pixel(x, y + dy - height) },
+                    Transition::SlideDown => if y >= dy { from.get_pixel(x, y - dy) } else { to.get_pixel(x, y + height - dy) },
+                    Transition::WipeLeft => if x + dx >= width { to.get_pixel(x, y) } else { from.get_pixel(x, y) },
+                    Transition::WipeRight => if x < dx { to.get_pixel(x, y) } else { from.get_pixel(x, y) },
+                    Transition::WipeUp => if y + dy >= height { to.get_pixel(x, y) } else { from.get_pixel(x, y) },
+                    Transition::WipeDown => if y < dy { to.get_pixel(x, y) } else { from.get_pixel(x, y) },
+                };
+                frame.set_pixel(x, y, pixel);
+            }
+        }
+        frame
+    }
+}
+
+impl WS1in5 {
+    /// Transition from what is on the screen to a new full screen frame over a duration
+    ///
+    /// Intermediate frames are timed against the clock, so a slow bus shows fewer frames rather than running long.
+    pub fn transition_to(&mut self, new_frame: &Framebuffer, transition: Transition, duration: Duration) -> Result<(), Error> {
+        let from = self.framebuffer().clone();
+        if transition != Transition::Cut && !duration.is_zero() {
+            let start = Instant::now();
+            loop {
+                let t = start.elapsed().as_secs_f32() / duration.as_secs_f32();
+                if t >= 1.0 {
+                    break
+                }
+                self.present(transition.frame(&from, new_frame, t))?;
+            }
+        }
+
+        self.present(new_frame.clone())?;
+        Ok(())
+    }
+
+    /// Transition between frames in a fixed number of steps with a delay after each intermediate step
+    pub fn transition_steps(&mut self, new_frame: &Framebuffer, transition: Transition, steps: usize, delay: Duration) -> Result<(), Error> {
+        let from = self.framebuffer().clone();
+        if transition != Transition::Cut {
+            for step in 1..steps {
+                self.present(transition.frame(&from, new_frame, step as f32 / steps as f32))?;
+                thread::sleep(delay);
+            }
+        }
+
+        self.present(new_frame.clone())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+//! Driving widgets from GPIO buttons and rotary encoders
+
+use std::{sync::mpsc::Receiver, thread, time::{Duration, Instant}};
+
+use rppal::gpio::{Gpio, InputPin};
+
+use crate::{WS1in5, Error, DriverError, widgets::{self, FocusGroup, TextInput}};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A navigation input
+pub enum InputEvent {
+    /// Move focus to the next widget
+    Next,
+    /// Move focus to the previous widget
+    Prev,
+    /// Select the focused widget
+    Select,
+    /// Leave the current screen
+    Back,
+}
+
+/// A source of navigation inputs, polled by a [`Ui`]
+pub trait Input: Send {
+    /// Add any inputs since the last poll to the events
+    fn poll(&mut self, events: &mut Vec<InputEvent>) -> Result<(), Error>;
+}
+
+/// Inputs sent from elsewhere, e.g. a network remote or a test
+impl Input for Receiver<InputEvent> {
+    fn poll(&mut self, events: &mut Vec<InputEvent>) -> Result<(), Error> {
+        events.extend(self.try_iter());
+        Ok(())
+    }
+}
+
+/// A push button between a GPIO pin and ground, debounced, sending an input when it's pressed
+pub struct GpioButton {
+    pin: InputPin,
+    event: InputEvent,
+    pressed: bool,
+    changed: Instant,
+    debounce: Duration,
+}
+
+impl GpioButton {
+    /// Create new on a pin (BCM numbering), with the pin's pull up enabled
+    pub fn new(pin: u8, event: InputEvent) -> Result<GpioButton, Error> {
+        let pin = Gpio::new().map_err(DriverError::GPIO)?.get(pin).map_err(DriverError::GPIO)?.into_input_pullup();
+        Ok(GpioButton { pressed: pin.is_low(), pin, event, changed: Instant::now(), debounce: Duration::from_millis(20) })
+    }
+
+    /// Ignore changes within a time of the last one, from the contacts bouncing
+    pub fn with_debounce(mut self, debounce: Duration) -> GpioButton {
+        self.debounce = debounce;
+        self
+    }
+}
+
+impl Input for GpioButton {
+    fn poll(&mut self, events: &mut Vec<InputEvent>) -> Result<(), Error> {
+        let pressed = self.pin.is_low();
+        if pressed != self.pressed && self.changed.elapsed() >= self.debounce {
+            self.pressed = pressed;
+            self.changed = Instant::now();
+            if pressed {
+                events.push(self.event);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A quadrature rotary encoder between two GPIO pins and ground, sending next and previous inputs for each detent
+pub struct RotaryEncoder {
+    a: InputPin,
+    b: InputPin,
+    state: u8,
+    steps: i8,
+    steps_per_detent: i8,
+}
+
+impl RotaryEncoder {
+    /// Create new on two pins (BCM numbering), with the pins' pull ups enabled
+    pub fn new(a: u8, b: u8) -> Result<RotaryEncoder, Error> {
+        let gpio = Gpio::new().map_err(DriverError::GPIO)?;
+        let a = gpio.get(a).map_err(DriverError::GPIO)?.into_input_pullup();
+        let b = gpio.get(b).map_err(DriverError::GPIO)?.into_input_pullup();
+        let state = (a.is_high() as u8) << 1 | b.is_high() as u8;
+        Ok(RotaryEncoder { a, b, state, steps: 0, steps_per_detent: 4 })
+    }
+
+    /// Set the number of quadrature steps per detent, most encoders have 4 but some have 2 or 1
+    pub fn with_steps_per_detent(mut self, steps: u8) -> RotaryEncoder {
+        self.steps_per_detent = steps.clamp(1, 4) as i8;
+        self
+    }
+}
+
+impl Input for RotaryEncoder {
+    fn poll(&mut self, events: &mut Vec<InputEvent>) -> Result<(), Error> {
+        let state = (self.a.is_high() as u8) << 1 | self.b.is_high() as u8;
+        // Gray code transitions, skipped states (from polling too slowly) count as no movement
+        self.steps += match (self.state, state) {
+            (0b00, 0b01) | (0b01, 0b11) | (0b11, 0b10) | (0b10, 0b00) => 1,
+            (0b00, 0b10) | (0b10, 0b11) | (0b11, 0b01) | (0b01, 0b00) => -1,
+            _ => 0,
+        };
+        self.state = state;
+
+        if self.steps >= self.steps_per_detent {
+            self.steps = 0;
+            events.push(InputEvent::Next);
+        } else if self.steps <= -self.steps_per_detent {
+            self.steps = 0;
+            events.push(InputEvent::Prev);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// Something the application needs to handle, from a [`Ui`]
+pub enum UiEvent {
+    /// A widget was selected, by its index in the focus group
+    Selected(usize),
+    /// Back was pressed
+    Back,
+}
+
+/// A retained UI loop, moving focus through a [`FocusGroup`] from polled inputs and redrawing the widgets that changed
+pub struct Ui<'a> {
+    group: FocusGroup<'a>,
+    inputs: Vec<Box<dyn Input>>,
+    poll_interval: Duration,
+    events: Vec<InputEvent>,
+}
+
+impl<'a> Ui<'a> {
+    /// Create new
+    pub fn new(group: FocusGroup<'a>) -> Ui<'a> {
+        Ui { group, inputs: vec![], poll_interval: Duration::from_millis(2), events: vec![] }
+    }
+
+    /// Add a source of inputs
+    pub fn with_input(mut self, input: Box<dyn Input>) -> Ui<'a> {
+        self.inputs.push(input);
+        self
+    }
+
+    /// Set how long to wait between polls, encoders miss steps if this is too long
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Ui<'a> {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// The widgets
+    pub fn group(&self) -> &FocusGroup<'a> {
+        &self.group
+    }
+
+    /// The widgets, e.g. to change one before redrawing it with [`FocusGroup::draw_index`]
+    pub fn group_mut(&mut self) -> &mut FocusGroup<'a> {
+        &mut self.group
+    }
+
+    /// Poll every input once and handle what came in. Returns the events for the application
+    pub fn poll(&mut self, display: &mut WS1in5) -> Result<Vec<UiEvent>, Error> {
+        self.events.clear();
+        for input in self.inputs.iter_mut() {
+            input.poll(&mut self.events)?;
+        }
+
+        let mut handled = vec![];
+        for i in 0..self.events.len() {
+            if let Some(event) = self.handle(display, self.events[i])? {
+                handled.push(event);
+            }
+        }
+        Ok(handled)
+    }
+
+    /// Handle an input, moving focus or selecting and redrawing the widgets that changed
+    pub fn handle(&mut self, display: &mut WS1in5, event: InputEvent) -> Result<Option<UiEvent>, Error> {
+        Ok(match event {
+            InputEvent::Next => {
+                self.group.next(display)?;
+                None
+            },
+            InputEvent::Prev => {
+                self.group.prev(display)?;
+                None
+            },
+            InputEvent::Select => self.group.select(display)?.map(UiEvent::Selected),
+            InputEvent::Back => Some(UiEvent::Back),
+        })
+    }
+
+    /// Show an on screen keyboard over the widgets and read text from the inputs. Next and previous move through the keys, select presses one
+    /// and back deletes the last character, or cancels when there's nothing to delete.
+    ///
+    /// Returns the text once the done key is pressed, or `None` if cancelled. Call [`FocusGroup::draw`] to show the widgets again
+    pub fn read_text(&mut self, display: &mut WS1in5, input: &mut TextInput) -> Result<Option<String>, Error> {
+        let theme = *self.group.theme();
+        widgets::draw_widget(display, input, &theme, false)?;
+        loop {
+            self.events.clear();
+            for source in self.inputs.iter_mut() {
+                source.poll(&mut self.events)?;
+            }
+
+            for event in self.events.iter() {
+                match event {
+                    InputEvent::Next => input.next(),
+                    InputEvent::Prev => input.prev(),
+                    InputEvent::Select => input.press(),
+                    InputEvent::Back => if !input.delete() {
+                        return Ok(None)
+                    },
+                }
+            }
+            if input.is_done() {
+                return Ok(Some(input.text().to_string()))
+            }
+            if !self.events.is_empty() {
+                widgets::draw_widget(display, input, &theme, false)?;
+            }
+            thread::sleep(self.poll_interval);
+        }
+    }
+
+    /// Draw every widget, then poll the inputs until the handler returns false
+    pub fn run<F: FnMut(&mut WS1in5, &mut FocusGroup<'a>, UiEvent) -> Result<bool, Error>>(&mut self, display: &mut WS1in5, mut handler: F) -> Result<(), Error> {
+        self.group.draw(display)?;
+        loop {
+            for event in self.poll(display)? {
+                if !handler(display, &mut self.group, event)? {
+                    return Ok(())
+                }
+            }
+            thread::sleep(self.poll_interval);
+        }
+    }
+}
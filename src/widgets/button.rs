@@ -0,0 +1,69 @@
+//! Push button
+
+use image::GrayImage;
+use rusttype::{Scale, Font};
+
+use crate::{Rect, Theme, text};
+use super::Widget;
+
+/// A focusable button with a centered text label
+pub struct Button<'a> {
+    bounds: Rect,
+    text: String,
+    scale: Scale,
+    font: Font<'a>,
+    presses: usize,
+}
+
+impl<'a> Button<'a> {
+    /// Create new
+    pub fn new(bounds: Rect, text: &str, scale: Scale, font: Font<'a>) -> Button<'a> {
+        Button { bounds, text: text.to_string(), scale, font, presses: 0 }
+    }
+
+    /// The button's text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Set the button's text
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+    }
+
+    /// Number of times the button has been selected
+    pub fn presses(&self) -> usize {
+        self.presses
+    }
+}
+
+impl<'a> Widget for Button<'a> {
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: Rect) {
+        self.bounds = bounds;
+    }
+
+    fn content_size(&self) -> (usize, usize) {
+        let (width, height) = text::text_size(&self.text, &self.scale, &self.font);
+        (width + 8, height + 4)
+    }
+
+    fn render(&self, canvas: &mut GrayImage, theme: &Theme) {
+        let (width, height) = text::text_size(&self.text, &self.scale, &self.font);
+        let x = (self.bounds.w as i32 - width as i32) / 2;
+        let y = (self.bounds.h as i32 - height as i32) / 2;
+        text::render_text(canvas, x, y, &self.text, &self.scale, &self.font, theme.foreground);
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn select(&mut self) -> bool {
+        self.presses += 1;
+        false
+    }
+}
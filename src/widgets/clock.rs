@@ -0,0 +1,224 @@
+//! Clock widgets which only redraw what changed
+
+use std::{time::{SystemTime, UNIX_EPOCH}, f32::consts::PI};
+
+use image::{GrayImage, Luma, imageops};
+use imageproc::drawing;
+use rusttype::{Scale, Font};
+
+use crate::{WS1in5, Error, text};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A time of day
+pub struct ClockTime {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+}
+
+impl ClockTime {
+    /// Create a new time, values are wrapped into range
+    pub fn new(hours: u8, minutes: u8, seconds: u8) -> ClockTime {
+        ClockTime { hours: hours % 24, minutes: minutes % 60, seconds: seconds % 60 }
+    }
+
+    /// The current system time, offset from UTC by a number of seconds
+    pub fn now(utc_offset: i64) -> ClockTime {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        let secs = (secs + utc_offset).rem_euclid(24 * 60 * 60);
+
+        ClockTime::new((secs / 3600) as u8, (secs / 60 % 60) as u8, (secs % 60) as u8)
+    }
+}
+
+/// A digital clock which only redraws the character cells that changed
+pub struct DigitalClock<'a> {
+    x: usize,
+    y: usize,
+    scale: Scale,
+    font: Font<'a>,
+    seconds: bool,
+    twelve_hour: bool,
+    last: Option<Vec<char>>,
+}
+
+impl<'a> DigitalClock<'a> {
+    /// Create a new clock at the specified coord (x should be even)
+    pub fn new(x: usize, y: usize, scale: Scale, font: Font<'a>) -> DigitalClock<'a> {
+        DigitalClock { x, y, scale, font, seconds: true, twelve_hour: false, last: None }
+    }
+
+    /// Set whether seconds are shown
+    pub fn with_seconds(mut self, seconds: bool) -> DigitalClock<'a> {
+        self.seconds = seconds;
+        self.last = None;
+        self
+    }
+
+    /// Set whether 12 hour time is used
+    pub fn with_twelve_hour(mut self, twelve_hour: bool) -> DigitalClock<'a> {
+        self.twelve_hour = twelve_hour;
+        self.last = None;
+        self
+    }
+
+    /// Format a time the way the clock displays it
+    pub fn format(&self, time: &ClockTime) -> String {
+        let hours = if self.twelve_hour {
+            match time.hours % 12 {
+                0 => format!("{:>2}", 12),
+                h => format!("{:>2}", h),
+            }
+        } else {
+            format!("{:02}", time.hours)
+        };
+
+        if self.seconds {
+            format!("{}:{:02}:{:02}", hours, time.minutes, time.seconds)
+        } else {
+            format!("{}:{:02}", hours, time.minutes)
+        }
+    }
+
+    /// Size of the clock on screen
+    pub fn size(&self) -> (usize, usize) {
+        text::text_size(&self.format(&ClockTime::new(0, 0, 0)), &self.scale, &self.font)
+    }
+
+    /// Force the whole clock to be redrawn on the next update
+    pub fn invalidate(&mut self) {
+        self.last = None;
+    }
+
+    /// Draw the time, only rewriting the characters that changed since the last update. Returns the number of characters drawn
+    pub fn update(&mut self, display: &mut WS1in5, time: &ClockTime) -> Result<usize, Error> {
+        let text: Vec<char> = self.format(time).chars().collect();
+        let (_, _, char_width) = text::text_size_full("_", &self.scale, &self.font);
+
+        let mut drawn = 0;
+        for (i, char) in text.iter().enumerate() {
+            let changed = match &self.last {
+                Some(last) => last.get(i) != Some(char),
+                None => true,
+            };
+
+            if changed {
+                display.draw_text(self.x + i * char_width, self.y, &char.to_string(), &self.scale, &self.font)?;
+                drawn += 1;
+            }
+        }
+
+        self.last = Some(text);
+        Ok(drawn)
+    }
+
+    /// Draw the current system time (see [`ClockTime::now`])
+    pub fn tick(&mut self, display: &mut WS1in5, utc_offset: i64) -> Result<usize, Error> {
+        self.update(display, &ClockTime::now(utc_offset))
+    }
+}
+
+/// An analog clock which only redraws the part of the face that changed
+pub struct AnalogClock {
+    x: usize,
+    y: usize,
+    size: usize,
+    seconds: bool,
+    last: Option<GrayImage>,
+}
+
+impl AnalogClock {
+    /// Create a new clock at the specified coord with a diameter (x and size are rounded up to be even)
+    pub fn new(x: usize, y: usize, size: usize) -> AnalogClock {
+        AnalogClock { x: x + x % 2, y, size: size + size % 2, seconds: true, last: None }
+    }
+
+    /// Set whether the second hand is shown
+    pub fn with_seconds(mut self, seconds: bool) -> AnalogClock {
+        self.seconds = seconds;
+        self.last = None;
+        self
+    }
+
+    /// Force the whole clock to be redrawn on the next update
+    pub fn invalidate(&mut self) {
+        self.last = None;
+    }
+
+    /// Render the clock face for a time (pixel values 0-15)
+    pub fn render(&self, time: &ClockTime) -> GrayImage {
+        let mut image = GrayImage::new(self.size as u32, self.size as u32);
+        let radius = (self.size / 2) as f32 - 1.0;
+        let center = (self.size as f32 / 2.0, self.size as f32 / 2.0);
+
+        drawing::draw_hollow_circle_mut(&mut image, (center.0 as i32, center.1 as i32), radius as i32, Luma([8]));
+        for tick in 0..12 {
+            let angle = tick as f32 / 12.0 * 2.0 * PI;
+            drawing::draw_line_segment_mut(
+                &mut image,
+                hand_point(center, angle, radius * 0.85),
+                hand_point(center, angle, radius),
+                Luma([8]),
+            );
+        }
+
+        let minutes = time.minutes as f32 + time.seconds as f32 / 60.0;
+        let hours = (time.hours % 12) as f32 + minutes / 60.0;
+        drawing::draw_line_segment_mut(&mut image, center, hand_point(center, hours / 12.0 * 2.0 * PI, radius * 0.5), Luma([15]));
+        drawing::draw_line_segment_mut(&mut image, center, hand_point(center, minutes / 60.0 * 2.0 * PI, radius * 0.75), Luma([15]));
+        if self.seconds {
+            drawing::draw_line_segment_mut(&mut image, center, hand_point(center, time.seconds as f32 / 60.0 * 2.0 * PI, radius * 0.8), Luma([6]));
+        }
+
+        image
+    }
+
+    /// Draw the time, only rewriting the smallest area containing every changed pixel. Returns true if anything was drawn
+    pub fn update(&mut self, display: &mut WS1in5, time: &ClockTime) -> Result<bool, Error> {
+        let image = self.render(time);
+
+        let bounds = match &self.last {
+            Some(last) => changed_bounds(last, &image),
+            None => Some((0, 0, self.size, self.size)),
+        };
+
+        if let Some((x, y, width, height)) = bounds {
+            let section = imageops::crop_imm(&image, x as u32, y as u32, width as u32, height as u32).to_image();
+            display.draw_image(self.x + x, self.y + y, &section)?;
+        }
+
+        self.last = Some(image);
+        Ok(bounds.is_some())
+    }
+
+    /// Draw the current system time (see [`ClockTime::now`])
+    pub fn tick(&mut self, display: &mut WS1in5, utc_offset: i64) -> Result<bool, Error> {
+        self.update(display, &ClockTime::now(utc_offset))
+    }
+}
+
+fn hand_point(center: (f32, f32), angle: f32, length: f32) -> (f32, f32) {
+    (center.0 + angle.sin() * length, center.1 - angle.cos() * length)
+}
+
+/// Bounding box of the pixels that differ between two images of the same size, with an even x and width
+fn changed_bounds(a: &GrayImage, b: &GrayImage) -> Option<(usize, usize, usize, usize)> {
+    let mut bounds: Option<(u32, u32, u32, u32)> = None;
+    for (x, y, pixel) in b.enumerate_pixels() {
+        if a.get_pixel(x, y) != pixel {
+            bounds = Some(match bounds {
+                Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+                None => (x, y, x, y),
+            });
+        }
+    }
+
+    bounds.map(|(x0, y0, x1, y1)| {
+        let x0 = x0 - x0 % 2;
+        let x1 = (x1 + 1 + (x1 + 1) % 2).min(b.width());
+        (x0 as usize, y0 as usize, (x1 - x0) as usize, (y1 + 1 - y0) as usize)
+    })
+}
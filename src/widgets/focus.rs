@@ -0,0 +1,133 @@
+//! Focus management
+
+use crate::{WS1in5, Error, Theme};
+use super::{Widget, draw_widget};
+
+/// A set of widgets on a screen with a single focused widget, traversed with next/prev/select inputs
+pub struct FocusGroup<'a> {
+    widgets: Vec<Box<dyn Widget + 'a>>,
+    focus: Option<usize>,
+    theme: Theme,
+}
+
+impl<'a> FocusGroup<'a> {
+    /// Create new
+    pub fn new(theme: Theme) -> FocusGroup<'a> {
+        FocusGroup { widgets: vec![], focus: None, theme }
+    }
+
+    /// Add a widget, widgets are traversed in the order they are added. Returns the widget's index
+    ///
+    /// The first focusable widget added takes focus.
+    pub fn add(&mut self, widget: Box<dyn Widget + 'a>) -> usize {
+        let index = self.widgets.len();
+        if self.focus.is_none() && widget.focusable() {
+            self.focus = Some(index);
+        }
+        self.widgets.push(widget);
+        index
+    }
+
+    /// The theme used to draw the widgets
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Set the theme, call [`FocusGroup::draw`] to apply it
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// Number of widgets in the group
+    pub fn len(&self) -> usize {
+        self.widgets.len()
+    }
+
+    /// Returns true if the group has no widgets
+    pub fn is_empty(&self) -> bool {
+        self.widgets.is_empty()
+    }
+
+    /// Get a widget
+    pub fn widget(&self, index: usize) -> Option<&(dyn Widget + 'a)> {
+        self.widgets.get(index).map(|w| w.as_ref())
+    }
+
+    /// Get a widget
+    pub fn widget_mut(&mut self, index: usize) -> Option<&mut (dyn Widget + 'a)> {
+        self.widgets.get_mut(index).map(|w| w.as_mut())
+    }
+
+    /// Index of the focused widget
+    pub fn focused(&self) -> Option<usize> {
+        self.focus
+    }
+
+    /// Draw every widget
+    pub fn draw(&self, display: &mut WS1in5) -> Result<(), Error> {
+        for index in 0..self.widgets.len() {
+            self.draw_index(display, index)?;
+        }
+        Ok(())
+    }
+
+    /// Redraw a single widget, e.g. after changing it through [`FocusGroup::widget_mut`]
+    pub fn draw_index(&self, display: &mut WS1in5, index: usize) -> Result<(), Error> {
+        match self.widgets.get(index) {
+            Some(widget) => draw_widget(display, widget.as_ref(), &self.theme, self.focus == Some(index)),
+            None => Ok(()),
+        }
+    }
+
+    /// Move focus to a widget, redrawing the widgets whose focus changed. Returns false if the widget can't take focus
+    pub fn set_focus(&mut self, display: &mut WS1in5, index: usize) -> Result<bool, Error> {
+        if !self.widgets.get(index).map(|w| w.focusable()).unwrap_or(false) {
+            return Ok(false)
+        }
+
+        if self.focus != Some(index) {
+            let previous = self.focus.replace(index);
+            if let Some(previous) = previous {
+                self.draw_index(display, previous)?;
+            }
+            self.draw_index(display, index)?;
+        }
+        Ok(true)
+    }
+
+    /// Move focus to the next focusable widget, wrapping around. Returns the newly focused widget
+    pub fn next(&mut self, display: &mut WS1in5) -> Result<Option<usize>, Error> {
+        let len = self.widgets.len();
+        let start = self.focus.map(|f| f + 1).unwrap_or(0);
+        let next = (0..len).map(|i| (start + i) % len).find(|&i| self.widgets[i].focusable());
+        self.move_focus(display, next)
+    }
+
+    /// Move focus to the previous focusable widget, wrapping around. Returns the newly focused widget
+    pub fn prev(&mut self, display: &mut WS1in5) -> Result<Option<usize>, Error> {
+        let len = self.widgets.len();
+        let start = self.focus.unwrap_or(0) + len;
+        let prev = (1..=len).map(|i| (start - i) % len).find(|&i| self.widgets[i].focusable());
+        self.move_focus(display, prev)
+    }
+
+    /// Select the focused widget. Returns the selected widget
+    pub fn select(&mut self, display: &mut WS1in5) -> Result<Option<usize>, Error> {
+        let index = match self.focus {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+
+        if self.widgets[index].select() {
+            self.draw_index(display, index)?;
+        }
+        Ok(Some(index))
+    }
+
+    fn move_focus(&mut self, display: &mut WS1in5, index: Option<usize>) -> Result<Option<usize>, Error> {
+        if let Some(index) = index {
+            self.set_focus(display, index)?;
+        }
+        Ok(self.focus)
+    }
+}
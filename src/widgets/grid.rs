@@ -0,0 +1,125 @@
+//! Dividing an area into rows and columns of cells
+
+use crate::{WS1in5, Error, Framebuffer, Rect};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// An area divided into equal rows and columns, e.g. a dashboard with a sensor in each quadrant
+///
+/// Column boundaries are rounded down to even x, so cells start on even columns as long as the separators are an even thickness.
+pub struct Grid {
+    area: Rect,
+    columns: usize,
+    rows: usize,
+    separator: usize,
+    separator_level: u8,
+    padding: usize,
+}
+
+impl Grid {
+    /// Create new, dividing an area into a number of columns and rows (at least one each)
+    pub const fn new(area: Rect, columns: usize, rows: usize) -> Grid {
+        let columns = if columns == 0 { 1 } else { columns };
+        let rows = if rows == 0 { 1 } else { rows };
+        Grid { area, columns, rows, separator: 0, separator_level: 0, padding: 0 }
+    }
+
+    /// Draw lines of a thickness and level (0-15) between the cells, 0 thickness draws none
+    pub const fn with_separators(mut self, thickness: usize, level: u8) -> Grid {
+        self.separator = thickness;
+        self.separator_level = level;
+        self
+    }
+
+    /// Leave space around the inside of each cell
+    pub const fn with_padding(mut self, padding: usize) -> Grid {
+        self.padding = padding;
+        self
+    }
+
+    /// The area divided up
+    pub fn area(&self) -> Rect {
+        self.area
+    }
+
+    /// Number of columns
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Number of rows
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Area of a cell inside the separators and padding, None if it's outside the grid
+    pub fn cell(&self, column: usize, row: usize) -> Option<Rect> {
+        self.span(column, row, 1, 1)
+    }
+
+    /// Area of a block of cells joined together, e.g. a header across the top row. None if any of it is outside the grid
+    pub fn span(&self, column: usize, row: usize, columns: usize, rows: usize) -> Option<Rect> {
+        if columns == 0 || rows == 0 || column + columns > self.columns || row + rows > self.rows {
+            return None
+        }
+
+        let (x, right) = self.track(column, column + columns, self.columns, self.area.x, self.area.w, true);
+        let (y, bottom) = self.track(row, row + rows, self.rows, self.area.y, self.area.h, false);
+        let (x, y) = (x + self.padding, y + self.padding);
+        let (right, bottom) = (right.saturating_sub(self.padding).max(x), bottom.saturating_sub(self.padding).max(y));
+        Some(Rect::new(x, y, right - x, bottom - y))
+    }
+
+    /// Every cell, row by row
+    pub fn cells(&self) -> impl Iterator<Item = Rect> + '_ {
+        (0..self.rows).flat_map(move |row| (0..self.columns).filter_map(move |column| self.cell(column, row)))
+    }
+
+    /// Areas of the separators between the cells
+    pub fn separators(&self) -> Vec<Rect> {
+        let mut separators = Vec::new();
+        if self.separator == 0 {
+            return separators
+        }
+
+        for column in 1..self.columns {
+            let x = boundary(column, self.columns, self.area.x, self.area.w, true);
+            separators.push(Rect::new(x, self.area.y, self.separator.min(self.area.right().saturating_sub(x)), self.area.h));
+        }
+        for row in 1..self.rows {
+            let y = boundary(row, self.rows, self.area.y, self.area.h, false);
+            separators.push(Rect::new(self.area.x, y, self.area.w, self.separator.min(self.area.bottom().saturating_sub(y))));
+        }
+        separators
+    }
+
+    /// Draw the separators onto a framebuffer
+    pub fn draw_separators(&self, framebuffer: &mut Framebuffer) {
+        for separator in self.separators() {
+            framebuffer.fill_rect(separator, self.separator_level);
+        }
+    }
+
+    /// Start and end of a run of columns or rows, inside the separators either side
+    fn track(&self, first: usize, end: usize, count: usize, start: usize, length: usize, even: bool) -> (usize, usize) {
+        let from = if first == 0 { start } else { boundary(first, count, start, length, even) + self.separator };
+        let to = if end == count { start + length } else { boundary(end, count, start, length, even) };
+        (from.min(to), to)
+    }
+}
+
+impl WS1in5 {
+    /// Draw a grid's separators and write them to the screen
+    pub fn draw_grid(&mut self, grid: &Grid) -> Result<(), Error> {
+        grid.draw_separators(self.framebuffer_mut());
+        for separator in grid.separators() {
+            self.flush_rect(separator)?;
+        }
+        Ok(())
+    }
+}
+
+/// Where the boundary before a column or row starts
+fn boundary(index: usize, count: usize, start: usize, length: usize, even: bool) -> usize {
+    let position = start + length * index / count;
+    if even { position - position % 2 } else { position }
+}
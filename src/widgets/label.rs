@@ -0,0 +1,50 @@
+//! Text label
+
+use image::GrayImage;
+use rusttype::{Scale, Font};
+
+use crate::{Rect, Theme, text};
+use super::Widget;
+
+/// A single line of text
+pub struct Label<'a> {
+    bounds: Rect,
+    text: String,
+    scale: Scale,
+    font: Font<'a>,
+}
+
+impl<'a> Label<'a> {
+    /// Create new
+    pub fn new(bounds: Rect, text: &str, scale: Scale, font: Font<'a>) -> Label<'a> {
+        Label { bounds, text: text.to_string(), scale, font }
+    }
+
+    /// The label's text
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Set the label's text
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+    }
+}
+
+impl<'a> Widget for Label<'a> {
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: Rect) {
+        self.bounds = bounds;
+    }
+
+    fn content_size(&self) -> (usize, usize) {
+        text::text_size(&self.text, &self.scale, &self.font)
+    }
+
+    fn render(&self, canvas: &mut GrayImage, theme: &Theme) {
+        text::render_text(canvas, 0, 0, &self.text, &self.scale, &self.font, theme.foreground);
+    }
+}
@@ -0,0 +1,314 @@
+//! Flexbox style layout, computing widget bounds from rows and columns
+
+use crate::{WS1in5, Error, Rect};
+use super::FocusGroup;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Which way a layout's children are laid out
+pub enum Direction {
+    /// Left to right
+    #[default]
+    Row,
+    /// Top to bottom
+    Column,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How much of a layout's length a child takes, children always fill the layout's other dimension
+pub enum Size {
+    /// A fixed number of pixels
+    Fixed(usize),
+    /// A share of the space left over after fixed and content sized children, weighted against the other flex children
+    Flex(usize),
+    /// The size of the widget's content, see [`Widget::content_size`](super::Widget::content_size)
+    Content,
+}
+
+enum Child {
+    Widget(usize),
+    Layout(Layout),
+}
+
+/// A row or column of widgets (by their index in a [`FocusGroup`]) and nested layouts
+pub struct Layout {
+    direction: Direction,
+    padding: usize,
+    gap: usize,
+    children: Vec<(Child, Size)>,
+}
+
+impl Layout {
+    /// Create new, laying out children left to right
+    pub fn row() -> Layout {
+        Layout::new(Direction::Row)
+    }
+
+    /// Create new, laying out children top to bottom
+    pub fn column() -> Layout {
+        Layout::new(Direction::Column)
+    }
+
+    /// Create new
+    pub fn new(direction: Direction) -> Layout {
+        Layout { direction, padding: 0, gap: 0, children: vec![] }
+    }
+
+    /// Leave space around the inside of the layout
+    pub fn with_padding(mut self, padding: usize) -> Layout {
+        self.padding = padding;
+        self
+    }
+
+    /// Leave space between children
+    pub fn with_gap(mut self, gap: usize) -> Layout {
+        self.gap = gap;
+        self
+    }
+
+    /// Add a widget by its index in the focus group
+    pub fn widget(mut self, index: usize, size: Size) -> Layout {
+        self.children.push((Child::Widget(index), size));
+        self
+    }
+
+    /// Add a nested layout
+    pub fn layout(mut self, layout: Layout, size: Size) -> Layout {
+        self.children.push((Child::Layout(layout), size));
+        self
+    }
+
+    /// Size of everything in the layout at its content size
+    pub fn content_size(&self, group: &FocusGroup) -> (usize, usize) {
+        let sizes = self.children.iter().map(|(child, size)| {
+            let (width, height) = child_content_size(child, group);
+            match (size, self.direction) {
+                (Size::Fixed(length), Direction::Row) => (*length, height),
+                (Size::Fixed(length), Direction::Column) => (width, *length),
+                _ => (width, height),
+            }
+        });
+
+        let gaps = self.gap * self.children.len().saturating_sub(1);
+        let (main, cross) = sizes.fold((gaps, 0), |(main, cross), (width, height)| match self.direction {
+            Direction::Row => (main + width, cross.max(height)),
+            Direction::Column => (main + height, cross.max(width)),
+        });
+        let (width, height) = match self.direction {
+            Direction::Row => (main, cross),
+            Direction::Column => (cross, main),
+        };
+        (width + self.padding * 2, height + self.padding * 2)
+    }
+
+    /// Work out the bounds of every widget in the layout when it fills an area, as (index, bounds).
+    ///
+    /// x and the width are shrunk to be even, as the screen is written two pixels at a time
+    pub fn compute(&self, area: Rect, group: &FocusGroup) -> Vec<(usize, Rect)> {
+        let mut bounds = vec![];
+        self.compute_into(area, group, &mut bounds);
+        bounds
+    }
+
+    fn compute_into(&self, area: Rect, group: &FocusGroup, bounds: &mut Vec<(usize, Rect)>) {
+        let inner = Rect::new(
+            area.x + self.padding,
+            area.y + self.padding,
+            area.w.saturating_sub(self.padding * 2),
+            area.h.saturating_sub(self.padding * 2),
+        );
+        let (length, cross) = match self.direction {
+            Direction::Row => (inner.w, inner.h),
+            Direction::Column => (inner.h, inner.w),
+        };
+
+        let lengths: Vec<Option<usize>> = self.children.iter().map(|(child, size)| match size {
+            Size::Fixed(length) => Some(*length),
+            Size::Flex(_) => None,
+            Size::Content => Some(match (child_content_size(child, group), self.direction) {
+                ((width, _), Direction::Row) => width,
+                ((_, height), Direction::Column) => height,
+            }),
+        }).collect();
+
+        let gaps = self.gap * self.children.len().saturating_sub(1);
+        let used: usize = lengths.iter().flatten().sum();
+        let free = length.saturating_sub(used + gaps);
+        let weights: usize = self.children.iter().map(|(_, size)| match size {
+            Size::Flex(weight) => *weight,
+            _ => 0,
+        }).sum();
+
+        // Flex children share what's left, the last one takes any rounding
+        let mut position = 0;
+        let mut flexed = 0;
+        let mut weight_so_far = 0;
+        for ((child, size), fixed) in self.children.iter().zip(lengths) {
+            let child_length = match (fixed, size) {
+                (Some(length), _) => length,
+                (None, Size::Flex(weight)) => {
+                    weight_so_far += weight;
+                    let share = free * weight_so_far / weights.max(1);
+                    let child_length = share - flexed;
+                    flexed = share;
+                    child_length
+                },
+                (None, _) => 0,
+            };
+            let child_length = child_length.min(length.saturating_sub(position));
+
+            let rect = match self.direction {
+                Direction::Row => Rect::new(inner.x + position, inner.y, child_length, cross),
+                Direction::Column => Rect::new(inner.x, inner.y + position, cross, child_length),
+            };
+            match child {
+                Child::Widget(index) => bounds.push((*index, shrink_even(rect))),
+                Child::Layout(layout) => layout.compute_into(rect, group, bounds),
+            }
+            position = (position + child_length + self.gap).min(length);
+        }
+    }
+}
+
+fn child_content_size(child: &Child, group: &FocusGroup) -> (usize, usize) {
+    match child {
+        Child::Widget(index) => group.widget(*index).map(|widget| widget.content_size()).unwrap_or((0, 0)),
+        Child::Layout(layout) => layout.content_size(group),
+    }
+}
+
+/// Shrink a rectangle horizontally so x and the width are even
+fn shrink_even(rect: Rect) -> Rect {
+    let x = rect.x + rect.x % 2;
+    let right = rect.right() - rect.right() % 2;
+    Rect::new(x, rect.y, right.saturating_sub(x), rect.h)
+}
+
+impl<'a> FocusGroup<'a> {
+    /// Lay the widgets out in an area, then clear the areas they moved out of and redraw only the widgets that moved or were uncovered.
+    /// Call again after changing a widget's content size, e.g. a label's text. Returns the widgets redrawn
+    pub fn apply_layout(&mut self, display: &mut WS1in5, layout: &Layout, area: Rect) -> Result<Vec<usize>, Error> {
+        let mut moved = vec![];
+        let mut vacated = vec![];
+        for (index, bounds) in layout.compute(area, self) {
+            let Some(widget) = self.widget_mut(index) else {
+                continue
+            };
+            let previous = widget.bounds();
+            widget.set_bounds(bounds);
+            if widget.bounds() != previous {
+                vacated.push(previous);
+                moved.push(index);
+            }
+        }
+
+        let background = self.theme().background;
+        for rect in &vacated {
+            display.fill_rect(*rect, background)?;
+        }
+
+        let mut redrawn = vec![];
+        for index in 0..self.len() {
+            let bounds = self.widget(index).map(|widget| widget.bounds()).unwrap_or_default();
+            if moved.contains(&index) || vacated.iter().any(|rect| rect.intersects(&bounds)) {
+                self.draw_index(display, index)?;
+                redrawn.push(index);
+            }
+        }
+        Ok(redrawn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::GrayImage;
+
+    use super::*;
+    use crate::{Theme, widgets::Widget};
+
+    struct Block {
+        bounds: Rect,
+        content: (usize, usize),
+    }
+
+    impl Widget for Block {
+        fn bounds(&self) -> Rect {
+            self.bounds
+        }
+
+        fn set_bounds(&mut self, bounds: Rect) {
+            self.bounds = bounds;
+        }
+
+        fn content_size(&self) -> (usize, usize) {
+            self.content
+        }
+
+        fn render(&self, _canvas: &mut GrayImage, _theme: &Theme) {}
+    }
+
+    fn group(contents: &[(usize, usize)]) -> FocusGroup<'static> {
+        let mut group = FocusGroup::new(Theme::default());
+        for content in contents {
+            group.add(Box::new(Block { bounds: Rect::default(), content: *content }));
+        }
+        group
+    }
+
+    #[test]
+    fn flex_children_share_what_fixed_children_leave() {
+        let group = group(&[(0, 0); 3]);
+        let layout = Layout::row().widget(0, Size::Fixed(20)).widget(1, Size::Flex(1)).widget(2, Size::Flex(3));
+        assert_eq!(layout.compute(Rect::new(0, 0, 100, 10), &group), vec![
+            (0, Rect::new(0, 0, 20, 10)),
+            (1, Rect::new(20, 0, 20, 10)),
+            (2, Rect::new(40, 0, 60, 10)),
+        ]);
+    }
+
+    #[test]
+    fn content_children_take_their_content_size() {
+        let group = group(&[(0, 12), (0, 0)]);
+        let layout = Layout::column().with_padding(2).widget(0, Size::Content).widget(1, Size::Flex(1));
+        assert_eq!(layout.compute(Rect::new(0, 0, 20, 50), &group), vec![
+            (0, Rect::new(2, 2, 16, 12)),
+            (1, Rect::new(2, 14, 16, 34)),
+        ]);
+    }
+
+    #[test]
+    fn last_flex_child_takes_the_remainder() {
+        let group = group(&[(0, 0); 3]);
+        let layout = Layout::column().widget(0, Size::Flex(1)).widget(1, Size::Flex(1)).widget(2, Size::Flex(1));
+        let heights: Vec<usize> = layout.compute(Rect::new(0, 0, 10, 100), &group).iter().map(|(_, rect)| rect.h).collect();
+        assert_eq!(heights, vec![33, 33, 34]);
+    }
+
+    #[test]
+    fn children_past_the_end_get_no_space() {
+        let group = group(&[(0, 0); 3]);
+        let layout = Layout::column().widget(0, Size::Fixed(20)).widget(1, Size::Fixed(20)).widget(2, Size::Flex(1));
+        assert_eq!(layout.compute(Rect::new(0, 0, 10, 30), &group), vec![
+            (0, Rect::new(0, 0, 10, 20)),
+            (1, Rect::new(0, 20, 10, 10)),
+            (2, Rect::new(0, 30, 10, 0)),
+        ]);
+    }
+
+    #[test]
+    fn empty_area_gives_empty_bounds() {
+        let group = group(&[(8, 8); 2]);
+        let layout = Layout::row().with_padding(4).with_gap(2).widget(0, Size::Flex(1)).widget(1, Size::Content);
+        for (_, rect) in layout.compute(Rect::new(0, 0, 0, 0), &group) {
+            assert!(rect.is_empty());
+        }
+    }
+
+    #[test]
+    fn zero_weights_get_no_space() {
+        let group = group(&[(0, 0); 2]);
+        let layout = Layout::column().widget(0, Size::Flex(0)).widget(1, Size::Flex(0));
+        for (_, rect) in layout.compute(Rect::new(0, 0, 10, 100), &group) {
+            assert_eq!(rect.h, 0);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Widgets
+
+use image::{GrayImage, Luma};
+use imageproc::{drawing, rect::Rect as DrawRect};
+
+use crate::{WS1in5, Error, Rect, Theme, FocusStyle};
+
+pub mod button;
+pub mod clock;
+pub mod focus;
+pub mod grid;
+pub mod label;
+pub mod layout;
+pub mod plot;
+pub mod progress;
+pub mod text_input;
+
+pub use button::Button;
+pub use clock::{ClockTime, DigitalClock, AnalogClock};
+pub use focus::FocusGroup;
+pub use grid::Grid;
+pub use label::Label;
+pub use layout::{Layout, Direction, Size};
+pub use plot::Plot;
+pub use progress::ProgressBar;
+pub use text_input::TextInput;
+
+/// A widget which can be rendered into its bounds
+pub trait Widget {
+    /// Position and size of the widget on screen
+    fn bounds(&self) -> Rect;
+
+    /// Move or resize the widget, e.g. by a [`Layout`]. Widgets with fixed bounds ignore this
+    fn set_bounds(&mut self, _bounds: Rect) {}
+
+    /// Size the widget's content needs, used for [`Size::Content`] in a layout
+    fn content_size(&self) -> (usize, usize) {
+        let bounds = self.bounds();
+        (bounds.w, bounds.h)
+    }
+
+    /// Render the widget onto a canvas the size of its bounds, already filled with the theme's background
+    fn render(&self, canvas: &mut GrayImage, theme: &Theme);
+
+    /// Returns true if the widget can take focus
+    fn focusable(&self) -> bool {
+        false
+    }
+
+    /// Called when the widget is selected while focused, returns true if the widget needs to be redrawn
+    fn select(&mut self) -> bool {
+        false
+    }
+}
+
+/// Render a widget, with the theme's focus highlight if focused
+pub fn render_widget(widget: &dyn Widget, theme: &Theme, focused: bool) -> GrayImage {
+    let bounds = widget.bounds();
+    let mut canvas = GrayImage::from_pixel(bounds.w as u32, bounds.h as u32, Luma([theme.background]));
+    widget.render(&mut canvas, theme);
+
+    if focused && !bounds.is_empty() {
+        match theme.focus {
+            FocusStyle::Invert => canvas.pixels_mut().for_each(|p| p.0[0] = 15 - p.0[0].min(15)),
+            FocusStyle::Outline => drawing::draw_hollow_rect_mut(
+                &mut canvas,
+                DrawRect::at(0, 0).of_size(bounds.w as u32, bounds.h as u32),
+                Luma([theme.focus_color])
+            ),
+            FocusStyle::Underline => drawing::draw_line_segment_mut(
+                &mut canvas,
+                (0.0, bounds.h as f32 - 1.0),
+                (bounds.w as f32 - 1.0, bounds.h as f32 - 1.0),
+                Luma([theme.focus_color])
+            ),
+        }
+    }
+
+    canvas
+}
+
+/// Draw a widget to the screen, with the theme's focus highlight if focused
+pub fn draw_widget(display: &mut WS1in5, widget: &dyn Widget, theme: &Theme, focused: bool) -> Result<(), Error> {
+    let bounds = widget.bounds();
+    if bounds.is_empty() {
+        return Ok(())
+    }
+
+    let canvas = render_widget(widget, theme, focused);
+    display.draw_image(bounds.x, bounds.y, &canvas)?;
+    Ok(())
+}
@@ -0,0 +1,224 @@
+//! Line plot of samples over a rolling time window
+
+use std::{collections::VecDeque, time::{Duration, Instant}};
+
+use image::{GrayImage, Luma, imageops};
+use imageproc::drawing;
+use rusttype::{Scale, Font};
+
+use crate::{WS1in5, Error, Rect, Theme, text};
+use super::{Widget, render_widget};
+
+struct Series {
+    samples: VecDeque<(Instant, f64)>,
+    level: u8,
+}
+
+/// A line plot of one or more series over the last stretch of time, with a y axis scaled to the samples or fixed, ticks and optional labels
+///
+/// Samples older than the window are dropped as new ones arrive. [`Plot::update`] redraws only the pixels that changed since the last update.
+pub struct Plot<'a> {
+    bounds: Rect,
+    window: Duration,
+    series: Vec<Series>,
+    range: Option<(f64, f64)>,
+    ticks: usize,
+    labels: Option<(Scale, Font<'a>)>,
+    last: Option<GrayImage>,
+}
+
+impl<'a> Plot<'a> {
+    /// Create new, showing the last minute, without series
+    pub fn new(bounds: Rect) -> Plot<'a> {
+        Plot { bounds, window: Duration::from_secs(60), series: vec![], range: None, ticks: 4, labels: None, last: None }
+    }
+
+    /// Set how far back the plot shows
+    pub fn with_window(mut self, window: Duration) -> Plot<'a> {
+        self.window = window;
+        self
+    }
+
+    /// Fix the y axis to a range rather than scaling it to the samples
+    pub fn with_range(mut self, min: f64, max: f64) -> Plot<'a> {
+        self.range = Some((min, max));
+        self
+    }
+
+    /// Set roughly how many ticks the y axis has, they're placed at round numbers
+    pub fn with_ticks(mut self, ticks: usize) -> Plot<'a> {
+        self.ticks = ticks.max(1);
+        self
+    }
+
+    /// Label the y axis ticks, a small font (e.g. 8px) suits the screen
+    pub fn with_labels(mut self, scale: Scale, font: Font<'a>) -> Plot<'a> {
+        self.labels = Some((scale, font));
+        self
+    }
+
+    /// Add a series drawn in a level (0-15). Returns the series' index
+    pub fn add_series(&mut self, level: u8) -> usize {
+        self.series.push(Series { samples: VecDeque::new(), level: level.min(15) });
+        self.series.len() - 1
+    }
+
+    /// Add a sample to a series, taken now
+    pub fn push(&mut self, series: usize, value: f64) {
+        self.push_at(series, Instant::now(), value);
+    }
+
+    /// Add a sample to a series taken at a time, samples must be added in time order. Unknown series are ignored
+    pub fn push_at(&mut self, series: usize, time: Instant, value: f64) {
+        let window = self.window;
+        let Some(series) = self.series.get_mut(series) else { return };
+        series.samples.push_back((time, value));
+        while series.samples.front().is_some_and(|(first, _)| time.saturating_duration_since(*first) > window) {
+            series.samples.pop_front();
+        }
+    }
+
+    /// Remove every sample
+    pub fn clear(&mut self) {
+        self.series.iter_mut().for_each(|series| series.samples.clear());
+    }
+
+    /// Forget what was last drawn, so the next [`Plot::update`] draws the whole plot
+    pub fn invalidate(&mut self) {
+        self.last = None;
+    }
+
+    /// Draw the plot, writing only the area that changed since the last update. Returns the area written
+    pub fn update(&mut self, display: &mut WS1in5, theme: &Theme) -> Result<Option<Rect>, Error> {
+        let image = render_widget(self, theme, false);
+        let changed = match &self.last {
+            Some(last) if last.dimensions() == image.dimensions() => changed_area(last, &image),
+            _ => Some(Rect::new(0, 0, image.width() as usize, image.height() as usize)),
+        };
+
+        if let Some(area) = changed {
+            let area = area.align_even().intersection(&Rect::new(0, 0, image.width() as usize, image.height() as usize)).unwrap_or(area);
+            let region = imageops::crop_imm(&image, area.x as u32, area.y as u32, area.w as u32, area.h as u32).to_image();
+            display.draw_image(self.bounds.x + area.x, self.bounds.y + area.y, &region)?;
+        }
+        self.last = Some(image);
+        Ok(changed.map(|area| Rect::new(self.bounds.x + area.x, self.bounds.y + area.y, area.w, area.h)))
+    }
+
+    /// The y axis range and the values of its ticks
+    fn axis(&self) -> (f64, f64, Vec<f64>) {
+        let (min, max) = self.range.unwrap_or_else(|| {
+            let values = self.series.iter().flat_map(|series| series.samples.iter().map(|(_, value)| *value));
+            values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| (min.min(value), max.max(value)))
+        });
+        let (min, max) = match (min.is_finite(), max.is_finite()) {
+            (true, true) if max > min => (min, max),
+            (true, true) => (min - 1.0, max + 1.0),
+            _ => (0.0, 1.0),
+        };
+
+        let step = nice_step((max - min) / self.ticks as f64);
+        let (min, max) = if self.range.is_some() { (min, max) } else { ((min / step).floor() * step, (max / step).ceil() * step) };
+        let ticks = (0..).map(|i| (min / step).ceil() * step + i as f64 * step).take_while(|tick| *tick <= max + step * 1e-6).collect();
+        (min, max, ticks)
+    }
+}
+
+/// A step of 1, 2 or 5 times a power of ten close to a rough step
+fn nice_step(rough: f64) -> f64 {
+    if !rough.is_finite() || rough <= 0.0 {
+        return 1.0
+    }
+    let power = 10f64.powf(rough.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0].into_iter().find(|step| step * power >= rough).unwrap_or(10.0);
+    step * power
+}
+
+/// A tick label, with as many decimals as the step between ticks needs
+fn label(value: f64, step: f64) -> String {
+    let decimals = if step >= 1.0 { 0 } else { (-step.log10().floor()) as usize };
+    format!("{:.*}", decimals, value)
+}
+
+/// The smallest area holding every pixel which differs between two images of the same size
+fn changed_area(old: &GrayImage, new: &GrayImage) -> Option<Rect> {
+    let mut area: Option<Rect> = None;
+    for (x, y, pixel) in new.enumerate_pixels() {
+        if old.get_pixel(x, y) != pixel {
+            let rect = Rect::new(x as usize, y as usize, 1, 1);
+            area = Some(area.map_or(rect, |area| area.union(&rect)));
+        }
+    }
+    area
+}
+
+impl<'a> Widget for Plot<'a> {
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: Rect) {
+        self.bounds = bounds;
+        self.last = None;
+    }
+
+    fn render(&self, canvas: &mut GrayImage, theme: &Theme) {
+        let (width, height) = (self.bounds.w as f32, self.bounds.h as f32);
+        if width < 8.0 || height < 8.0 {
+            return
+        }
+
+        let (min, max, ticks) = self.axis();
+        let step = if ticks.len() > 1 { ticks[1] - ticks[0] } else { max - min };
+        let labels: Vec<String> = ticks.iter().map(|tick| label(*tick, step)).collect();
+
+        // Room on the left for the labels, and half a label above and below so the end labels fit
+        let (label_width, label_height) = match &self.labels {
+            Some((scale, font)) => labels.iter()
+                .map(|label| text::text_size(label, scale, font))
+                .fold((0, 0), |(w, h), (lw, lh)| (w.max(lw), h.max(lh))),
+            None => (0, 0),
+        };
+        let left = (label_width + 3) as f32;
+        let top = (label_height / 2) as f32;
+        let bottom = height - 3.0 - (label_height / 2) as f32;
+        let right = width - 1.0;
+        if right - left < 2.0 || bottom - top < 2.0 {
+            return
+        }
+        let y_of = |value: f64| bottom - ((value - min) / (max - min)) as f32 * (bottom - top);
+        let axis = Luma([theme.foreground]);
+
+        drawing::draw_line_segment_mut(canvas, (left, top), (left, bottom), axis);
+        drawing::draw_line_segment_mut(canvas, (left, bottom), (right, bottom), axis);
+        for (tick, label) in ticks.iter().zip(labels.iter()) {
+            let y = y_of(*tick);
+            drawing::draw_line_segment_mut(canvas, (left - 2.0, y), (left, y), axis);
+            if let Some((scale, font)) = &self.labels {
+                let (label_w, _) = text::text_size(label, scale, font);
+                text::render_text(canvas, label_width as i32 - label_w as i32, y as i32 - (label_height / 2) as i32, label, scale, font, theme.foreground);
+            }
+        }
+        for i in 0..=4 {
+            let x = left + (right - left) * i as f32 / 4.0;
+            drawing::draw_line_segment_mut(canvas, (x, bottom), (x, bottom + 2.0), axis);
+        }
+
+        let Some(newest) = self.series.iter().filter_map(|series| series.samples.back().map(|(time, _)| *time)).max() else { return };
+        let window = self.window.as_secs_f32().max(f32::EPSILON);
+        let x_of = |time: Instant| right - newest.saturating_duration_since(time).as_secs_f32() / window * (right - left - 1.0);
+        for series in self.series.iter() {
+            // Another series' newer samples can leave this one's oldest outside the window
+            let points: Vec<(f32, f32)> = series.samples.iter()
+                .filter(|(time, _)| newest.saturating_duration_since(*time) <= self.window)
+                .map(|(time, value)| (x_of(*time), y_of(value.clamp(min, max))))
+                .collect();
+            for pair in points.windows(2) {
+                drawing::draw_line_segment_mut(canvas, pair[0], pair[1], Luma([series.level]));
+            }
+            if let [(x, y)] = points[..] {
+                canvas.put_pixel(x as u32, y as u32, Luma([series.level]));
+            }
+        }
+    }
+}
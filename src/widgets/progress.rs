@@ -0,0 +1,88 @@
+//! Progress bar
+
+use image::{GrayImage, Luma};
+use imageproc::{drawing, rect::Rect as DrawRect};
+use rusttype::{Scale, Font};
+
+use crate::{Rect, Theme, text};
+use super::Widget;
+
+/// A horizontal progress bar with an optional message above it
+pub struct ProgressBar<'a> {
+    bounds: Rect,
+    progress: f32,
+    message: String,
+    text: Option<(Scale, Font<'a>)>,
+}
+
+impl<'a> ProgressBar<'a> {
+    /// Create new, without a message
+    pub fn new(bounds: Rect) -> ProgressBar<'a> {
+        ProgressBar { bounds, progress: 0.0, message: String::new(), text: None }
+    }
+
+    /// Show a message above the bar
+    pub fn with_text(mut self, scale: Scale, font: Font<'a>) -> ProgressBar<'a> {
+        self.text = Some((scale, font));
+        self
+    }
+
+    /// Progress from 0 to 1
+    pub fn progress(&self) -> f32 {
+        self.progress
+    }
+
+    /// Set the progress, clamped from 0 to 1
+    pub fn set_progress(&mut self, progress: f32) {
+        self.progress = if progress.is_nan() { 0.0 } else { progress.clamp(0.0, 1.0) };
+    }
+
+    /// The message shown above the bar
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Set the message shown above the bar, ignored if the bar was created without text
+    pub fn set_message(&mut self, message: &str) {
+        self.message = message.to_string();
+    }
+}
+
+impl<'a> Widget for ProgressBar<'a> {
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: Rect) {
+        self.bounds = bounds;
+    }
+
+    fn render(&self, canvas: &mut GrayImage, theme: &Theme) {
+        let mut bar_y = 0;
+        if let Some((scale, font)) = &self.text {
+            let (_, height) = text::text_size(&self.message, scale, font);
+            text::render_text(canvas, 0, 0, &self.message, scale, font, theme.foreground);
+            bar_y = height.min(self.bounds.h);
+        }
+
+        let bar_h = self.bounds.h - bar_y;
+        if bar_h == 0 || self.bounds.w == 0 {
+            return
+        }
+
+        drawing::draw_hollow_rect_mut(
+            canvas,
+            DrawRect::at(0, bar_y as i32).of_size(self.bounds.w as u32, bar_h as u32),
+            Luma([theme.foreground])
+        );
+
+        let fill = ((self.bounds.w.saturating_sub(4)) as f32 * self.progress).round() as u32;
+        if fill > 0 && bar_h > 4 {
+            drawing::draw_filled_rect_mut(
+                canvas,
+                DrawRect::at(2, bar_y as i32 + 2).of_size(fill, bar_h as u32 - 4),
+                Luma([theme.foreground])
+            );
+        }
+    }
+}
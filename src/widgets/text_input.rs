@@ -0,0 +1,216 @@
+//! On screen keyboard for entering text with an encoder or a few buttons
+
+use image::{GrayImage, Luma};
+use imageproc::{drawing, rect::Rect as DrawRect};
+use rusttype::{Scale, Font};
+
+use crate::{Rect, Theme, text};
+use super::Widget;
+
+/// Characters on each page of the keyboard, switched between with the page key
+const PAGES: [&str; 3] = [
+    "abcdefghijklmnopqrstuvwxyz",
+    "ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+    "0123456789!@#$%&*()-_=+.,:;/?'\"<>[]{}\\|~`^",
+];
+/// Labels of the page key, naming the page it switches to
+const PAGE_LABELS: [&str; 3] = ["ABC", "123", "abc"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Char(char),
+    Page,
+    Space,
+    Delete,
+    Done,
+}
+
+/// A grid of characters with page, space, delete and done keys along the bottom, under the text entered so far
+///
+/// Next and previous move through the keys in reading order, selecting presses the key. See [`Ui::read_text`](crate::Ui::read_text)
+/// to run it from the UI's inputs.
+pub struct TextInput<'a> {
+    bounds: Rect,
+    scale: Scale,
+    font: Font<'a>,
+    text: String,
+    max_len: Option<usize>,
+    mask: Option<char>,
+    page: usize,
+    selected: usize,
+    done: bool,
+}
+
+impl<'a> TextInput<'a> {
+    /// Create new, with nothing entered
+    pub fn new(bounds: Rect, scale: Scale, font: Font<'a>) -> TextInput<'a> {
+        TextInput { bounds, scale, font, text: String::new(), max_len: None, mask: None, page: 0, selected: 0, done: false }
+    }
+
+    /// Start with some text entered, e.g. the current value being edited
+    pub fn with_text(mut self, text: &str) -> TextInput<'a> {
+        self.text = text.to_string();
+        self
+    }
+
+    /// Stop accepting characters after a length
+    pub fn with_max_len(mut self, max_len: usize) -> TextInput<'a> {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// Show every entered character as a mask character, e.g. `*` for passwords
+    pub fn with_mask(mut self, mask: char) -> TextInput<'a> {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// The text entered so far
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns true once the done key has been pressed
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Clear the text and start again, e.g. to reuse the keyboard
+    pub fn reset(&mut self) {
+        self.text.clear();
+        self.page = 0;
+        self.selected = 0;
+        self.done = false;
+    }
+
+    /// Move to the next key, wrapping around
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % self.keys().len();
+    }
+
+    /// Move to the previous key, wrapping around
+    pub fn prev(&mut self) {
+        let len = self.keys().len();
+        self.selected = (self.selected + len - 1) % len;
+    }
+
+    /// Remove the last character entered. Returns false if there was nothing to remove
+    pub fn delete(&mut self) -> bool {
+        self.text.pop().is_some()
+    }
+
+    /// Press the selected key
+    pub fn press(&mut self) {
+        match self.keys()[self.selected] {
+            Key::Char(char) => self.push(char),
+            Key::Space => self.push(' '),
+            Key::Page => {
+                // Stay on the page key, which moves with the number of characters on the page
+                self.page = (self.page + 1) % PAGES.len();
+                self.selected = PAGES[self.page].chars().count();
+            },
+            Key::Delete => {
+                self.delete();
+            },
+            Key::Done => self.done = true,
+        }
+    }
+
+    fn push(&mut self, char: char) {
+        if self.max_len.is_none_or(|max_len| self.text.chars().count() < max_len) {
+            self.text.push(char);
+        }
+    }
+
+    fn keys(&self) -> Vec<Key> {
+        PAGES[self.page].chars().map(Key::Char)
+            .chain([Key::Page, Key::Space, Key::Delete, Key::Done])
+            .collect()
+    }
+
+    /// Size of a character key, and the height of the text line
+    fn cell(&self) -> (usize, usize) {
+        let (width, height) = text::text_size("_", &self.scale, &self.font);
+        (width + 4, height + 2)
+    }
+
+    fn render_key(&self, canvas: &mut GrayImage, area: Rect, label: &str, selected: bool, theme: &Theme) {
+        let color = if selected {
+            drawing::draw_filled_rect_mut(canvas, DrawRect::at(area.x as i32, area.y as i32).of_size(area.w as u32, area.h as u32), Luma([theme.foreground]));
+            theme.background
+        } else {
+            theme.foreground
+        };
+        let (width, height) = text::text_size(label, &self.scale, &self.font);
+        let x = area.x as i32 + (area.w as i32 - width as i32) / 2;
+        let y = area.y as i32 + (area.h as i32 - height as i32) / 2;
+        text::render_text(canvas, x, y, label, &self.scale, &self.font, color);
+    }
+}
+
+impl<'a> Widget for TextInput<'a> {
+    fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    fn set_bounds(&mut self, bounds: Rect) {
+        self.bounds = bounds;
+    }
+
+    fn content_size(&self) -> (usize, usize) {
+        // Ten keys across fits the letters in three rows
+        let (cell_width, cell_height) = self.cell();
+        (cell_width * 10, cell_height * 5 + 2)
+    }
+
+    fn render(&self, canvas: &mut GrayImage, theme: &Theme) {
+        let (cell_width, cell_height) = self.cell();
+        let (char_width, _) = text::text_size("_", &self.scale, &self.font);
+
+        // The end of the text and a cursor, scrolled to fit
+        let shown: String = match self.mask {
+            Some(mask) => self.text.chars().map(|_| mask).collect(),
+            None => self.text.clone(),
+        };
+        let fits = (self.bounds.w / char_width.max(1)).saturating_sub(1);
+        let skip = shown.chars().count().saturating_sub(fits);
+        let line: String = shown.chars().skip(skip).chain(Some('_')).collect();
+        text::render_text(canvas, 0, 0, &line, &self.scale, &self.font, theme.foreground);
+        drawing::draw_line_segment_mut(canvas, (0.0, cell_height as f32), (self.bounds.w as f32 - 1.0, cell_height as f32), Luma([theme.foreground]));
+
+        let keys = self.keys();
+        let chars = keys.len() - 4;
+        let columns = (self.bounds.w / cell_width).max(1);
+        let top = cell_height + 2;
+        // Rows of characters that fit above the bottom row, scrolled to keep the selected key in view
+        let rows = ((self.bounds.h.saturating_sub(top) / cell_height).saturating_sub(1)).max(1);
+        let selected_row = self.selected.min(chars.saturating_sub(1)) / columns;
+        let first_row = selected_row.saturating_sub(rows - 1);
+
+        for (i, key) in keys[..chars].iter().enumerate() {
+            let row = i / columns;
+            if row < first_row || row >= first_row + rows {
+                continue
+            }
+            let Key::Char(char) = key else { continue };
+            let area = Rect::new((i % columns) * cell_width, top + (row - first_row) * cell_height, cell_width, cell_height);
+            self.render_key(canvas, area, &char.to_string(), i == self.selected, theme);
+        }
+
+        let bottom = top + rows * cell_height;
+        let width = self.bounds.w / 4;
+        for (i, label) in [PAGE_LABELS[self.page], "spc", "del", "ok"].iter().enumerate() {
+            let area = Rect::new(i * width, bottom, width, cell_height);
+            self.render_key(canvas, area, label, chars + i == self.selected, theme);
+        }
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn select(&mut self) -> bool {
+        self.press();
+        true
+    }
+}
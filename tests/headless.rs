@@ -0,0 +1,19 @@
+//! Frames saved by the headless simulator
+
+use std::time::Duration;
+
+use ws_1in5_i2c::{WS1in5, TransferConfig, headless::HeadlessSimulator};
+
+#[test]
+fn chunked_flush_saves_one_frame() {
+    let directory = std::env::temp_dir().join(format!("ws1in5-headless-{}", std::process::id()));
+    let simulator = HeadlessSimulator::new(&directory).unwrap();
+    let mut screen = WS1in5::with_interface(Box::new(simulator)).unwrap()
+        .with_transfer_config(TransferConfig::new(32, Duration::ZERO));
+
+    screen.flush().unwrap();
+
+    let frames = std::fs::read_dir(&directory).unwrap().count();
+    std::fs::remove_dir_all(&directory).unwrap();
+    assert_eq!(frames, 1);
+}
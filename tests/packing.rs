@@ -0,0 +1,57 @@
+//! Round trips between greyscale images and the screen's packed layout
+
+use image::{GrayImage, Luma};
+use ws_1in5_i2c::{images, Framebuffer, Error, DriverError};
+
+fn pattern(width: u32, height: u32) -> GrayImage {
+    GrayImage::from_fn(width, height, |x, y| Luma([((x * 7 + y * 3) % 16) as u8]))
+}
+
+#[test]
+fn pack_then_unpack_is_lossless() {
+    for (width, height) in [(128, 128), (2, 1), (64, 3), (10, 17)] {
+        let image = pattern(width, height);
+        let packed = images::pack(&image);
+        assert_eq!(packed.len(), (width / 2 * height) as usize);
+
+        let unpacked = images::unpack_buffer(&packed, width as usize, height as usize).unwrap();
+        assert_eq!(unpacked, image);
+    }
+}
+
+#[test]
+fn unpack_then_pack_is_lossless() {
+    let packed: Vec<u8> = (0..=255).collect();
+    let image = images::unpack_buffer(&packed, 32, 16).unwrap();
+    assert_eq!(images::pack(&image), packed);
+}
+
+#[test]
+fn even_pixel_is_the_high_nibble() {
+    let image = images::unpack_buffer(&[0xa5], 2, 1).unwrap();
+    assert_eq!(image.get_pixel(0, 0).0[0], 0x0a);
+    assert_eq!(image.get_pixel(1, 0).0[0], 0x05);
+}
+
+#[test]
+fn odd_last_column_is_dropped() {
+    let image = pattern(5, 4);
+    let unpacked = images::unpack_buffer(&images::pack(&image), 5, 4).unwrap();
+    for (x, y, pixel) in unpacked.enumerate_pixels() {
+        let expected = if x == 4 { 0 } else { image.get_pixel(x, y).0[0] };
+        assert_eq!(pixel.0[0], expected);
+    }
+}
+
+#[test]
+fn short_buffer_is_out_of_bounds() {
+    assert!(matches!(images::unpack_buffer(&[0; 7], 4, 4), Err(Error::Driver(DriverError::OutOfBounds { len: 7, .. }))));
+}
+
+#[test]
+fn matches_the_framebuffer_layout() {
+    let image = pattern(16, 8);
+    let framebuffer = Framebuffer::from_image(&image);
+    assert_eq!(framebuffer.as_bytes(), images::pack(&image).as_slice());
+    assert_eq!(images::unpack_buffer(framebuffer.as_bytes(), 16, 8).unwrap(), framebuffer.to_image());
+}
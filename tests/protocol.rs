@@ -0,0 +1,59 @@
+//! Round trips through the remote display protocol
+
+use ws_1in5_i2c::{Framebuffer, Error, protocol::{self, Message, Compression}};
+
+fn pattern(width: usize, height: usize) -> Framebuffer {
+    let mut frame = Framebuffer::new(width, height);
+    frame.as_bytes_mut().iter_mut().enumerate().for_each(|(i, byte)| *byte = (i / 7) as u8);
+    frame
+}
+
+fn round_trip(message: &Message, compression: Compression) -> Message {
+    let mut stream = vec![];
+    protocol::write_message(&mut stream, message, compression).unwrap();
+    protocol::read_message(&mut stream.as_slice()).unwrap()
+}
+
+#[test]
+fn frames_keep_their_size() {
+    for (width, height) in [(128, 128), (128, 96), (96, 64), (64, 64)] {
+        let message = Message::Frame(pattern(width, height));
+        assert_eq!(round_trip(&message, Compression::None), message);
+    }
+}
+
+#[cfg(feature = "compression")]
+#[test]
+fn compressed_frames_round_trip() {
+    for compression in [Compression::Deflate, Compression::Lz4] {
+        for (width, height) in [(128, 128), (64, 64)] {
+            let message = Message::Frame(pattern(width, height));
+            assert_eq!(round_trip(&message, compression), message);
+        }
+        let message = Message::Region { rect: ws_1in5_i2c::Rect::new(2, 4, 16, 8), data: vec![0x11; 64] };
+        assert_eq!(round_trip(&message, compression), message);
+    }
+}
+
+#[test]
+fn frame_larger_than_the_controller_is_rejected() {
+    let mut stream = vec![];
+    protocol::write_message(&mut stream, &Message::Frame(pattern(130, 2)), Compression::None).unwrap();
+    assert!(matches!(protocol::read_message(&mut stream.as_slice()), Err(Error::Protocol(_))));
+}
+
+#[test]
+fn malformed_frame_is_a_protocol_error() {
+    for (width, height, len) in [(3, 2, 3), (4, 2, 3)] {
+        let mut payload = vec![];
+        for value in [width as u16, height as u16] {
+            payload.extend_from_slice(&value.to_be_bytes());
+        }
+        payload.extend(std::iter::repeat_n(0, len));
+
+        let mut stream = vec![0x01, 0];
+        stream.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        stream.extend_from_slice(&payload);
+        assert!(matches!(protocol::read_message(&mut stream.as_slice()), Err(Error::Protocol(_))));
+    }
+}